@@ -35,5 +35,6 @@ pub fn check(
         }),
         actual: serde_json::json!(bit_value),
         summary,
+        children: Vec::new(),
     })
 }
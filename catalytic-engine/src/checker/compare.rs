@@ -1,7 +1,7 @@
 //! 双变量比较
 
-use crate::checker::CheckOutput;
-use crate::model::{CompareOp, VariablePool};
+use crate::checker::{to_unit, CheckOutput};
+use crate::model::{CompareOp, Variable, VariablePool};
 use crate::error::{EngineError, Result};
 
 /// 双变量比较
@@ -11,16 +11,27 @@ pub fn check(
     operator: &CompareOp,
     variables: &VariablePool,
 ) -> Result<CheckOutput> {
-    let val_a = variables.get(var_a)
-        .ok_or_else(|| EngineError::CheckError(format!("变量 '{}' 不存在", var_a)))?
-        .as_f64()
-        .ok_or_else(|| EngineError::CheckError("变量无法转换为数值".to_string()))?;
+    let a = variables.get(var_a)
+        .ok_or_else(|| EngineError::CheckError(format!("变量 '{}' 不存在", var_a)))?;
+    let b = variables.get(var_b)
+        .ok_or_else(|| EngineError::CheckError(format!("变量 '{}' 不存在", var_b)))?;
+
+    // 字符串、或两侧均为布尔时按文本比较；布尔与数值比较时布尔按 0/1 参与数值比较
+    if matches!(
+        (a, b),
+        (Variable::String(_), _) | (_, Variable::String(_)) | (Variable::Bool(_), Variable::Bool(_))
+    ) {
+        return check_text(var_a, a, var_b, b, operator);
+    }
 
-    let val_b = variables.get(var_b)
-        .ok_or_else(|| EngineError::CheckError(format!("变量 '{}' 不存在", var_b)))?
-        .as_f64()
+    let val_a = a.as_f64()
+        .ok_or_else(|| EngineError::CheckError("变量无法转换为数值".to_string()))?;
+    let val_b = b.as_f64()
         .ok_or_else(|| EngineError::CheckError("变量无法转换为数值".to_string()))?;
 
+    // 两者均带单位时将 b 换算到 a 的单位
+    let val_b = to_unit(val_b, variables.unit(var_b), variables.unit(var_a))?;
+
     let passed = operator.compare(val_a, val_b);
     let op_str = operator.as_str();
 
@@ -36,5 +47,54 @@ pub fn check(
         params: serde_json::json!({"var_a": var_a, "operator": op_str, "var_b": var_b}),
         actual: serde_json::json!({"a": val_a, "b": val_b}),
         summary,
+        children: Vec::new(),
     })
 }
+
+/// 文本比较（仅支持 == 与 !=）
+fn check_text(var_a: &str, a: &Variable, var_b: &str, b: &Variable, operator: &CompareOp) -> Result<CheckOutput> {
+    let (text_a, text_b) = (a.as_string(), b.as_string());
+    let passed = match operator {
+        CompareOp::Eq => text_a == text_b,
+        CompareOp::Ne => text_a != text_b,
+        other => {
+            return Err(EngineError::CheckError(format!("文本变量不支持运算符 {}", other.as_str())));
+        }
+    };
+    let op_str = operator.as_str();
+
+    let summary = if passed {
+        format!("{} ('{}') {} {} ('{}') → PASS", var_a, text_a, op_str, var_b, text_b)
+    } else {
+        format!("{} ('{}') {} {} ('{}') → FAIL", var_a, text_a, op_str, var_b, text_b)
+    };
+
+    Ok(CheckOutput {
+        passed,
+        template: "compare".to_string(),
+        params: serde_json::json!({"var_a": var_a, "operator": op_str, "var_b": var_b}),
+        actual: serde_json::json!({"a": text_a, "b": text_b}),
+        summary,
+        children: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bool_compared_with_number() {
+        let mut pool = VariablePool::new();
+        pool.set("flag", Variable::Bool(true));
+        pool.set("one", Variable::Int(1));
+        pool.set("zero", Variable::Float(0.0));
+        pool.set("other", Variable::Bool(true));
+
+        assert!(check("flag", "one", &CompareOp::Eq, &pool).unwrap().passed);
+        assert!(!check("flag", "zero", &CompareOp::Eq, &pool).unwrap().passed);
+        assert!(check("flag", "zero", &CompareOp::Gt, &pool).unwrap().passed);
+        assert!(check("flag", "other", &CompareOp::Eq, &pool).unwrap().passed);
+        assert!(check("flag", "other", &CompareOp::Gt, &pool).is_err());
+    }
+}
@@ -31,5 +31,6 @@ pub fn check(
         }),
         actual: serde_json::json!(val),
         summary,
+        children: Vec::new(),
     })
 }
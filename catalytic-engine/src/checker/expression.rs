@@ -1,30 +1,156 @@
-//! 表达式检查
+//! 表达式检查与求值
 
-use crate::checker::CheckOutput;
-use crate::model::VariablePool;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::checker::{functions, CheckOutput};
+use crate::model::{Variable, VariablePool};
 use crate::error::{EngineError, Result};
 use evalexpr::*;
 
+/// 表达式可访问的槽位信息
+///
+/// 变量池中没有同名变量时，表达式中的 `slot_id` 与 `sn` 取自这里
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExprScope<'a> {
+    pub slot_id: Option<u32>,
+    pub sn: Option<&'a str>,
+}
+
+/// 预编译的表达式
+#[derive(Debug, Clone)]
+pub struct CompiledExpr {
+    source: String,
+    node: Node,
+    /// 表达式引用的变量名（求值时只填充这些变量）
+    identifiers: Vec<String>,
+}
+
+impl CompiledExpr {
+    /// 编译表达式
+    pub fn compile(expr: &str) -> Result<Self> {
+        let node = build_operator_tree(expr)
+            .map_err(|e| EngineError::ExpressionError(format!("表达式编译失败 '{}': {}", expr, e)))?;
+        let mut identifiers: Vec<String> = node.iter_variable_identifiers().map(str::to_string).collect();
+        identifiers.sort();
+        identifiers.dedup();
+        Ok(Self { source: expr.to_string(), node, identifiers })
+    }
+
+    /// 以变量池为上下文求布尔值
+    pub fn eval_boolean(&self, variables: &VariablePool, scope: &ExprScope) -> Result<bool> {
+        self.node.eval_boolean_with_context(&self.context(variables, scope)?)
+            .map_err(|e| self.eval_error(e))
+    }
+
+    /// 以变量池为上下文求值（计算步骤）
+    pub fn eval(&self, variables: &VariablePool, scope: &ExprScope) -> Result<Variable> {
+        let value = self.node.eval_with_context(&self.context(variables, scope)?)
+            .map_err(|e| self.eval_error(e))?;
+        match value {
+            Value::Float(v) => Ok(Variable::Float(v)),
+            Value::Int(v) => Ok(Variable::Int(v)),
+            Value::Boolean(b) => Ok(Variable::Bool(b)),
+            Value::String(s) => Ok(Variable::String(s)),
+            Value::Tuple(items) => items.iter()
+                .map(|v| v.as_number().ok())
+                .collect::<Option<Vec<_>>>()
+                .map(Variable::FloatArray)
+                .ok_or_else(|| EngineError::ExpressionError(format!("表达式 '{}' 的结果不是数值数组", self.source))),
+            Value::Empty => Err(EngineError::ExpressionError(format!("表达式 '{}' 没有结果", self.source))),
+        }
+    }
+
+    fn eval_error(&self, e: EvalexprError) -> EngineError {
+        match e {
+            EvalexprError::FunctionIdentifierNotFound(name) => {
+                EngineError::ExpressionError(format!("表达式 '{}' 调用了未知函数 '{}'", self.source, name))
+            }
+            other => EngineError::ExpressionError(format!("表达式求值失败 '{}': {}", self.source, other)),
+        }
+    }
+
+    /// 只填充表达式引用到的变量，未定义的变量直接报错
+    fn context(&self, variables: &VariablePool, scope: &ExprScope) -> Result<EvalContext> {
+        let mut context = EvalContext::default();
+        let mut missing = Vec::new();
+        for name in &self.identifiers {
+            let value = match variables.get(name) {
+                Some(var) => to_value(var),
+                None => match (name.as_str(), scope.slot_id, scope.sn) {
+                    ("slot_id", Some(id), _) => Value::Int(id as IntType),
+                    ("sn", _, Some(sn)) => Value::String(sn.to_string()),
+                    _ => {
+                        missing.push(name.as_str());
+                        continue;
+                    }
+                },
+            };
+            context.values.insert(name.clone(), value);
+        }
+
+        if !missing.is_empty() {
+            return Err(EngineError::ExpressionError(format!(
+                "表达式 '{}' 引用了未定义的变量: {}", self.source, missing.join(", ")
+            )));
+        }
+        Ok(context)
+    }
+}
+
+/// 单次求值的上下文：变量按需填充，函数取自共享的内置函数表
+#[derive(Default)]
+struct EvalContext {
+    values: HashMap<String, Value>,
+}
+
+impl Context for EvalContext {
+    fn get_value(&self, identifier: &str) -> Option<&Value> {
+        self.values.get(identifier)
+    }
+
+    fn call_function(&self, identifier: &str, argument: &Value) -> EvalexprResult<Value> {
+        functions::call(identifier, argument)
+    }
+
+    fn are_builtin_functions_disabled(&self) -> bool {
+        false
+    }
+
+    fn set_builtin_functions_disabled(&mut self, disabled: bool) -> EvalexprResult<()> {
+        if disabled {
+            return Err(EvalexprError::ContextNotMutable);
+        }
+        Ok(())
+    }
+}
+
+/// 变量转换为表达式值（字节与浮点数组转换为元组）
+fn to_value(var: &Variable) -> Value {
+    match var {
+        Variable::Int(v) => Value::Int(*v),
+        Variable::Float(v) => Value::Float(*v),
+        Variable::Bool(b) => Value::Boolean(*b),
+        Variable::String(s) => Value::String(s.clone()),
+        Variable::Bytes(bytes) => Value::Tuple(bytes.iter().map(|b| Value::Int(*b as IntType)).collect()),
+        Variable::FloatArray(values) => Value::Tuple(values.iter().map(|v| Value::Float(*v)).collect()),
+    }
+}
+
 /// 表达式检查
+///
+/// compiled 中存在该表达式时直接使用，否则即时编译
 pub fn check(
     expr: &str,
     variables: &VariablePool,
+    scope: &ExprScope,
+    compiled: Option<&HashMap<String, CompiledExpr>>,
 ) -> Result<CheckOutput> {
-    // 创建上下文并填充变量
-    let mut context = HashMapContext::new();
-    
-    for name in variables.keys() {
-        if let Some(var) = variables.get(name) {
-            if let Some(val) = var.as_f64() {
-                context.set_value(name.clone(), Value::Float(val))
-                    .map_err(|e| EngineError::ExpressionError(format!("设置变量失败: {}", e)))?;
-            }
-        }
-    }
-
-    // 求值
-    let result = eval_boolean_with_context(expr, &context)
-        .map_err(|e| EngineError::ExpressionError(format!("表达式求值失败: {}", e)))?;
+    let compiled = match compiled.and_then(|c| c.get(expr)) {
+        Some(c) => Cow::Borrowed(c),
+        None => Cow::Owned(CompiledExpr::compile(expr)?),
+    };
+    let result = compiled.eval_boolean(variables, scope)?;
 
     let summary = if result {
         format!("{} → PASS", expr)
@@ -38,13 +164,13 @@ pub fn check(
         params: serde_json::json!({"expr": expr}),
         actual: serde_json::json!(result),
         summary,
+        children: Vec::new(),
     })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::Variable;
 
     #[test]
     fn test_expression_check() {
@@ -52,7 +178,7 @@ mod tests {
         pool.set("voltage", Variable::Float(3.31));
         pool.set("threshold", Variable::Float(3.0));
 
-        let result = check("voltage > threshold", &pool).unwrap();
+        let result = check("voltage > threshold", &pool, &ExprScope::default(), None).unwrap();
         assert!(result.passed);
     }
 
@@ -62,7 +188,95 @@ mod tests {
         pool.set("a", Variable::Float(10.0));
         pool.set("b", Variable::Float(20.0));
 
-        let result = check("(a + b) > 25", &pool).unwrap();
+        let result = check("(a + b) > 25", &pool, &ExprScope::default(), None).unwrap();
+        assert!(result.passed);
+    }
+
+    #[test]
+    fn test_string_and_bool_expression() {
+        let mut pool = VariablePool::new();
+        pool.set("fw", Variable::String("1.2.3".into()));
+        pool.set("locked", Variable::Bool(true));
+
+        let result = check("fw == \"1.2.3\" && locked", &pool, &ExprScope::default(), None).unwrap();
         assert!(result.passed);
     }
+
+    #[test]
+    fn test_compiled_expression_reused() {
+        let compiled = CompiledExpr::compile("a > b && a < 10").unwrap();
+        assert_eq!(compiled.identifiers, vec!["a".to_string(), "b".to_string()]);
+
+        let cache = HashMap::from([("a > b && a < 10".to_string(), compiled)]);
+        let mut pool = VariablePool::new();
+        pool.set("a", Variable::Float(5.0));
+        pool.set("b", Variable::Int(2));
+        pool.set("unused", Variable::Bytes(vec![1, 2]));
+        assert!(check("a > b && a < 10", &pool, &ExprScope::default(), Some(&cache)).unwrap().passed);
+
+        assert!(CompiledExpr::compile("(a > 1").is_err());
+    }
+
+    #[test]
+    fn test_eval_value() {
+        let mut pool = VariablePool::new();
+        pool.set("v", Variable::Float(3.3));
+        pool.set("i", Variable::Float(0.5));
+
+        let power = CompiledExpr::compile("v * i").unwrap().eval(&pool, &ExprScope::default()).unwrap();
+        assert!(matches!(power, Variable::Float(p) if (p - 1.65).abs() < 1e-12));
+        assert!(matches!(CompiledExpr::compile("2 + 3").unwrap().eval(&pool, &ExprScope::default()), Ok(Variable::Int(5))));
+        assert!(matches!(CompiledExpr::compile("v > i").unwrap().eval(&pool, &ExprScope::default()), Ok(Variable::Bool(true))));
+        assert!(CompiledExpr::compile("missing * 2").unwrap().eval(&pool, &ExprScope::default()).is_err());
+    }
+
+    #[test]
+    fn test_typed_variables_and_functions() {
+        let mut pool = VariablePool::new();
+        pool.set("count", Variable::Int(7));
+        pool.set("status", Variable::Bytes(vec![0x00, 0x05]));
+        pool.set("wave", Variable::FloatArray(vec![1.0, -3.0, 2.0]));
+        pool.set("reg", Variable::String("0x1F".into()));
+        let scope = ExprScope::default();
+        let eval = |expr: &str| CompiledExpr::compile(expr).unwrap().eval(&pool, &scope);
+
+        assert!(matches!(eval("count / 2"), Ok(Variable::Int(3))));
+        assert!(matches!(eval("len(wave)"), Ok(Variable::Int(3))));
+        assert!(matches!(eval("max(wave)"), Ok(Variable::Float(v)) if v == 2.0));
+        assert!(matches!(eval("min(wave, -5)"), Ok(Variable::Float(v)) if v == -5.0));
+        assert!(matches!(eval("abs(min(wave))"), Ok(Variable::Float(v)) if v == 3.0));
+        assert!(matches!(eval("mean(wave)"), Ok(Variable::Float(v)) if v == 0.0));
+        assert!(matches!(eval("max(1, 4, 2)"), Ok(Variable::Int(4))));
+        assert!(matches!(eval("hex(reg)"), Ok(Variable::Int(31))));
+        assert!(matches!(eval("bit(hex(reg), 4) && !bit(count, 3)"), Ok(Variable::Bool(true))));
+        assert!(matches!(eval("matches(\"MY54001234\", \"^MY\\\\d+$\")"), Ok(Variable::Bool(true))));
+        assert!(matches!(eval("(1, 2.5)"), Ok(Variable::FloatArray(v)) if v == vec![1.0, 2.5]));
+        assert_eq!(eval("len(status)").unwrap().as_i64(), Some(2));
+
+        // 超过 2^53 的整数按整数比较，不经浮点转换
+        let big = (1i64 << 53) + 1;
+        pool.set("big", Variable::Int(big));
+        let eval = |expr: &str| CompiledExpr::compile(expr).unwrap().eval(&pool, &scope);
+        assert!(matches!(eval("max(big, 9007199254740992)"), Ok(Variable::Int(v)) if v == big));
+        assert!(matches!(eval("min(big, big + 1)"), Ok(Variable::Int(v)) if v == big));
+        assert!(eval("matches(\"A\", \"(\")").is_err());
+        assert!(matches!(eval("matches(\"B12\", \"^MY\\\\d+$\")"), Ok(Variable::Bool(false))));
+    }
+
+    #[test]
+    fn test_slot_scope_and_errors() {
+        let mut pool = VariablePool::new();
+        pool.set("v", Variable::Float(3.3));
+        let scope = ExprScope { slot_id: Some(2), sn: Some("P5-0001") };
+
+        let rule = "slot_id == 2 && matches(sn, \"^P5-\")";
+        assert!(check(rule, &pool, &scope, None).unwrap().passed);
+        let err = check(rule, &pool, &ExprScope::default(), None).err().unwrap().to_string();
+        assert!(err.contains("未定义的变量: slot_id, sn"), "{}", err);
+
+        let err = check("vv > 3", &pool, &scope, None).err().unwrap().to_string();
+        assert!(err.contains("'vv > 3'") && err.contains("vv"), "{}", err);
+        let err = check("avg(v) > 3", &pool, &scope, None).err().unwrap().to_string();
+        assert!(err.contains("未知函数 'avg'"), "{}", err);
+    }
 }
@@ -0,0 +1,62 @@
+//! 外部检查（由 Host 判定）
+
+use serde::{Deserialize, Serialize};
+
+use crate::checker::CheckOutput;
+use crate::error::{EngineError, Result};
+
+/// Host 提交的外部检查结果
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExternalCheckResult {
+    pub passed: bool,
+    /// 实际值（任意 JSON）
+    #[serde(default)]
+    pub actual: serde_json::Value,
+    /// 结果摘要（为空时自动生成）
+    #[serde(default)]
+    pub summary: String,
+}
+
+impl ExternalCheckResult {
+    /// 从提交的数据解析
+    pub fn from_slice(data: &[u8]) -> Result<Self> {
+        serde_json::from_slice(data)
+            .map_err(|e| EngineError::CheckError(format!("外部检查结果无效: {}", e)))
+    }
+
+    /// 转换为检查输出
+    pub fn into_output(self) -> CheckOutput {
+        let verdict = if self.passed { "PASS" } else { "FAIL" };
+        let summary = match self.summary.trim() {
+            "" if self.actual.is_null() => format!("外部检查 → {}", verdict),
+            "" => format!("外部检查: {} → {}", self.actual, verdict),
+            _ => self.summary,
+        };
+
+        CheckOutput {
+            passed: self.passed,
+            template: "external".to_string(),
+            params: serde_json::json!({}),
+            actual: self.actual,
+            summary,
+            children: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_external_result_summary() {
+        let out = ExternalCheckResult::from_slice(br#"{"passed":false,"actual":42}"#).unwrap().into_output();
+        assert!(!out.passed);
+        assert_eq!(out.summary, "外部检查: 42 → FAIL");
+
+        let out = ExternalCheckResult::from_slice(r#"{"passed":true,"summary":"光斑居中"}"#.as_bytes()).unwrap().into_output();
+        assert_eq!((out.passed, out.summary.as_str()), (true, "光斑居中"));
+
+        assert!(ExternalCheckResult::from_slice(b"ok").is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! 表达式内置函数
+//!
+//! | 函数 | 说明 |
+//! |------|------|
+//! | `abs(x)` | 绝对值 |
+//! | `min(...)` / `max(...)` | 最小 / 最大值，参数可为数值或数组 |
+//! | `mean(...)` | 平均值，参数可为数值或数组 |
+//! | `len(x)` | 字符串字符数或数组长度 |
+//! | `bit(x, n)` | 整数 x 的第 n 位是否为 1 |
+//! | `hex(s)` | 解析十六进制字符串（可带 0x 前缀） |
+//! | `matches(s, pattern)` | 正则匹配 |
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use evalexpr::*;
+use parking_lot::Mutex;
+use regex::Regex;
+
+/// 内置函数表（进程内只构建一次，所有表达式共享）
+static FUNCTIONS: LazyLock<HashMapContext> = LazyLock::new(|| {
+    let mut context = HashMapContext::new();
+    register(&mut context).expect("内置函数注册失败");
+    context
+});
+
+/// matches() 的正则缓存上限，超出时整体清空
+const REGEX_CACHE_CAPACITY: usize = 64;
+
+/// matches() 的正则缓存（按模式串）
+static REGEX_CACHE: LazyLock<Mutex<HashMap<String, Regex>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 调用内置函数
+pub fn call(identifier: &str, argument: &Value) -> EvalexprResult<Value> {
+    FUNCTIONS.call_function(identifier, argument)
+}
+
+/// 取出（或编译并缓存）正则
+fn cached_regex(pattern: &str) -> EvalexprResult<Regex> {
+    let mut cache = REGEX_CACHE.lock();
+    if let Some(re) = cache.get(pattern) {
+        return Ok(re.clone());
+    }
+    let re = Regex::new(pattern)
+        .map_err(|e| EvalexprError::CustomMessage(format!("matches() 正则无效 '{}': {}", pattern, e)))?;
+    if cache.len() >= REGEX_CACHE_CAPACITY {
+        cache.clear();
+    }
+    cache.insert(pattern.to_string(), re.clone());
+    Ok(re)
+}
+
+/// 将参数（可嵌套数组）展开为数值列表
+fn numbers(argument: &Value, out: &mut Vec<FloatType>) -> EvalexprResult<()> {
+    match argument {
+        Value::Tuple(items) => items.iter().try_for_each(|item| numbers(item, out)),
+        Value::Empty => Ok(()),
+        other => {
+            out.push(other.as_number()?);
+            Ok(())
+        }
+    }
+}
+
+/// 展开后非空的数值列表
+fn non_empty(name: &str, argument: &Value) -> EvalexprResult<Vec<FloatType>> {
+    let mut values = Vec::new();
+    numbers(argument, &mut values)?;
+    if values.is_empty() {
+        return Err(EvalexprError::CustomMessage(format!("{}() 需要至少一个数值", name)));
+    }
+    Ok(values)
+}
+
+/// 将参数展开为整数列表；含非整数时返回 None
+fn ints(argument: &Value, out: &mut Vec<IntType>) -> bool {
+    match argument {
+        Value::Tuple(items) => items.iter().all(|item| ints(item, out)),
+        Value::Int(v) => {
+            out.push(*v);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// 最小 / 最大值：全部为整数时按整数比较，避免大于 2^53 的整数经浮点转换丢失精度
+fn extreme(
+    name: &'static str,
+    pick_int: fn(IntType, IntType) -> IntType,
+    pick: fn(FloatType, FloatType) -> FloatType,
+) -> Function {
+    Function::new(move |argument| {
+        let mut values = Vec::new();
+        if ints(argument, &mut values) {
+            return match values.into_iter().reduce(pick_int) {
+                Some(v) => Ok(Value::Int(v)),
+                None => Err(EvalexprError::CustomMessage(format!("{}() 需要至少一个数值", name))),
+            };
+        }
+        let value = non_empty(name, argument)?.into_iter().reduce(pick).unwrap_or_default();
+        Ok(Value::Float(value))
+    })
+}
+
+/// 向上下文注册内置函数（同名时覆盖 evalexpr 自带函数）
+fn register(context: &mut HashMapContext) -> EvalexprResult<()> {
+    context.set_function("abs".into(), Function::new(|argument| match argument {
+        Value::Int(v) => Ok(Value::Int(v.abs())),
+        other => Ok(Value::Float(other.as_number()?.abs())),
+    }))?;
+    context.set_function("min".into(), extreme("min", IntType::min, FloatType::min))?;
+    context.set_function("max".into(), extreme("max", IntType::max, FloatType::max))?;
+    context.set_function("mean".into(), Function::new(|argument| {
+        let values = non_empty("mean", argument)?;
+        Ok(Value::Float(values.iter().sum::<FloatType>() / values.len() as FloatType))
+    }))?;
+    context.set_function("len".into(), Function::new(|argument| match argument {
+        Value::String(s) => Ok(Value::Int(s.chars().count() as IntType)),
+        Value::Tuple(items) => Ok(Value::Int(items.len() as IntType)),
+        other => Err(EvalexprError::type_error(other.clone(), vec![ValueType::String, ValueType::Tuple])),
+    }))?;
+    context.set_function("bit".into(), Function::new(|argument| {
+        let args = argument.as_fixed_len_tuple(2)?;
+        let (value, index) = (args[0].as_int()?, args[1].as_int()?);
+        if !(0..64).contains(&index) {
+            return Err(EvalexprError::CustomMessage(format!("bit() 位号越界: {}", index)));
+        }
+        Ok(Value::Boolean((value >> index) & 1 == 1))
+    }))?;
+    context.set_function("hex".into(), Function::new(|argument| {
+        let text = argument.as_string()?;
+        let digits = text.trim();
+        let digits = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")).unwrap_or(digits);
+        IntType::from_str_radix(digits, 16)
+            .map(Value::Int)
+            .map_err(|_| EvalexprError::CustomMessage(format!("hex() 无法解析 '{}'", text)))
+    }))?;
+    context.set_function("matches".into(), Function::new(|argument| {
+        let args = argument.as_fixed_len_tuple(2)?;
+        let (subject, pattern) = (args[0].as_string()?, args[1].as_string()?);
+        Ok(Value::Boolean(cached_regex(&pattern)?.is_match(&subject)))
+    }))?;
+    Ok(())
+}
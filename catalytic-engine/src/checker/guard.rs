@@ -0,0 +1,110 @@
+//! 保护带（临界判定）
+//!
+//! 依据检查输出中导出的限值（params 的 min/max，或阈值的 operator/value）
+//! 判断通过的读数是否距限值过近。
+
+use crate::checker::CheckOutput;
+use crate::model::GuardBand;
+
+/// 数值读数及其下限 / 上限
+struct Limits {
+    actual: f64,
+    lower: Option<f64>,
+    upper: Option<f64>,
+}
+
+/// 从检查参数提取 (下限, 上限)（未导出数值限值时返回 None）
+pub(crate) fn spec_limits(params: &serde_json::Value) -> Option<(Option<f64>, Option<f64>)> {
+    match (params["min"].as_f64(), params["max"].as_f64()) {
+        (None, None) => {
+            let value = params["value"].as_f64()?;
+            match params["operator"].as_str()? {
+                ">" | ">=" => Some((Some(value), None)),
+                "<" | "<=" => Some((None, Some(value))),
+                _ => None,
+            }
+        }
+        bounds => Some(bounds),
+    }
+}
+
+/// 从检查输出提取数值限值（非数值检查返回 None）
+fn limits(output: &CheckOutput) -> Option<Limits> {
+    let actual = output.actual.as_f64().or_else(|| output.actual["value"].as_f64())?;
+    let (lower, upper) = spec_limits(&output.params)?;
+    Some(Limits { actual, lower, upper })
+}
+
+impl GuardBand {
+    /// 保护带宽度：双边限值按区间宽度折算，单边按限值绝对值折算
+    pub fn width(&self, lower: Option<f64>, upper: Option<f64>) -> f64 {
+        let span = match (lower, upper) {
+            (Some(lo), Some(hi)) => (hi - lo).abs(),
+            (Some(limit), None) | (None, Some(limit)) => limit.abs(),
+            (None, None) => 0.0,
+        };
+        self.absolute.max(span * self.percent / 100.0)
+    }
+}
+
+/// 通过的检查是否落在保护带内（组合检查递归判定通过的子项）
+pub fn is_marginal(output: &CheckOutput, band: &GuardBand) -> bool {
+    if !output.passed {
+        return false;
+    }
+    if output.children.iter().any(|child| is_marginal(child, band)) {
+        return true;
+    }
+    let Some(Limits { actual, lower, upper }) = limits(output) else {
+        return false;
+    };
+    let width = band.width(lower, upper);
+    width > 0.0
+        && (lower.is_some_and(|lo| actual - lo < width) || upper.is_some_and(|hi| hi - actual < width))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(params: serde_json::Value, actual: f64) -> CheckOutput {
+        CheckOutput {
+            passed: true,
+            template: "range_check".to_string(),
+            params,
+            actual: serde_json::json!(actual),
+            summary: String::new(),
+            children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_two_sided_band() {
+        // 区间 [3.0, 3.6]，5% 带宽 = 0.03
+        let band = GuardBand { percent: 5.0, absolute: 0.0 };
+        let range = |v| output(serde_json::json!({"min": 3.0, "max": 3.6}), v);
+        assert!(!is_marginal(&range(3.3), &band));
+        assert!(is_marginal(&range(3.58), &band));
+        assert!(is_marginal(&range(3.01), &band));
+
+        let mut failed = range(3.59);
+        failed.passed = false;
+        assert!(!is_marginal(&failed, &band));
+    }
+
+    #[test]
+    fn test_threshold_and_composite() {
+        let band = GuardBand { percent: 0.0, absolute: 0.5 };
+        let upper = output(serde_json::json!({"operator": "<", "value": 10.0}), 9.8);
+        assert!(is_marginal(&upper, &band));
+        let lower = output(serde_json::json!({"operator": ">=", "value": 10.0}), 12.0);
+        assert!(!is_marginal(&lower, &band));
+
+        let mut all = output(serde_json::json!({"count": 2}), 2.0);
+        all.children = vec![lower, upper];
+        assert!(is_marginal(&all, &band));
+
+        let text = CheckOutput { actual: serde_json::json!("A.03"), ..output(serde_json::json!({}), 0.0) };
+        assert!(!is_marginal(&text, &band));
+    }
+}
@@ -6,9 +6,21 @@ pub mod compare;
 pub mod contains;
 pub mod bit;
 pub mod expression;
+pub mod waveform;
+pub mod tolerance;
+pub mod text;
+pub mod external;
+pub mod guard;
+pub mod functions;
 
-use crate::model::{CheckRule, Variable, VariablePool};
-use crate::error::Result;
+pub use expression::{CompiledExpr, ExprScope};
+
+use std::collections::HashMap;
+
+use crate::model::{convert_unit, CheckResultDetail, CheckRule, LimitSource, LimitValue, Variable, VariablePool};
+use crate::core::extension::Extensions;
+use crate::error::{EngineError, Result};
+use crate::parser::Patterns;
 
 /// 检查结果
 pub struct CheckOutput {
@@ -17,20 +29,94 @@ pub struct CheckOutput {
     pub params: serde_json::Value,
     pub actual: serde_json::Value,
     pub summary: String,
+    /// 组合检查的子项结果（单项检查为空）
+    pub children: Vec<CheckOutput>,
+}
+
+impl From<CheckOutput> for CheckResultDetail {
+    fn from(output: CheckOutput) -> Self {
+        Self {
+            template: output.template,
+            params: output.params,
+            actual: output.actual,
+            passed: output.passed,
+            children: output.children.into_iter().map(Self::from).collect(),
+        }
+    }
+}
+
+/// 检查上下文
+pub struct CheckContext<'a> {
+    /// 当前值（来自解析规则）
+    pub value: Option<&'a Variable>,
+    /// 当前值的单位
+    pub unit: Option<&'a str>,
+    /// 槽位变量池
+    pub variables: &'a VariablePool,
+    /// 预编译的表达式（None 或未命中时即时编译）
+    pub expressions: Option<&'a HashMap<String, CompiledExpr>>,
+    /// 预编译的正则（None 或未命中时即时编译）
+    pub patterns: Option<&'a Patterns>,
+    /// 槽位生效的限值表（`@名称` 引用）
+    pub limits: Option<&'a HashMap<String, f64>>,
+    /// 表达式可访问的槽位信息
+    pub scope: ExprScope<'a>,
+    /// 自定义检查器注册表（None 时自定义检查报错）
+    pub extensions: Option<&'a Extensions>,
+}
+
+impl<'a> CheckContext<'a> {
+    /// 仅含变量池的上下文
+    pub fn new(variables: &'a VariablePool) -> Self {
+        Self {
+            value: None,
+            unit: None,
+            variables,
+            expressions: None,
+            patterns: None,
+            limits: None,
+            scope: ExprScope::default(),
+            extensions: None,
+        }
+    }
+
+    /// 取被检查的值及其单位：指定变量名时从变量池读取，否则使用当前值
+    pub fn resolve(&self, variable: Option<&str>) -> (Option<&'a Variable>, Option<&'a str>) {
+        match variable {
+            Some(name) => (self.variables.get(name), self.variables.unit(name)),
+            None => (self.value, self.unit),
+        }
+    }
+
+    /// 求限值：变量引用换算到规则单位，`@名称` 从限值表读取
+    pub fn limit(&self, limit: &LimitValue, unit: Option<&str>) -> Result<f64> {
+        match limit.source()? {
+            LimitSource::Number(v) => Ok(v),
+            LimitSource::Variable(name) => {
+                let val = self.variables.get(name)
+                    .ok_or_else(|| EngineError::CheckError(format!("限值变量 '{}' 不存在", name)))?
+                    .as_f64()
+                    .ok_or_else(|| EngineError::CheckError(format!("限值变量 '{}' 无法转换为数值", name)))?;
+                to_unit(val, self.variables.unit(name), unit)
+            }
+            LimitSource::Named(name) => self.limits
+                .and_then(|limits| limits.get(name).copied())
+                .ok_or_else(|| EngineError::CheckError(format!("限值 '@{}' 未定义", name))),
+        }
+    }
 }
 
 /// 执行检查
-pub fn execute_check(
-    rule: &CheckRule,
-    current_value: Option<&Variable>,
-    variables: &VariablePool,
-) -> Result<CheckOutput> {
+pub fn execute_check(rule: &CheckRule, ctx: &CheckContext) -> Result<CheckOutput> {
+    let variables = ctx.variables;
     match rule {
-        CheckRule::RangeCheck { variable, min, max, include_min, include_max } => {
-            range::check(variable.as_deref(), current_value, variables, *min, *max, *include_min, *include_max)
+        CheckRule::RangeCheck { variable, min, max, include_min, include_max, unit } => {
+            let (value, value_unit) = ctx.resolve(variable.as_deref());
+            let (min, max) = (ctx.limit(min, unit.as_deref())?, ctx.limit(max, unit.as_deref())?);
+            range::check(value, value_unit, unit.as_deref(), min, max, *include_min, *include_max)
         }
-        CheckRule::Threshold { variable, operator, value } => {
-            threshold::check(variable, variables, operator, *value)
+        CheckRule::Threshold { variable, operator, value, unit } => {
+            threshold::check(variable, variables, operator, ctx.limit(value, unit.as_deref())?, unit.as_deref())
         }
         CheckRule::Compare { var_a, operator, var_b } => {
             compare::check(var_a, var_b, operator, variables)
@@ -38,11 +124,291 @@ pub fn execute_check(
         CheckRule::Contains { variable, substring } => {
             contains::check(variable, substring, variables)
         }
+        CheckRule::StringEquals { variable, expected, ignore_case } => {
+            text::check_equals(variable, expected, *ignore_case, variables)
+        }
+        CheckRule::RegexMatch { variable, pattern } => {
+            let adhoc = Patterns::new();
+            let compiled = ctx.patterns.unwrap_or(&adhoc).regex(pattern)?;
+            text::check_regex(variable, pattern, &compiled, variables)
+        }
+        CheckRule::StartsWith { variable, prefix, ignore_case } => {
+            text::check_affix(variable, prefix, text::Affix::Prefix, *ignore_case, variables)
+        }
+        CheckRule::EndsWith { variable, suffix, ignore_case } => {
+            text::check_affix(variable, suffix, text::Affix::Suffix, *ignore_case, variables)
+        }
+        CheckRule::OneOf { variable, values, ignore_case } => {
+            text::check_one_of(variable, values, *ignore_case, variables)
+        }
+        CheckRule::LengthRange { variable, min, max } => {
+            text::check_length(variable, *min, *max, variables)
+        }
+        CheckRule::Version { variable, operator, version } => {
+            text::check_version(variable, operator, version, variables)
+        }
         CheckRule::BitCheck { variable, bit, value } => {
             bit::check(variable, *bit, *value, variables)
         }
         CheckRule::Expression { expr } => {
-            expression::check(expr, variables)
+            expression::check(expr, variables, &ctx.scope, ctx.expressions)
+        }
+        CheckRule::Tolerance { variable, nominal, tolerance, unit } => {
+            let (value, value_unit) = ctx.resolve(variable.as_deref());
+            let (nominal, tolerance) = (ctx.limit(nominal, unit.as_deref())?, ctx.limit(tolerance, unit.as_deref())?);
+            tolerance::check_abs(value, value_unit, unit.as_deref(), nominal, tolerance)
+        }
+        CheckRule::TolerancePercent { variable, nominal, percent, unit } => {
+            let (value, value_unit) = ctx.resolve(variable.as_deref());
+            let (nominal, percent) = (ctx.limit(nominal, unit.as_deref())?, ctx.limit(percent, None)?);
+            tolerance::check_percent(value, value_unit, unit.as_deref(), nominal, percent)
+        }
+        CheckRule::ApproxEqual { variable, expected, abs_tol, rel_tol, max_ulps, unit } => {
+            let (value, value_unit) = ctx.resolve(variable.as_deref());
+            let tol = tolerance::Approx {
+                abs_tol: ctx.limit(abs_tol, unit.as_deref())?,
+                rel_tol: ctx.limit(rel_tol, None)?,
+                max_ulps: *max_ulps,
+            };
+            let expected = ctx.limit(expected, unit.as_deref())?;
+            tolerance::check_approx(value, value_unit, unit.as_deref(), expected, &tol)
+        }
+        CheckRule::WaveformStat { variable, statistic, min, max, unit } => {
+            let (value, value_unit) = ctx.resolve(variable.as_deref());
+            let (min, max) = (ctx.limit(min, unit.as_deref())?, ctx.limit(max, unit.as_deref())?);
+            waveform::check_stat(value, value_unit, unit.as_deref(), *statistic, min, max)
+        }
+        CheckRule::WaveformMask { variable, upper, lower } => {
+            waveform::check_mask(ctx.resolve(variable.as_deref()).0, upper.as_deref(), lower.as_deref())
         }
+        CheckRule::Monotonic { variable, direction, strict } => {
+            waveform::check_monotonic(ctx.resolve(variable.as_deref()).0, *direction, *strict)
+        }
+        CheckRule::SettlingTime { variable, target, tolerance, sample_interval, max_time } => {
+            waveform::check_settling(ctx.resolve(variable.as_deref()).0, *target, *tolerance, *sample_interval, *max_time)
+        }
+        CheckRule::RiseTime { variable, sample_interval, max_time, low_pct, high_pct } => {
+            waveform::check_rise(ctx.resolve(variable.as_deref()).0, *sample_interval, *max_time, *low_pct, *high_pct)
+        }
+        CheckRule::All { rules } => compose(Group::All, rules, ctx),
+        CheckRule::Any { rules } => compose(Group::Any, rules, ctx),
+        CheckRule::Not { rule } => {
+            let child = execute_check(rule, ctx)?;
+            let passed = !child.passed;
+            Ok(CheckOutput {
+                passed,
+                template: "not".to_string(),
+                params: serde_json::json!({}),
+                actual: serde_json::json!(child.passed),
+                summary: format!("NOT ({}) → {}", child.summary, verdict(passed)),
+                children: vec![child],
+            })
+        }
+        CheckRule::Custom { name, params } => ctx.extensions
+            .ok_or_else(|| EngineError::CheckError(format!("自定义检查器 '{}' 未注册", name)))?
+            .checker(name)?
+            .check(params, ctx),
+    }
+}
+
+/// 组合检查的汇总方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Group {
+    /// 全部子项通过
+    All,
+    /// 任一子项通过
+    Any,
+}
+
+impl Group {
+    fn as_str(self) -> &'static str {
+        match self {
+            Group::All => "all",
+            Group::Any => "any",
+        }
+    }
+}
+
+/// 组合检查：逐条执行全部子规则（不短路，每条都给出结果），再按 all/any 汇总
+///
+/// 子规则执行出错时记为未通过的子项（actual 中带错误信息），不中断其余子规则
+fn compose(group: Group, rules: &[CheckRule], ctx: &CheckContext) -> Result<CheckOutput> {
+    let template = group.as_str();
+    if rules.is_empty() {
+        return Err(EngineError::CheckError(format!("组合检查 {} 至少需要一条规则", template)));
+    }
+    let children: Vec<CheckOutput> = rules
+        .iter()
+        .map(|r| execute_check(r, ctx).unwrap_or_else(|e| failed_child(r, &e)))
+        .collect();
+
+    let passed_count = children.iter().filter(|c| c.passed).count();
+    let passed = match group {
+        Group::All => passed_count == children.len(),
+        Group::Any => passed_count > 0,
+    };
+    let summary = format!(
+        "{} {}/{} 通过 [{}] → {}",
+        template.to_uppercase(),
+        passed_count,
+        children.len(),
+        children.iter().map(|c| c.summary.as_str()).collect::<Vec<_>>().join("; "),
+        verdict(passed),
+    );
+
+    Ok(CheckOutput {
+        passed,
+        template: template.to_string(),
+        params: serde_json::json!({"count": children.len()}),
+        actual: serde_json::json!(passed_count),
+        summary,
+        children,
+    })
+}
+
+/// 执行出错的子规则：模板名取自规则本身，错误信息记入 actual
+fn failed_child(rule: &CheckRule, error: &EngineError) -> CheckOutput {
+    let params = serde_json::to_value(rule).unwrap_or_default();
+    let template = params["template"].as_str().unwrap_or("unknown").to_string();
+    let message = error.to_string();
+    CheckOutput {
+        passed: false,
+        summary: format!("{} 错误: {} → FAIL", template, message),
+        template,
+        params,
+        actual: serde_json::json!({"error": message}),
+        children: Vec::new(),
+    }
+}
+
+/// 将数值从变量单位换算到规则单位
+///
+/// 规则未声明单位时按原始数值比较（兼容不带单位的既有规则）；
+/// 规则声明了单位而值没有单位时无法判断量纲，报错而不是直接比较原始数值
+pub(crate) fn to_unit(value: f64, from: Option<&str>, to: Option<&str>) -> Result<f64> {
+    match (from, to) {
+        (Some(from), Some(to)) => convert_unit(value, from, to).ok_or_else(|| {
+            EngineError::CheckError(format!("单位不兼容: {} 无法与 {} 比较", from, to))
+        }),
+        (_, None) => Ok(value),
+        (None, Some(to)) => Err(EngineError::CheckError(format!(
+            "单位不匹配: 规则单位为 {}，值未声明单位", to
+        ))),
+    }
+}
+
+/// 摘要中的单位后缀
+pub(crate) fn unit_suffix(unit: Option<&str>) -> String {
+    unit.map(|u| format!(" {}", u)).unwrap_or_default()
+}
+
+/// 摘要中的判定结果
+pub(crate) fn verdict(passed: bool) -> &'static str {
+    if passed { "PASS" } else { "FAIL" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_composite_rules_from_json() {
+        let rule: CheckRule = serde_json::from_value(serde_json::json!({
+            "template": "any",
+            "rules": [
+                {"template": "threshold", "variable": "v", "operator": ">", "value": 5.0},
+                {"template": "not", "rule": {"template": "contains", "variable": "fw", "substring": "beta"}},
+            ]
+        })).unwrap();
+
+        let mut pool = VariablePool::new();
+        pool.set("v", Variable::Float(1.0));
+        pool.set("fw", Variable::String("v2.1".into()));
+
+        let output = execute_check(&rule, &CheckContext::new(&pool)).unwrap();
+        assert!(output.passed);
+        assert_eq!(output.actual, serde_json::json!(1));
+
+        let detail = CheckResultDetail::from(output);
+        assert_eq!(detail.children.len(), 2);
+        assert_eq!(detail.children[1].children[0].template, "contains");
+    }
+
+    #[test]
+    fn test_limit_references() {
+        let mut pool = VariablePool::new();
+        pool.set_with_unit("vref", Variable::Float(3300.0), Some("mV".into()));
+        pool.set_with_unit("vout", Variable::Float(3.31), Some("V".into()));
+        let limits = HashMap::from([("vtol".to_string(), 0.05)]);
+        let ctx = CheckContext { limits: Some(&limits), ..CheckContext::new(&pool) };
+
+        let rule: CheckRule = serde_json::from_value(serde_json::json!({
+            "template": "tolerance", "variable": "vout", "nominal": "$vref", "tolerance": "@vtol", "unit": "V"
+        })).unwrap();
+        let output = execute_check(&rule, &ctx).unwrap();
+        assert!(output.passed);
+        assert!((output.params["nominal"].as_f64().unwrap() - 3.3).abs() < 1e-12);
+
+        let missing = CheckRule::Threshold {
+            variable: "vout".into(), operator: crate::model::CompareOp::Lt, value: LimitValue::Ref("@vmax".into()), unit: None,
+        };
+        assert!(execute_check(&missing, &ctx).is_err());
+    }
+
+    #[test]
+    fn test_group_records_child_errors() {
+        let mut pool = VariablePool::new();
+        pool.set("v", Variable::Float(1.0));
+        let rules = vec![
+            CheckRule::Threshold {
+                variable: "missing".into(), operator: crate::model::CompareOp::Gt, value: 0.0.into(), unit: None,
+            },
+            CheckRule::Threshold {
+                variable: "v".into(), operator: crate::model::CompareOp::Gt, value: 0.5.into(), unit: None,
+            },
+        ];
+
+        // any：出错的分支不影响其他分支通过
+        let output = execute_check(&CheckRule::Any { rules: rules.clone() }, &CheckContext::new(&pool)).unwrap();
+        assert!(output.passed);
+        let detail = CheckResultDetail::from(output);
+        assert_eq!(detail.children.len(), 2);
+        assert!(!detail.children[0].passed);
+        assert_eq!(detail.children[0].template, "threshold");
+        assert!(detail.children[0].actual["error"].as_str().unwrap().contains("missing"));
+
+        // all：出错的子项记为未通过
+        let output = execute_check(&CheckRule::All { rules }, &CheckContext::new(&pool)).unwrap();
+        assert!(!output.passed);
+        assert_eq!(output.actual, serde_json::json!(1));
+
+        // 未知组合模板在反序列化时即被拒绝，而不是退化为 any
+        assert!(serde_json::from_value::<CheckRule>(serde_json::json!({"template": "al", "rules": []})).is_err());
+    }
+
+    #[test]
+    fn test_unit_declared_only_on_value_or_rule() {
+        let mut pool = VariablePool::new();
+        pool.set_with_unit("vout", Variable::Float(3300.0), Some("mV".into()));
+        pool.set("raw", Variable::Float(3.3));
+        let ctx = CheckContext::new(&pool);
+        let threshold = |variable: &str, unit: Option<&str>| CheckRule::Threshold {
+            variable: variable.into(), operator: crate::model::CompareOp::Lt, value: 3.6.into(), unit: unit.map(Into::into),
+        };
+
+        // 值带单位、规则未声明：按原始数值 3300 比较
+        assert!(!execute_check(&threshold("vout", None), &ctx).unwrap().passed);
+        // 规则声明单位、值没有单位：无法判断量纲
+        let err = execute_check(&threshold("raw", Some("V")), &ctx).err().unwrap();
+        assert!(err.to_string().contains("单位不匹配"), "{}", err);
+
+        assert!(execute_check(&threshold("vout", Some("V")), &ctx).unwrap().passed);
+        assert!(execute_check(&threshold("raw", None), &ctx).unwrap().passed);
+    }
+
+    #[test]
+    fn test_empty_group_rejected() {
+        let pool = VariablePool::new();
+        assert!(execute_check(&CheckRule::All { rules: vec![] }, &CheckContext::new(&pool)).is_err());
     }
 }
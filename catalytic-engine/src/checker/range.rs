@@ -1,32 +1,27 @@
 //! 范围检查
 
-use crate::checker::CheckOutput;
-use crate::model::{Variable, VariablePool};
+use crate::checker::{to_unit, unit_suffix, CheckOutput};
+use crate::model::Variable;
 use crate::error::{EngineError, Result};
 
 /// 范围检查
 /// - include_min: true 表示 >=，false 表示 >
 /// - include_max: true 表示 <=，false 表示 <
+/// - value_unit / unit: 变量单位与限值单位，均已声明时先换算
 pub fn check(
-    variable_name: Option<&str>,
-    current_value: Option<&Variable>,
-    variables: &VariablePool,
+    value: Option<&Variable>,
+    value_unit: Option<&str>,
+    unit: Option<&str>,
     min: f64,
     max: f64,
     include_min: bool,
     include_max: bool,
 ) -> Result<CheckOutput> {
-    // 获取要检查的值
-    let value = if let Some(name) = variable_name {
-        variables.get(name)
-    } else {
-        current_value
-    };
-
     let val = value
         .ok_or_else(|| EngineError::CheckError("变量不存在".to_string()))?
         .as_f64()
         .ok_or_else(|| EngineError::CheckError("变量无法转换为数值".to_string()))?;
+    let val = to_unit(val, value_unit, unit)?;
 
     // 根据配置判断边界
     let min_ok = if include_min { val >= min } else { val > min };
@@ -37,10 +32,11 @@ pub fn check(
     let min_op = if include_min { ">=" } else { ">" };
     let max_op = if include_max { "<=" } else { "<" };
 
+    let u = unit_suffix(unit.or(value_unit));
     let summary = if passed {
-        format!("{:.4} ({}{:.4} && {}{:.4}) → PASS", val, min_op, min, max_op, max)
+        format!("{:.4}{} ({}{:.4} && {}{:.4}) → PASS", val, u, min_op, min, max_op, max)
     } else {
-        format!("{:.4} ({}{:.4} && {}{:.4}) → FAIL", val, min_op, min, max_op, max)
+        format!("{:.4}{} ({}{:.4} && {}{:.4}) → FAIL", val, u, min_op, min, max_op, max)
     };
 
     Ok(CheckOutput {
@@ -54,5 +50,6 @@ pub fn check(
         }),
         actual: serde_json::json!(val),
         summary,
+        children: Vec::new(),
     })
 }
@@ -0,0 +1,259 @@
+//! 字符串检查（相等、正则、前后缀、枚举、长度、版本号）
+
+use std::cmp::Ordering;
+
+use regex::Regex;
+
+use crate::checker::{verdict, CheckOutput};
+use crate::model::{CompareOp, VariablePool};
+use crate::error::{EngineError, Result};
+
+/// 取变量的字符串值
+fn text(variable: &str, variables: &VariablePool) -> Result<String> {
+    Ok(variables.get(variable)
+        .ok_or_else(|| EngineError::CheckError(format!("变量 '{}' 不存在", variable)))?
+        .as_string())
+}
+
+fn fold(s: &str, ignore_case: bool) -> String {
+    if ignore_case { s.to_lowercase() } else { s.to_string() }
+}
+
+/// 字符串相等
+pub fn check_equals(variable: &str, expected: &str, ignore_case: bool, variables: &VariablePool) -> Result<CheckOutput> {
+    let val = text(variable, variables)?;
+    let passed = fold(&val, ignore_case) == fold(expected, ignore_case);
+    let op = if passed { "==" } else { "!=" };
+
+    Ok(CheckOutput {
+        passed,
+        template: "string_equals".to_string(),
+        params: serde_json::json!({"variable": variable, "expected": expected, "ignore_case": ignore_case}),
+        actual: serde_json::json!(val),
+        summary: format!("'{}' {} '{}'{} → {}", val, op, expected, if ignore_case { " (忽略大小写)" } else { "" }, verdict(passed)),
+        children: Vec::new(),
+    })
+}
+
+/// 正则匹配
+pub fn check_regex(variable: &str, pattern: &str, re: &Regex, variables: &VariablePool) -> Result<CheckOutput> {
+    let val = text(variable, variables)?;
+    let passed = re.is_match(&val);
+
+    Ok(CheckOutput {
+        passed,
+        template: "regex_match".to_string(),
+        params: serde_json::json!({"variable": variable, "pattern": pattern}),
+        actual: serde_json::json!(val),
+        summary: if passed {
+            format!("'{}' 匹配 /{}/ → PASS", val, pattern)
+        } else {
+            format!("'{}' 不匹配 /{}/ → FAIL", val, pattern)
+        },
+        children: Vec::new(),
+    })
+}
+
+/// 前缀 / 后缀
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Affix {
+    Prefix,
+    Suffix,
+}
+
+/// 前后缀匹配
+pub fn check_affix(
+    variable: &str,
+    affix: &str,
+    kind: Affix,
+    ignore_case: bool,
+    variables: &VariablePool,
+) -> Result<CheckOutput> {
+    let val = text(variable, variables)?;
+    let (folded, affix_folded) = (fold(&val, ignore_case), fold(affix, ignore_case));
+    let (passed, template, word) = match kind {
+        Affix::Prefix => (folded.starts_with(&affix_folded), "starts_with", "开头"),
+        Affix::Suffix => (folded.ends_with(&affix_folded), "ends_with", "结尾"),
+    };
+    let key = match kind {
+        Affix::Prefix => "prefix",
+        Affix::Suffix => "suffix",
+    };
+
+    Ok(CheckOutput {
+        passed,
+        template: template.to_string(),
+        params: serde_json::json!({"variable": variable, key: affix, "ignore_case": ignore_case}),
+        actual: serde_json::json!(val),
+        summary: format!("'{}' {}以 '{}' {} → {}", val, if passed { "" } else { "不" }, affix, word, verdict(passed)),
+        children: Vec::new(),
+    })
+}
+
+/// 枚举：值属于给定集合
+pub fn check_one_of(variable: &str, values: &[String], ignore_case: bool, variables: &VariablePool) -> Result<CheckOutput> {
+    if values.is_empty() {
+        return Err(EngineError::CheckError("枚举检查的候选集合为空".to_string()));
+    }
+    let val = text(variable, variables)?;
+    let folded = fold(&val, ignore_case);
+    let passed = values.iter().any(|v| fold(v, ignore_case) == folded);
+
+    Ok(CheckOutput {
+        passed,
+        template: "one_of".to_string(),
+        params: serde_json::json!({"variable": variable, "values": values, "ignore_case": ignore_case}),
+        actual: serde_json::json!(val),
+        summary: format!("'{}' {} {{{}}} → {}", val, if passed { "∈" } else { "∉" }, values.join(", "), verdict(passed)),
+        children: Vec::new(),
+    })
+}
+
+/// 字符串长度范围（按字符计）
+pub fn check_length(variable: &str, min: usize, max: usize, variables: &VariablePool) -> Result<CheckOutput> {
+    let val = text(variable, variables)?;
+    let len = val.chars().count();
+    let passed = len >= min && len <= max;
+
+    Ok(CheckOutput {
+        passed,
+        template: "length_range".to_string(),
+        params: serde_json::json!({"variable": variable, "min": min, "max": max}),
+        actual: serde_json::json!(len),
+        summary: format!("len('{}') = {} (>={} && <={}) → {}", val, len, min, max, verdict(passed)),
+        children: Vec::new(),
+    })
+}
+
+/// 比较两个版本号
+///
+/// 忽略前缀 v 与 `+build` 元数据；主版本部分按 `.` 分段比较，两侧均为数字时按数值，
+/// 否则按字符串，缺失的段视为 0。带预发布标记（`-rc.1`）的版本低于同号正式版本，
+/// 预发布标记之间按同样规则分段比较。
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a_core, a_pre) = split_version(a);
+    let (b_core, b_pre) = split_version(b);
+    compare_segments(a_core, b_core, true).then_with(|| match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => compare_segments(a, b, false),
+    })
+}
+
+/// 拆分为 (主版本, 预发布标记)
+fn split_version(version: &str) -> (&str, Option<&str>) {
+    let version = version.trim();
+    let version = version.strip_prefix(['v', 'V']).unwrap_or(version);
+    let version = version.split('+').next().unwrap_or_default();
+    match version.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (version, None),
+    }
+}
+
+/// 分段比较；pad_zero 为 true 时缺失的段按 0 处理（1.2 == 1.2.0），否则段数少者较小
+fn compare_segments(a: &str, b: &str, pad_zero: bool) -> Ordering {
+    let (mut a_iter, mut b_iter) = (a.split('.'), b.split('.'));
+    loop {
+        let (x, y) = match (a_iter.next(), b_iter.next()) {
+            (None, None) => return Ordering::Equal,
+            (Some(x), None) if pad_zero => (x, "0"),
+            (None, Some(y)) if pad_zero => ("0", y),
+            (Some(_), None) => return Ordering::Greater,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(x), Some(y)) => (x, y),
+        };
+        let ord = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            // 语义化版本：数字标识符低于字母数字标识符
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => x.cmp(y),
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+}
+
+/// 版本号比较
+pub fn check_version(variable: &str, operator: &CompareOp, version: &str, variables: &VariablePool) -> Result<CheckOutput> {
+    if version.trim().is_empty() {
+        return Err(EngineError::CheckError("版本号比较的目标版本为空".to_string()));
+    }
+    let val = text(variable, variables)?;
+    let ord = compare_versions(&val, version);
+    let passed = match operator {
+        CompareOp::Gt => ord == Ordering::Greater,
+        CompareOp::Lt => ord == Ordering::Less,
+        CompareOp::Gte => ord != Ordering::Less,
+        CompareOp::Lte => ord != Ordering::Greater,
+        CompareOp::Eq => ord == Ordering::Equal,
+        CompareOp::Ne => ord != Ordering::Equal,
+    };
+
+    Ok(CheckOutput {
+        passed,
+        template: "version".to_string(),
+        params: serde_json::json!({"variable": variable, "operator": operator.as_str(), "version": version}),
+        actual: serde_json::json!(val),
+        summary: format!("{} {} {} → {}", val, operator.as_str(), version, verdict(passed)),
+        children: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Variable;
+
+    fn pool(value: &str) -> VariablePool {
+        let mut pool = VariablePool::new();
+        pool.set("s", Variable::String(value.to_string()));
+        pool
+    }
+
+    #[test]
+    fn test_equals_and_affix() {
+        let pool = pool("KEYSIGHT");
+        assert!(!check_equals("s", "Keysight", false, &pool).unwrap().passed);
+        assert!(check_equals("s", "Keysight", true, &pool).unwrap().passed);
+
+        let out = check_affix("s", "key", Affix::Prefix, true, &pool).unwrap();
+        assert!(out.passed);
+        assert_eq!(out.summary, "'KEYSIGHT' 以 'key' 开头 → PASS");
+        assert!(!check_affix("s", "HT ", Affix::Suffix, false, &pool).unwrap().passed);
+    }
+
+    #[test]
+    fn test_regex_one_of_and_length() {
+        let pool = pool("SN-00123");
+        let re = Regex::new(r"^SN-\d{5}$").unwrap();
+        assert!(check_regex("s", re.as_str(), &re, &pool).unwrap().passed);
+
+        let values = vec!["sn-00123".to_string(), "SN-00999".to_string()];
+        assert!(!check_one_of("s", &values, false, &pool).unwrap().passed);
+        assert!(check_one_of("s", &values, true, &pool).unwrap().passed);
+        assert!(check_one_of("s", &[], false, &pool).is_err());
+
+        assert_eq!(check_length("s", 8, 8, &pool).unwrap().actual, 8);
+        assert!(!check_length("s", 1, 4, &pool).unwrap().passed);
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("2.3.1", "2.3.1"), Ordering::Equal);
+        assert_eq!(compare_versions("v2.10.0", "2.9.9"), Ordering::Greater);
+        assert_eq!(compare_versions("2.3", "2.3.0"), Ordering::Equal);
+        assert_eq!(compare_versions("2.3.1-rc.1", "2.3.1"), Ordering::Less);
+        assert_eq!(compare_versions("2.3.1-rc.2", "2.3.1-rc.10"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.0-alpha", "1.0.0-alpha.1"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.0+build.5", "1.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("A.03.01", "A.02.99"), Ordering::Greater);
+
+        let pool = pool("2.3.0");
+        assert!(!check_version("s", &CompareOp::Gte, "2.3.1", &pool).unwrap().passed);
+        assert!(check_version("s", &CompareOp::Lt, "2.3.1", &pool).unwrap().passed);
+    }
+}
@@ -1,6 +1,6 @@
 //! 阈值检查
 
-use crate::checker::CheckOutput;
+use crate::checker::{to_unit, unit_suffix, CheckOutput};
 use crate::model::{CompareOp, VariablePool};
 use crate::error::{EngineError, Result};
 
@@ -10,19 +10,22 @@ pub fn check(
     variables: &VariablePool,
     operator: &CompareOp,
     threshold: f64,
+    unit: Option<&str>,
 ) -> Result<CheckOutput> {
     let val = variables.get(variable)
         .ok_or_else(|| EngineError::CheckError(format!("变量 '{}' 不存在", variable)))?
         .as_f64()
         .ok_or_else(|| EngineError::CheckError("变量无法转换为数值".to_string()))?;
+    let val = to_unit(val, variables.unit(variable), unit)?;
 
     let passed = operator.compare(val, threshold);
     let op_str = operator.as_str();
 
+    let u = unit_suffix(unit.or(variables.unit(variable)));
     let summary = if passed {
-        format!("{:.2} {} {:.2} → PASS", val, op_str, threshold)
+        format!("{:.2}{} {} {:.2} → PASS", val, u, op_str, threshold)
     } else {
-        format!("{:.2} {} {:.2} → FAIL", val, op_str, threshold)
+        format!("{:.2}{} {} {:.2} → FAIL", val, u, op_str, threshold)
     };
 
     Ok(CheckOutput {
@@ -31,5 +34,6 @@ pub fn check(
         params: serde_json::json!({"variable": variable, "operator": op_str, "value": threshold}),
         actual: serde_json::json!(val),
         summary,
+        children: Vec::new(),
     })
 }
@@ -0,0 +1,186 @@
+//! 容差检查（标称值 ± 容差、近似相等）
+
+use crate::checker::{to_unit, unit_suffix, verdict, CheckOutput};
+use crate::model::Variable;
+use crate::error::{EngineError, Result};
+
+/// 取数值并换算到规则单位
+fn numeric(value: Option<&Variable>, value_unit: Option<&str>, unit: Option<&str>) -> Result<f64> {
+    let val = value
+        .ok_or_else(|| EngineError::CheckError("变量不存在".to_string()))?
+        .as_f64()
+        .ok_or_else(|| EngineError::CheckError("变量无法转换为数值".to_string()))?;
+    to_unit(val, value_unit, unit)
+}
+
+/// 标称值 ± 绝对容差（闭区间）
+pub fn check_abs(
+    value: Option<&Variable>,
+    value_unit: Option<&str>,
+    unit: Option<&str>,
+    nominal: f64,
+    tolerance: f64,
+) -> Result<CheckOutput> {
+    if tolerance < 0.0 {
+        return Err(EngineError::CheckError(format!("容差不能为负: {}", tolerance)));
+    }
+    let val = numeric(value, value_unit, unit)?;
+    let (lower, upper) = (nominal - tolerance, nominal + tolerance);
+    let passed = val >= lower && val <= upper;
+
+    let u = unit_suffix(unit.or(value_unit));
+    Ok(CheckOutput {
+        passed,
+        template: "tolerance".to_string(),
+        params: serde_json::json!({"nominal": nominal, "tolerance": tolerance, "min": lower, "max": upper}),
+        actual: serde_json::json!(val),
+        summary: format!(
+            "{:.4}{} ({:.4} ± {:.4} → [{:.4}, {:.4}]) → {}",
+            val, u, nominal, tolerance, lower, upper, verdict(passed)
+        ),
+        children: Vec::new(),
+    })
+}
+
+/// 标称值 ± 百分比（闭区间，容差按标称值绝对值折算）
+///
+/// 标称值为 0 时百分比容差无意义，直接报错（应改用绝对容差）
+pub fn check_percent(
+    value: Option<&Variable>,
+    value_unit: Option<&str>,
+    unit: Option<&str>,
+    nominal: f64,
+    percent: f64,
+) -> Result<CheckOutput> {
+    if percent < 0.0 {
+        return Err(EngineError::CheckError(format!("容差百分比不能为负: {}", percent)));
+    }
+    if nominal == 0.0 {
+        return Err(EngineError::CheckError("标称值为 0 时无法使用百分比容差，请改用绝对容差".to_string()));
+    }
+    let val = numeric(value, value_unit, unit)?;
+    let tolerance = nominal.abs() * percent / 100.0;
+    let (lower, upper) = (nominal - tolerance, nominal + tolerance);
+    let passed = val >= lower && val <= upper;
+    let deviation_pct = (val - nominal) / nominal.abs() * 100.0;
+
+    let u = unit_suffix(unit.or(value_unit));
+    Ok(CheckOutput {
+        passed,
+        template: "tolerance_percent".to_string(),
+        params: serde_json::json!({
+            "nominal": nominal, "percent": percent, "tolerance": tolerance, "min": lower, "max": upper
+        }),
+        actual: serde_json::json!(val),
+        summary: format!(
+            "{:.4}{} ({:.4} ± {}% → [{:.4}, {:.4}], 偏差 {:+.3}%) → {}",
+            val, u, nominal, percent, lower, upper, deviation_pct, verdict(passed)
+        ),
+        children: Vec::new(),
+    })
+}
+
+/// 近似相等的判定条件
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Approx {
+    pub abs_tol: f64,
+    pub rel_tol: f64,
+    pub max_ulps: u64,
+}
+
+/// 两个浮点数之间相隔的可表示值个数（NaN 返回 None）
+pub fn ulp_distance(a: f64, b: f64) -> Option<u64> {
+    if a.is_nan() || b.is_nan() {
+        return None;
+    }
+    // 将位模式映射为单调有序的整数，+0 与 -0 重合
+    let ordered = |x: f64| {
+        let bits = x.to_bits() as i64;
+        if bits < 0 { i64::MIN.wrapping_sub(bits) } else { bits }
+    };
+    Some((ordered(a) as i128 - ordered(b) as i128).unsigned_abs().min(u64::MAX as u128) as u64)
+}
+
+/// 近似相等检查
+pub fn check_approx(
+    value: Option<&Variable>,
+    value_unit: Option<&str>,
+    unit: Option<&str>,
+    expected: f64,
+    tol: &Approx,
+) -> Result<CheckOutput> {
+    if tol.abs_tol < 0.0 || tol.rel_tol < 0.0 {
+        return Err(EngineError::CheckError("容差不能为负".to_string()));
+    }
+    let val = numeric(value, value_unit, unit)?;
+    let deviation = (val - expected).abs();
+    let allowed = tol.abs_tol.max(tol.rel_tol * val.abs().max(expected.abs()));
+    let ulps = ulp_distance(val, expected);
+    let passed = deviation <= allowed || ulps.is_some_and(|n| n <= tol.max_ulps);
+
+    let u = unit_suffix(unit.or(value_unit));
+    Ok(CheckOutput {
+        passed,
+        template: "approx_equal".to_string(),
+        params: serde_json::json!({
+            "expected": expected,
+            "abs_tol": tol.abs_tol,
+            "rel_tol": tol.rel_tol,
+            "max_ulps": tol.max_ulps,
+            "min": expected - allowed,
+            "max": expected + allowed,
+        }),
+        actual: serde_json::json!({"value": val, "deviation": deviation, "ulps": ulps}),
+        summary: format!("{}{} ≈ {} (偏差 {:.3e}) → {}", val, u, expected, deviation, verdict(passed)),
+        children: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tolerance_abs() {
+        let out = check_abs(Some(&Variable::Float(100.4)), Some("Ω"), Some("Ω"), 100.0, 0.5).unwrap();
+        assert!(out.passed);
+        assert_eq!(out.params["min"], 99.5);
+        assert_eq!(out.params["max"], 100.5);
+
+        assert!(!check_abs(Some(&Variable::Float(100.6)), None, None, 100.0, 0.5).unwrap().passed);
+    }
+
+    #[test]
+    fn test_tolerance_percent_with_unit() {
+        // 5020 mV 换算为 5.02 V，在 5.0 V ± 2% 内
+        let out = check_percent(Some(&Variable::Float(5020.0)), Some("mV"), Some("V"), 5.0, 2.0).unwrap();
+        assert!(out.passed);
+        assert!((out.params["min"].as_f64().unwrap() - 4.9).abs() < 1e-12);
+        assert!((out.params["max"].as_f64().unwrap() - 5.1).abs() < 1e-12);
+
+        assert!(!check_percent(Some(&Variable::Float(5.2)), None, None, 5.0, 2.0).unwrap().passed);
+        assert!(check_percent(Some(&Variable::Float(5.0)), None, None, 5.0, -1.0).is_err());
+
+        // 标称值为 0 时报错，而不是输出 NaN% 偏差
+        let err = check_percent(Some(&Variable::Float(0.0)), None, None, 0.0, 5.0).err().unwrap();
+        assert!(err.to_string().contains("绝对容差"), "{}", err);
+    }
+
+    #[test]
+    fn test_approx_equal() {
+        assert_eq!(ulp_distance(0.0, -0.0), Some(0));
+        assert_eq!(ulp_distance(1.0, 1.0 + f64::EPSILON), Some(1));
+        assert_eq!(ulp_distance(f64::NAN, 1.0), None);
+
+        let sum = Variable::Float(0.1 + 0.2);
+        let exact = Approx::default();
+        assert!(!check_approx(Some(&sum), None, None, 0.3, &exact).unwrap().passed);
+
+        let ulps = Approx { max_ulps: 4, ..Default::default() };
+        assert!(check_approx(Some(&sum), None, None, 0.3, &ulps).unwrap().passed);
+
+        let rel = Approx { rel_tol: 1e-3, ..Default::default() };
+        assert!(check_approx(Some(&Variable::Float(1000.5)), None, None, 1000.0, &rel).unwrap().passed);
+        assert!(!check_approx(Some(&Variable::Float(1002.0)), None, None, 1000.0, &rel).unwrap().passed);
+    }
+}
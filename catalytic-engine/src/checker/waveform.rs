@@ -0,0 +1,296 @@
+//! 波形检查（FloatArray）
+
+use crate::checker::{to_unit, unit_suffix, verdict, CheckOutput};
+use crate::model::{MonotonicDirection, Variable, WaveformStatistic};
+use crate::error::{EngineError, Result};
+
+/// 取出波形采样点
+fn samples(value: Option<&Variable>) -> Result<&[f64]> {
+    let samples = value
+        .ok_or_else(|| EngineError::CheckError("变量不存在".to_string()))?
+        .as_f64_slice()
+        .ok_or_else(|| EngineError::CheckError("变量不是浮点数组".to_string()))?;
+    if samples.is_empty() {
+        return Err(EngineError::CheckError("波形为空".to_string()));
+    }
+    Ok(samples)
+}
+
+fn require_interval(sample_interval: f64) -> Result<()> {
+    if sample_interval > 0.0 {
+        Ok(())
+    } else {
+        Err(EngineError::CheckError(format!("采样间隔必须大于 0: {}", sample_interval)))
+    }
+}
+
+/// 计算统计量
+pub fn statistic(samples: &[f64], statistic: WaveformStatistic) -> f64 {
+    let n = samples.len() as f64;
+    let min = samples.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = samples.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let mean = samples.iter().sum::<f64>() / n;
+    match statistic {
+        WaveformStatistic::Min => min,
+        WaveformStatistic::Max => max,
+        WaveformStatistic::Mean => mean,
+        WaveformStatistic::Rms => (samples.iter().map(|v| v * v).sum::<f64>() / n).sqrt(),
+        WaveformStatistic::StdDev => (samples.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt(),
+        WaveformStatistic::PeakToPeak => max - min,
+    }
+}
+
+/// 统计量范围检查（闭区间）
+pub fn check_stat(
+    value: Option<&Variable>,
+    value_unit: Option<&str>,
+    unit: Option<&str>,
+    stat: WaveformStatistic,
+    min: f64,
+    max: f64,
+) -> Result<CheckOutput> {
+    let val = statistic(samples(value)?, stat);
+    let val = to_unit(val, value_unit, unit)?;
+    let passed = val >= min && val <= max;
+
+    let u = unit_suffix(unit.or(value_unit));
+    Ok(CheckOutput {
+        passed,
+        template: "waveform_stat".to_string(),
+        params: serde_json::json!({"statistic": stat.as_str(), "min": min, "max": max}),
+        actual: serde_json::json!(val),
+        summary: format!("{} = {:.4}{} (>={:.4} && <={:.4}) → {}", stat.as_str(), val, u, min, max, verdict(passed)),
+        children: Vec::new(),
+    })
+}
+
+/// 逐点限值模板检查，actual 中给出越限点序号
+pub fn check_mask(value: Option<&Variable>, upper: Option<&[f64]>, lower: Option<&[f64]>) -> Result<CheckOutput> {
+    let samples = samples(value)?;
+    if upper.is_none() && lower.is_none() {
+        return Err(EngineError::CheckError("限值模板至少需要 upper 或 lower".to_string()));
+    }
+    for (name, limit) in [("upper", upper), ("lower", lower)] {
+        if let Some(limit) = limit {
+            if limit.len() != samples.len() {
+                return Err(EngineError::CheckError(format!(
+                    "限值模板 {} 长度 {} 与波形点数 {} 不一致", name, limit.len(), samples.len()
+                )));
+            }
+        }
+    }
+
+    let failing: Vec<usize> = samples
+        .iter()
+        .enumerate()
+        .filter(|(i, v)| upper.is_some_and(|u| **v > u[*i]) || lower.is_some_and(|l| **v < l[*i]))
+        .map(|(i, _)| i)
+        .collect();
+    let passed = failing.is_empty();
+
+    Ok(CheckOutput {
+        passed,
+        template: "waveform_mask".to_string(),
+        params: serde_json::json!({"upper": upper, "lower": lower}),
+        actual: serde_json::json!({"points": samples.len(), "failing_indices": failing}),
+        summary: format!("{}/{} 点超限 → {}", failing.len(), samples.len(), verdict(passed)),
+        children: Vec::new(),
+    })
+}
+
+/// 单调性检查，actual 中给出违反单调性的点序号
+pub fn check_monotonic(value: Option<&Variable>, direction: MonotonicDirection, strict: bool) -> Result<CheckOutput> {
+    let samples = samples(value)?;
+    let failing: Vec<usize> = samples
+        .windows(2)
+        .enumerate()
+        .filter(|(_, w)| match (direction, strict) {
+            (MonotonicDirection::Increasing, false) => w[1] < w[0],
+            (MonotonicDirection::Increasing, true) => w[1] <= w[0],
+            (MonotonicDirection::Decreasing, false) => w[1] > w[0],
+            (MonotonicDirection::Decreasing, true) => w[1] >= w[0],
+        })
+        .map(|(i, _)| i + 1)
+        .collect();
+    let passed = failing.is_empty();
+
+    let direction_str = match direction {
+        MonotonicDirection::Increasing => "increasing",
+        MonotonicDirection::Decreasing => "decreasing",
+    };
+    Ok(CheckOutput {
+        passed,
+        template: "monotonic".to_string(),
+        params: serde_json::json!({"direction": direction_str, "strict": strict}),
+        actual: serde_json::json!({"points": samples.len(), "failing_indices": failing}),
+        summary: format!("{} {} 处违反 → {}", direction_str, failing.len(), verdict(passed)),
+        children: Vec::new(),
+    })
+}
+
+/// 稳定时间检查：最后一个越出 target ± tolerance 的点之后即视为稳定
+pub fn check_settling(
+    value: Option<&Variable>,
+    target: f64,
+    tolerance: f64,
+    sample_interval: f64,
+    max_time: f64,
+) -> Result<CheckOutput> {
+    let samples = samples(value)?;
+    require_interval(sample_interval)?;
+
+    let last_out = samples.iter().rposition(|v| (v - target).abs() > tolerance);
+    let settled_index = last_out.map_or(0, |i| i + 1);
+    let params = serde_json::json!({
+        "target": target, "tolerance": tolerance, "sample_interval": sample_interval, "max_time": max_time
+    });
+
+    // 最后一点仍越限：波形未稳定
+    if settled_index >= samples.len() {
+        return Ok(CheckOutput {
+            passed: false,
+            template: "settling_time".to_string(),
+            params,
+            actual: serde_json::json!({"settling_time": null, "failing_indices": [samples.len() - 1]}),
+            summary: format!("未稳定到 {:.4} ± {:.4} → FAIL", target, tolerance),
+            children: Vec::new(),
+        });
+    }
+
+    let time = settled_index as f64 * sample_interval;
+    let passed = time <= max_time;
+    Ok(CheckOutput {
+        passed,
+        template: "settling_time".to_string(),
+        params,
+        actual: serde_json::json!({"settling_time": time, "settled_index": settled_index}),
+        summary: format!("settling {:.6} s (<={:.6}) → {}", time, max_time, verdict(passed)),
+        children: Vec::new(),
+    })
+}
+
+/// 上升时间检查：阈值按波形最小值到最大值的幅度折算，交点线性插值
+pub fn check_rise(
+    value: Option<&Variable>,
+    sample_interval: f64,
+    max_time: f64,
+    low_pct: f64,
+    high_pct: f64,
+) -> Result<CheckOutput> {
+    let samples = samples(value)?;
+    require_interval(sample_interval)?;
+    if !(0.0..=100.0).contains(&low_pct) || !(0.0..=100.0).contains(&high_pct) || low_pct >= high_pct {
+        return Err(EngineError::CheckError(format!("上升时间阈值无效: {}% - {}%", low_pct, high_pct)));
+    }
+
+    let min = statistic(samples, WaveformStatistic::Min);
+    let amplitude = statistic(samples, WaveformStatistic::PeakToPeak);
+    if amplitude <= 0.0 {
+        return Err(EngineError::CheckError("波形幅度为零，无法计算上升时间".to_string()));
+    }
+    let low = min + amplitude * low_pct / 100.0;
+    let high = min + amplitude * high_pct / 100.0;
+
+    let params = serde_json::json!({
+        "sample_interval": sample_interval, "max_time": max_time, "low_pct": low_pct, "high_pct": high_pct
+    });
+    let t_low = crossing(samples, low, 0);
+    let t_high = t_low.and_then(|t| crossing(samples, high, t.floor() as usize));
+    let (t_low, t_high) = match (t_low, t_high) {
+        (Some(l), Some(h)) => (l, h),
+        _ => {
+            return Ok(CheckOutput {
+                passed: false,
+                template: "rise_time".to_string(),
+                params,
+                actual: serde_json::json!({"rise_time": null}),
+                summary: format!("未找到 {}% → {}% 上升沿 → FAIL", low_pct, high_pct),
+                children: Vec::new(),
+            });
+        }
+    };
+
+    let time = (t_high - t_low) * sample_interval;
+    let passed = time <= max_time;
+    Ok(CheckOutput {
+        passed,
+        template: "rise_time".to_string(),
+        params,
+        actual: serde_json::json!({"rise_time": time}),
+        summary: format!("rise {:.6} s (<={:.6}) → {}", time, max_time, verdict(passed)),
+        children: Vec::new(),
+    })
+}
+
+/// 从 start 开始首次达到 level 的位置（以采样点为单位，相邻点间线性插值）
+fn crossing(samples: &[f64], level: f64, start: usize) -> Option<f64> {
+    let i = start + samples[start..].iter().position(|v| *v >= level)?;
+    if i == 0 || samples[i - 1] >= level {
+        return Some(i as f64);
+    }
+    let (a, b) = (samples[i - 1], samples[i]);
+    Some((i - 1) as f64 + (level - a) / (b - a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wave(v: &[f64]) -> Variable {
+        Variable::FloatArray(v.to_vec())
+    }
+
+    #[test]
+    fn test_statistics() {
+        let w = [1.0, -1.0, 1.0, -1.0];
+        assert_eq!(statistic(&w, WaveformStatistic::Mean), 0.0);
+        assert_eq!(statistic(&w, WaveformStatistic::Rms), 1.0);
+        assert_eq!(statistic(&w, WaveformStatistic::StdDev), 1.0);
+        assert_eq!(statistic(&w, WaveformStatistic::PeakToPeak), 2.0);
+
+        let out = check_stat(Some(&wave(&w)), None, None, WaveformStatistic::Rms, 0.9, 1.1).unwrap();
+        assert!(out.passed);
+        assert!(check_stat(Some(&Variable::Float(1.0)), None, None, WaveformStatistic::Max, 0.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_mask_reports_failing_indices() {
+        let out = check_mask(Some(&wave(&[0.0, 2.0, 1.0, -3.0])), Some(&[1.0; 4]), Some(&[-1.0; 4])).unwrap();
+        assert!(!out.passed);
+        assert_eq!(out.actual["failing_indices"], serde_json::json!([1, 3]));
+
+        assert!(check_mask(Some(&wave(&[0.0, 1.0])), Some(&[1.0]), None).is_err());
+    }
+
+    #[test]
+    fn test_monotonic() {
+        let w = wave(&[1.0, 2.0, 2.0, 3.0, 2.5]);
+        let out = check_monotonic(Some(&w), MonotonicDirection::Increasing, false).unwrap();
+        assert_eq!(out.actual["failing_indices"], serde_json::json!([4]));
+
+        let out = check_monotonic(Some(&w), MonotonicDirection::Increasing, true).unwrap();
+        assert_eq!(out.actual["failing_indices"], serde_json::json!([2, 4]));
+    }
+
+    #[test]
+    fn test_settling_time() {
+        let w = wave(&[0.0, 6.0, 4.5, 5.1, 4.95, 5.0]);
+        let out = check_settling(Some(&w), 5.0, 0.2, 0.001, 0.005).unwrap();
+        assert!(out.passed);
+        assert_eq!(out.actual["settled_index"], 3);
+
+        let unsettled = check_settling(Some(&wave(&[5.0, 0.0])), 5.0, 0.2, 0.001, 1.0).unwrap();
+        assert!(!unsettled.passed);
+    }
+
+    #[test]
+    fn test_rise_time() {
+        // 0 → 10 线性上升 10 个采样点：10% 到 90% 对应 8 个采样间隔
+        let w: Vec<f64> = (0..=10).map(|i| i as f64).collect();
+        let out = check_rise(Some(&wave(&w)), 0.5, 5.0, 10.0, 90.0).unwrap();
+        assert!(out.passed);
+        assert!((out.actual["rise_time"].as_f64().unwrap() - 4.0).abs() < 1e-9);
+
+        assert!(check_rise(Some(&wave(&[1.0, 1.0])), 0.5, 5.0, 10.0, 90.0).is_err());
+    }
+}
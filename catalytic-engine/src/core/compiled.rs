@@ -0,0 +1,206 @@
+//! 预编译方案
+//!
+//! 方案加载后把步骤引用的正则、JSON 路径与表达式编译一次，
+//! 以 Arc 在所有槽位间共享，执行期间不再重复编译。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::checker::{CheckContext, CompiledExpr, ExprScope};
+use crate::core::extension::Extensions;
+use crate::error::{EngineError, Result};
+use crate::model::{CheckRule, ExecutionMode, ParseRule, TestStep, Variable, VariablePool};
+use crate::parser::{parse_response, ParseOutput, Patterns};
+
+/// 预编译方案
+#[derive(Debug, Clone, Default)]
+pub struct CompiledPlan {
+    /// 方案名称（None 表示默认步骤列表）
+    pub name: Option<String>,
+    /// 步骤列表
+    pub steps: Vec<TestStep>,
+    /// 解析规则与检查规则的正则、JSON 路径
+    pub patterns: Patterns,
+    /// 检查规则的表达式 {表达式文本: 编译结果}
+    pub expressions: HashMap<String, CompiledExpr>,
+    /// 编译时的自定义解析器与检查器注册表
+    pub extensions: Arc<Extensions>,
+}
+
+impl CompiledPlan {
+    /// 编译步骤列表，所有无效模式合并为一个 ValidationError 返回
+    pub fn compile(name: Option<String>, steps: Vec<TestStep>, extensions: Arc<Extensions>) -> Result<Self> {
+        let mut problems = Vec::new();
+        let (patterns, expressions) = compile_steps(&steps, Some(&extensions), &mut problems);
+
+        if !problems.is_empty() {
+            return Err(EngineError::ValidationError(problems.join("; ")));
+        }
+        Ok(Self { name, steps, patterns, expressions, extensions })
+    }
+
+    /// 仅校验步骤列表能否编译，返回问题列表（用于配置校验）
+    pub fn check(steps: &[TestStep], extensions: &Extensions) -> Vec<String> {
+        let mut problems = Vec::new();
+        compile_steps(steps, Some(extensions), &mut problems);
+        problems
+    }
+
+    /// 仅校验正则、JSON 路径与表达式能否编译，不检查自定义扩展（扩展可能在加载配置之后才注册）
+    pub fn check_patterns(steps: &[TestStep]) -> Vec<String> {
+        let mut problems = Vec::new();
+        compile_steps(steps, None, &mut problems);
+        problems
+    }
+
+    /// 按解析规则解析响应（自定义规则交给注册的解析器）
+    pub fn parse(&self, data: &[u8], rule: &ParseRule) -> Result<ParseOutput> {
+        match rule {
+            ParseRule::Custom { name, params } => self.extensions.parser(name)?.parse(data, params),
+            _ => parse_response(data, rule, &self.patterns),
+        }
+    }
+
+    /// 构建检查上下文
+    pub fn check_context<'a>(
+        &'a self,
+        value: Option<&'a Variable>,
+        unit: Option<&'a str>,
+        variables: &'a VariablePool,
+        limits: &'a HashMap<String, f64>,
+        scope: ExprScope<'a>,
+    ) -> CheckContext<'a> {
+        CheckContext {
+            value,
+            unit,
+            variables,
+            expressions: Some(&self.expressions),
+            patterns: Some(&self.patterns),
+            limits: Some(limits),
+            scope,
+            extensions: Some(&self.extensions),
+        }
+    }
+}
+
+/// 编译步骤引用的模式与表达式，失败项追加到 problems（extensions 为 None 时不校验自定义扩展）
+fn compile_steps(
+    steps: &[TestStep],
+    extensions: Option<&Extensions>,
+    problems: &mut Vec<String>,
+) -> (Patterns, HashMap<String, CompiledExpr>) {
+    let mut patterns = Patterns::new();
+    let mut expressions = HashMap::new();
+
+    for step in steps {
+        if let Some(rule) = step.engine_task.as_ref().and_then(|t| t.parse_rule.as_ref()) {
+            let validated = patterns.add_rule(rule)
+                .and_then(|_| extensions.map_or(Ok(()), |ext| ext.validate_parse_rule(rule)));
+            if let Err(e) = validated {
+                problems.push(format!("步骤 {}: {}", step.step_id, e));
+            }
+        }
+        if let Some(rule) = &step.check_rule {
+            compile_check_rule(step.step_id, rule, &mut patterns, &mut expressions, problems);
+            if let Some(Err(e)) = extensions.map(|ext| ext.validate_check_rule(rule)) {
+                problems.push(format!("步骤 {}: {}", step.step_id, e));
+            }
+        }
+        if step.execution_mode == ExecutionMode::Calculation && step.calculations.is_empty() {
+            problems.push(format!("步骤 {}: 计算步骤缺少计算项", step.step_id));
+        }
+        for calc in &step.calculations {
+            if expressions.contains_key(&calc.expr) {
+                continue;
+            }
+            match CompiledExpr::compile(&calc.expr) {
+                Ok(compiled) => {
+                    expressions.insert(calc.expr.clone(), compiled);
+                }
+                Err(e) => problems.push(format!("步骤 {}: {}", step.step_id, e)),
+            }
+        }
+        if let Some(band) = &step.guard_band {
+            if band.percent < 0.0 || band.absolute < 0.0 {
+                problems.push(format!("步骤 {}: 保护带宽度不能为负", step.step_id));
+            }
+        }
+    }
+
+    (patterns, expressions)
+}
+
+/// 编译检查规则（含组合检查的子规则）中的表达式与正则
+fn compile_check_rule(
+    step_id: u32,
+    rule: &CheckRule,
+    patterns: &mut Patterns,
+    expressions: &mut HashMap<String, CompiledExpr>,
+    problems: &mut Vec<String>,
+) {
+    match rule {
+        CheckRule::Expression { expr } if !expressions.contains_key(expr) => match CompiledExpr::compile(expr) {
+            Ok(compiled) => {
+                expressions.insert(expr.clone(), compiled);
+            }
+            Err(e) => problems.push(format!("步骤 {}: {}", step_id, e)),
+        },
+        CheckRule::RegexMatch { pattern, .. } => {
+            if let Err(e) = patterns.add_regex(pattern) {
+                problems.push(format!("步骤 {}: {}", step_id, e));
+            }
+        }
+        CheckRule::All { rules } | CheckRule::Any { rules } => {
+            rules.iter().for_each(|r| compile_check_rule(step_id, r, patterns, expressions, problems));
+        }
+        CheckRule::Not { rule } => compile_check_rule(step_id, rule, patterns, expressions, problems),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{EngineTask, ParseRule};
+
+    fn step(step_id: u32, pattern: &str, expr: &str) -> TestStep {
+        TestStep {
+            step_id,
+            engine_task: Some(EngineTask {
+                parse_rule: Some(ParseRule::Regex { pattern: pattern.to_string(), group: 0, value_type: Default::default() }),
+                ..Default::default()
+            }),
+            check_rule: Some(CheckRule::Expression { expr: expr.to_string() }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_compile_shares_patterns() {
+        let plan = CompiledPlan::compile(None, vec![step(1, r"\d+", "x > 1"), step(2, r"\d+", "x > 1")], Default::default()).unwrap();
+        assert_eq!(plan.patterns.len(), 1);
+        assert_eq!(plan.expressions.len(), 1);
+        assert_eq!(plan.steps.len(), 2);
+    }
+
+    #[test]
+    fn test_compile_reports_all_problems() {
+        let err = CompiledPlan::compile(None, vec![step(1, "(", "x > 1"), step(2, r"\d+", "(x > 1")], Default::default())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("步骤 1") && err.contains("步骤 2"), "{}", err);
+    }
+
+    #[test]
+    fn test_compile_nested_expressions() {
+        let mut nested = step(1, r"\d+", "x > 1");
+        nested.check_rule = Some(CheckRule::All {
+            rules: vec![
+                CheckRule::Expression { expr: "x > 1".into() },
+                CheckRule::Not { rule: Box::new(CheckRule::Expression { expr: "x > 5".into() }) },
+            ],
+        });
+        let plan = CompiledPlan::compile(None, vec![nested], Default::default()).unwrap();
+        assert_eq!(plan.expressions.len(), 2);
+    }
+}
@@ -1,14 +1,19 @@
 //! CatEngine 主结构
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ffi::c_void;
 use std::sync::Arc;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use tokio::runtime::Runtime;
 
+use crate::core::compiled::CompiledPlan;
+use crate::core::extension::{CustomChecker, CustomParser, Extensions};
 use crate::core::slot::SlotContext;
-use crate::model::{DeviceType, TestStep, SlotBinding, DeviceInstance};
-use crate::ffi::callback::{EngineTaskCallback, HostTaskCallback, UIUpdateCallback, LogCallback};
+use crate::core::spc::SpcMonitor;
+use crate::core::trace::TraceRecorder;
+use crate::model::{DeviceType, TestStep, SlotBinding, DeviceInstance, TestPlan, PlanAssignment, PlanPackage, PackageMetadata, StepLimit, LimitConfig, LimitSource};
+use crate::storage::migration::{self, MigrationReport, CURRENT_SCHEMA_VERSION};
+use crate::ffi::callback::{EngineTaskCallback, HostTaskCallback, ExternalCheckCallback, UIUpdateCallback, LogCallback};
 use crate::error::{EngineError, Result};
 
 /// 回调函数集合
@@ -19,6 +24,9 @@ pub struct Callbacks {
     pub host_task: Option<HostTaskCallback>,
     pub host_task_user_data: *mut c_void,
 
+    pub external_check: Option<ExternalCheckCallback>,
+    pub external_check_user_data: *mut c_void,
+
     pub ui_update: Option<UIUpdateCallback>,
     pub ui_update_user_data: *mut c_void,
     
@@ -38,6 +46,8 @@ impl Default for Callbacks {
             engine_task_user_data: std::ptr::null_mut(),
             host_task: None,
             host_task_user_data: std::ptr::null_mut(),
+            external_check: None,
+            external_check_user_data: std::ptr::null_mut(),
             ui_update: None,
             ui_update_user_data: std::ptr::null_mut(),
             log: None,
@@ -114,6 +124,37 @@ impl Callbacks {
         )
     }
 
+    /// 调用外部检查回调
+    pub fn call_external_check(
+        &self,
+        slot_id: u32,
+        task_id: u64,
+        step_id: u32,
+        value_json: &str,
+        variables_json: &str,
+        timeout_ms: u32,
+    ) -> i32 {
+        use std::ffi::CString;
+
+        let callback = match self.external_check {
+            Some(cb) => cb,
+            None => return -1,
+        };
+
+        let value_c = CString::new(value_json).unwrap_or_default();
+        let variables_c = CString::new(variables_json).unwrap_or_default();
+
+        callback(
+            slot_id,
+            task_id,
+            step_id,
+            value_c.as_ptr(),
+            variables_c.as_ptr(),
+            timeout_ms,
+            self.external_check_user_data,
+        )
+    }
+
     /// 调用 UI 更新回调
     pub fn call_ui_update(&self, json: &str) {
         use std::ffi::CString;
@@ -138,9 +179,18 @@ pub struct CatEngine {
     /// 设备类型配置 {type_name: DeviceType}
     device_types: HashMap<String, DeviceType>,
 
-    /// 测试步骤列表
+    /// 测试步骤列表（默认方案）
     test_steps: Vec<TestStep>,
 
+    /// 命名测试方案 {plan_name: TestPlan}
+    test_plans: HashMap<String, TestPlan>,
+
+    /// 方案分配（按槽位 / SN 前缀）
+    plan_assignment: PlanAssignment,
+
+    /// 外部限值表（`@名称` 引用）
+    limits: LimitConfig,
+
     /// 槽位绑定配置
     pub slot_bindings: Vec<SlotBinding>,
 
@@ -154,10 +204,45 @@ pub struct CatEngine {
     runtime: Runtime,
     
     /// 数据存储（设置 data_path 后初始化）
-    storage: Option<crate::storage::Storage>,
+    storage: Option<Arc<crate::storage::Storage>>,
+
+    /// I/O 追踪记录器
+    trace: Arc<TraceRecorder>,
+
+    /// SPC 滚动统计
+    spc: Arc<SpcMonitor>,
     
     /// 数据目录路径
     data_path: Option<String>,
+
+    /// 最近一次迁移报告（加载存储或导入方案包时生成）
+    last_migration_report: Option<MigrationReport>,
+
+    /// 进行中的配置事务（开始时的配置快照）
+    transaction: Option<ConfigSnapshot>,
+
+    /// 最近一次宽松加载（cat_engine_load_config）发现的配置问题
+    config_warnings: Vec<String>,
+
+    /// 预编译方案缓存 {方案名称（None 为默认步骤）: 编译结果}，配置变更时清空
+    compiled: Mutex<HashMap<Option<String>, Arc<CompiledPlan>>>,
+
+    /// 自定义解析器与检查器注册表（不持久化，由宿主在加载配置前注册）
+    extensions: Arc<Extensions>,
+}
+
+/// 配置快照（用于事务回滚）
+struct ConfigSnapshot {
+    /// 事务开始时的槽位上下文（回滚时原样恢复，保留运行状态、SN 与结果）
+    slots: Vec<Arc<RwLock<SlotContext>>>,
+    /// 各槽位事务开始时的设备绑定
+    slot_devices: Vec<HashMap<String, DeviceInstance>>,
+    device_types: HashMap<String, DeviceType>,
+    test_steps: Vec<TestStep>,
+    test_plans: HashMap<String, TestPlan>,
+    plan_assignment: PlanAssignment,
+    limits: LimitConfig,
+    slot_bindings: Vec<SlotBinding>,
 }
 
 impl CatEngine {
@@ -175,12 +260,22 @@ impl CatEngine {
             slots,
             device_types: HashMap::new(),
             test_steps: Vec::new(),
+            test_plans: HashMap::new(),
+            plan_assignment: PlanAssignment::default(),
+            limits: LimitConfig::default(),
             slot_bindings: Vec::new(),
             callbacks: Arc::new(RwLock::new(Callbacks::default())),
             task_registry: Arc::new(crate::core::task::TaskRegistry::new()),
             runtime,
             storage: None,
+            trace: Arc::new(TraceRecorder::new()),
+            spc: Arc::new(SpcMonitor::new()),
             data_path: None,
+            last_migration_report: None,
+            transaction: None,
+            config_warnings: Vec::new(),
+            compiled: Mutex::new(HashMap::new()),
+            extensions: Arc::new(Extensions::default()),
         })
     }
 
@@ -200,8 +295,10 @@ impl CatEngine {
         
         // 打开 redb 数据库
         let db_path = path_obj.join("engine.db");
-        let storage = crate::storage::Storage::open(db_path.to_str().unwrap_or(path))?;
+        let storage = Arc::new(crate::storage::Storage::open(db_path.to_str().unwrap_or(path))?);
         
+        self.trace.set_storage(Some(Arc::clone(&storage)));
+        self.spc.set_storage(Some(Arc::clone(&storage)));
         self.storage = Some(storage);
         self.data_path = Some(path.to_string());
         
@@ -216,14 +313,25 @@ impl CatEngine {
         self.data_path.as_deref()
     }
     
-    /// 将当前配置保存到存储
+    /// 将当前配置保存到存储（事务进行中时推迟到提交）
+    ///
+    /// 所有配置修改都经过此处，同时清空预编译方案缓存
     fn save_to_storage(&self) -> Result<()> {
+        self.compiled.lock().clear();
+        if self.transaction.is_some() {
+            return Ok(());
+        }
+
         if let Some(storage) = &self.storage {
             // 构建完整配置 JSON（包含 slot_count）
             let config = serde_json::json!({
+                "schema_version": CURRENT_SCHEMA_VERSION,
                 "slot_count": self.slots.len(),
                 "device_types": &self.device_types,
                 "test_steps": &self.test_steps,
+                "test_plans": &self.test_plans,
+                "plan_assignment": &self.plan_assignment,
+                "limits": &self.limits,
                 "slot_bindings": &self.slot_bindings,
             });
             
@@ -252,10 +360,22 @@ impl CatEngine {
                     #[serde(default)]
                     test_steps: Vec<TestStep>,
                     #[serde(default)]
+                    test_plans: HashMap<String, TestPlan>,
+                    #[serde(default)]
+                    plan_assignment: PlanAssignment,
+                    #[serde(default)]
+                    limits: LimitConfig,
+                    #[serde(default)]
                     slot_bindings: Vec<SlotBinding>,
                 }
                 
-                let config: StoredConfig = serde_json::from_slice(&bytes)
+                let mut doc: serde_json::Value = serde_json::from_slice(&bytes)
+                    .map_err(|e| EngineError::InternalError(format!("反序列化配置失败: {}", e)))?;
+
+                // 升级旧版本配置
+                let report = migration::migrate(&mut doc)?;
+
+                let config: StoredConfig = serde_json::from_value(doc)
                     .map_err(|e| {
                         eprintln!("[Engine] deserialization failed: {}", e);
                         EngineError::InternalError(format!("反序列化配置失败: {}", e))
@@ -287,9 +407,21 @@ impl CatEngine {
                 
                 // 恢复测试步骤
                 self.test_steps = config.test_steps;
+
+                // 恢复命名方案及分配（方案名以 key 为准）
+                self.test_plans = config.test_plans
+                    .into_iter()
+                    .map(|(name, mut plan)| {
+                        plan.name = name.clone();
+                        (name, plan)
+                    })
+                    .collect();
+                self.plan_assignment = config.plan_assignment;
+                self.limits = config.limits;
                 
                 // 恢复槽位绑定
                 self.slot_bindings = config.slot_bindings;
+                self.compiled.lock().clear();
 
                 // 强制同步：将恢复的绑定应用到槽位运行时
                 for slot_id in 0..self.slots.len() {
@@ -299,6 +431,13 @@ impl CatEngine {
                 }
                 
                 eprintln!("[Engine] loaded {} device types, {} slots into memory", self.device_types.len(), self.slots.len());
+
+                // 迁移后的配置立即写回，避免每次启动重复迁移
+                if report.migrated() {
+                    eprintln!("[Engine] config migrated from v{} to v{}: {:?}", report.from_version, report.to_version, report.changes);
+                    self.last_migration_report = Some(report);
+                    self.save_to_storage()?;
+                }
             } else {
                 eprintln!("[Engine] no full_config found in storage");
             }
@@ -308,6 +447,145 @@ impl CatEngine {
         Ok(())
     }
 
+    /// 获取最近一次迁移报告
+    pub fn last_migration_report(&self) -> Option<&MigrationReport> {
+        self.last_migration_report.as_ref()
+    }
+
+    /// 记录迁移报告（供 FFI 加载配置时使用）
+    pub(crate) fn record_migration_report(&mut self, report: MigrationReport) {
+        self.last_migration_report = Some(report);
+    }
+
+    // ========== 方案包导入导出 ==========
+
+    /// 导出方案包
+    ///
+    /// plan_name 为 None 时导出默认步骤列表。只导出方案引用到的设备类型，且不含设备实例。
+    pub fn export_package(&self, plan_name: Option<&str>, mut metadata: PackageMetadata) -> Result<PlanPackage> {
+        let plan = match plan_name {
+            Some(name) => self.test_plans
+                .get(name)
+                .cloned()
+                .ok_or_else(|| EngineError::PlanNotFound(name.to_string()))?,
+            None => TestPlan {
+                steps: self.test_steps.clone(),
+                ..Default::default()
+            },
+        };
+
+        let mut device_types = HashMap::new();
+        let mut commands = HashMap::new();
+        for step in &plan.steps {
+            let Some(task) = &step.engine_task else { continue };
+            if device_types.contains_key(&task.target_device) {
+                continue;
+            }
+            if let Some(dt) = self.device_types.get(&task.target_device) {
+                commands.insert(task.target_device.clone(), dt.commands.clone());
+                device_types.insert(task.target_device.clone(), DeviceType {
+                    type_name: dt.type_name.clone(),
+                    name: dt.name.clone(),
+                    plugin_id: dt.plugin_id.clone(),
+                    instances: Vec::new(),
+                    commands: Vec::new(),
+                });
+            }
+        }
+
+        let referenced: HashSet<&str> = plan.steps
+            .iter()
+            .filter_map(|s| s.check_rule.as_ref())
+            .flat_map(|rule| rule.limit_values())
+            .filter_map(|limit| match limit.source() {
+                Ok(LimitSource::Named(name)) => Some(name),
+                _ => None,
+            })
+            .collect();
+        let named_limits = self.limits.subset(&referenced);
+
+        let limits = plan.steps
+            .iter()
+            .filter_map(|s| s.check_rule.as_ref().map(|rule| StepLimit {
+                step_id: s.step_id,
+                step_name: s.step_name.clone(),
+                check_rule: rule.clone(),
+            }))
+            .collect();
+
+        if metadata.created_at.is_empty() {
+            metadata.created_at = chrono::Local::now().to_rfc3339();
+        }
+        metadata.engine_version = env!("CARGO_PKG_VERSION").to_string();
+
+        let mut package = PlanPackage {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            metadata,
+            plan,
+            device_types,
+            commands,
+            limits,
+            named_limits,
+            checksum: String::new(),
+        };
+        package.seal();
+        Ok(package)
+    }
+
+    /// 导入方案包
+    ///
+    /// 校验 checksum → 迁移 schema → 应用限值 → 合并设备类型（保留本工位的设备实例）→ 写入方案。
+    /// 方案名为空时替换默认步骤列表，否则添加或替换同名方案。
+    pub fn import_package(&mut self, json: &str) -> Result<MigrationReport> {
+        let mut doc: serde_json::Value = serde_json::from_str(json)?;
+
+        let checksum = doc.get("checksum").and_then(|v| v.as_str()).unwrap_or_default();
+        if checksum.is_empty() {
+            return Err(EngineError::PackageError("缺少 checksum".to_string()));
+        }
+        if !checksum.eq_ignore_ascii_case(&crate::model::package::checksum_of(&doc)) {
+            return Err(EngineError::PackageError("checksum 不匹配，方案包可能已损坏或被修改".to_string()));
+        }
+
+        let mut report = migration::migrate(&mut doc)?;
+        let mut package: PlanPackage = serde_json::from_value(doc)?;
+        check_compilable(&format!("方案包 {}", package.plan.name), &package.plan.steps)?;
+
+        let applied = package.apply_limits();
+        if applied > 0 {
+            report.changes.push(format!("应用 {} 个步骤限值", applied));
+        }
+        let merged = self.limits.merge(std::mem::take(&mut package.named_limits));
+        if merged > 0 {
+            report.changes.push(format!("合并 {} 个命名限值", merged));
+        }
+
+        for (name, mut device_type) in package.device_types {
+            device_type.type_name = name.clone();
+            device_type.commands = package.commands.remove(&name).unwrap_or_default();
+            if let Some(existing) = self.device_types.get(&name) {
+                device_type.instances = existing.instances.clone();
+                report.changes.push(format!("更新设备类型 {}（保留 {} 个实例）", name, device_type.instances.len()));
+            } else {
+                report.changes.push(format!("新增设备类型 {}", name));
+            }
+            self.device_types.insert(name, device_type);
+        }
+
+        let plan = package.plan;
+        if plan.name.is_empty() {
+            report.changes.push(format!("替换默认步骤列表（{} 个步骤）", plan.steps.len()));
+            self.test_steps = plan.steps;
+        } else {
+            report.changes.push(format!("导入方案 {}（{} 个步骤）", plan.name, plan.steps.len()));
+            self.test_plans.insert(plan.name.clone(), plan);
+        }
+
+        self.save_to_storage()?;
+        self.last_migration_report = Some(report.clone());
+        Ok(report)
+    }
+
     /// 获取槽位数量
     pub fn slot_count(&self) -> u32 {
         self.slots.len() as u32
@@ -348,6 +626,8 @@ impl CatEngine {
             }
         } else if new < current {
             self.slots.truncate(new);
+            // 移除已删除槽位的方案分配，避免之后扩容时旧分配重新生效
+            self.plan_assignment.slots.retain(|slot_id, _| *slot_id < new_count);
         }
         
         // 保存到持久化存储
@@ -406,6 +686,7 @@ impl CatEngine {
 
     /// 添加测试步骤
     pub fn add_test_step(&mut self, step: TestStep) -> Result<()> {
+        check_compilable("默认步骤", std::slice::from_ref(&step))?;
         self.test_steps.push(step);
         self.save_to_storage()?;
         Ok(())
@@ -413,6 +694,7 @@ impl CatEngine {
 
     /// 更新测试步骤
     pub fn update_test_step(&mut self, step_id: u32, new_step: TestStep) -> Result<()> {
+        check_compilable("默认步骤", std::slice::from_ref(&new_step))?;
         let idx = self.test_steps
             .iter()
             .position(|s| s.step_id == step_id)
@@ -449,6 +731,161 @@ impl CatEngine {
         Ok(())
     }
 
+    // ========== 测试方案管理 ==========
+
+    /// 添加或替换命名测试方案
+    pub fn add_test_plan(&mut self, name: String, mut plan: TestPlan) -> Result<()> {
+        check_compilable(&format!("方案 {}", name), &plan.steps)?;
+        plan.name = name.clone();
+        self.test_plans.insert(name, plan);
+        self.save_to_storage()?;
+        Ok(())
+    }
+
+    /// 移除命名测试方案（同时清除引用它的分配）
+    pub fn remove_test_plan(&mut self, name: &str) -> Result<()> {
+        self.test_plans
+            .remove(name)
+            .ok_or_else(|| EngineError::PlanNotFound(name.to_string()))?;
+        self.plan_assignment.remove_plan(name);
+        self.save_to_storage()?;
+        Ok(())
+    }
+
+    /// 获取命名测试方案
+    pub fn get_test_plan(&self, name: &str) -> Option<&TestPlan> {
+        self.test_plans.get(name)
+    }
+
+    /// 获取所有命名测试方案
+    pub fn get_test_plans(&self) -> &HashMap<String, TestPlan> {
+        &self.test_plans
+    }
+
+    /// 获取方案分配配置
+    pub fn get_plan_assignment(&self) -> &PlanAssignment {
+        &self.plan_assignment
+    }
+
+    /// 为槽位分配方案（None 表示恢复为按 SN / 默认方案选择）
+    pub fn set_slot_plan(&mut self, slot_id: u32, plan: Option<String>) -> Result<()> {
+        if slot_id >= self.slots.len() as u32 {
+            return Err(EngineError::InvalidSlotId(slot_id));
+        }
+
+        match plan {
+            Some(name) => {
+                if !self.test_plans.contains_key(&name) {
+                    return Err(EngineError::PlanNotFound(name));
+                }
+                self.plan_assignment.slots.insert(slot_id, name);
+            }
+            None => {
+                self.plan_assignment.slots.remove(&slot_id);
+            }
+        }
+
+        self.save_to_storage()?;
+        Ok(())
+    }
+
+    /// 设置 SN 前缀规则（None 表示移除该前缀规则）
+    pub fn set_sn_plan_rule(&mut self, prefix: String, plan: Option<String>) -> Result<()> {
+        match plan {
+            Some(name) => {
+                if !self.test_plans.contains_key(&name) {
+                    return Err(EngineError::PlanNotFound(name));
+                }
+                self.plan_assignment.set_sn_rule(prefix, name);
+            }
+            None => {
+                self.plan_assignment.remove_sn_rule(&prefix);
+            }
+        }
+
+        self.save_to_storage()?;
+        Ok(())
+    }
+
+    /// 解析槽位当前应执行的方案
+    ///
+    /// 返回 (方案名称, 步骤列表)，方案名称为 None 表示默认步骤列表
+    pub fn resolve_slot_plan(&self, slot_id: u32) -> Result<(Option<String>, Vec<TestStep>)> {
+        let slot = self.get_slot(slot_id)?;
+        let sn = slot.read().sn.clone();
+
+        match self.plan_assignment.resolve(slot_id, sn.as_deref()) {
+            Some(name) => {
+                let plan = self.test_plans
+                    .get(name)
+                    .ok_or_else(|| EngineError::PlanNotFound(name.to_string()))?;
+                Ok((Some(name.to_string()), plan.steps.clone()))
+            }
+            None => Ok((None, self.test_steps.clone())),
+        }
+    }
+
+    /// 获取槽位当前方案的预编译结果（按方案缓存，所有槽位共享）
+    pub fn compiled_slot_plan(&self, slot_id: u32) -> Result<Arc<CompiledPlan>> {
+        let (name, steps) = self.resolve_slot_plan(slot_id)?;
+        self.compile_cached(name, steps)
+    }
+
+    /// 按名称获取方案的预编译结果（None 表示默认步骤列表）
+    pub fn compiled_plan(&self, name: Option<&str>) -> Result<Arc<CompiledPlan>> {
+        let steps = match name {
+            Some(name) => self.test_plans
+                .get(name)
+                .ok_or_else(|| EngineError::PlanNotFound(name.to_string()))?
+                .steps
+                .clone(),
+            None => self.test_steps.clone(),
+        };
+        self.compile_cached(name.map(str::to_string), steps)
+    }
+
+    /// 取缓存的编译结果，未命中时编译并缓存
+    fn compile_cached(&self, name: Option<String>, steps: Vec<TestStep>) -> Result<Arc<CompiledPlan>> {
+        if let Some(plan) = self.compiled.lock().get(&name) {
+            return Ok(Arc::clone(plan));
+        }
+
+        let plan = Arc::new(CompiledPlan::compile(name.clone(), steps, Arc::clone(&self.extensions))?);
+        self.compiled.lock().insert(name, Arc::clone(&plan));
+        Ok(plan)
+    }
+
+    // ========== 限值表 ==========
+
+    /// 获取限值配置
+    pub fn get_limit_config(&self) -> &LimitConfig {
+        &self.limits
+    }
+
+    /// 替换限值配置
+    pub fn set_limit_config(&mut self, limits: LimitConfig) -> Result<()> {
+        self.limits = limits;
+        self.save_to_storage()
+    }
+
+    /// 从 CSV 导入限值（合并到现有配置），返回导入的行数
+    ///
+    /// 任一行格式错误时整体不生效
+    pub fn import_limits_csv(&mut self, csv: &str) -> Result<usize> {
+        let mut limits = self.limits.clone();
+        let count = limits.import_csv(csv)?;
+        self.limits = limits;
+        self.save_to_storage()?;
+        Ok(count)
+    }
+
+    /// 解析槽位当前生效的限值（按槽位与 SN 选择变体）
+    pub fn resolve_slot_limits(&self, slot_id: u32) -> Result<HashMap<String, f64>> {
+        let slot = self.get_slot(slot_id)?;
+        let sn = slot.read().sn.clone();
+        Ok(self.limits.resolve(slot_id, sn.as_deref()))
+    }
+
     // ========== 槽位绑定 ==========
 
     /// 设置槽位绑定（支持多设备）
@@ -505,6 +942,212 @@ impl CatEngine {
         Ok(())
     }
 
+    // ========== 配置事务 ==========
+
+    /// 开始配置事务
+    ///
+    /// 事务期间的修改只作用于内存，不写入存储；提交时统一校验并写入一次
+    pub fn begin_transaction(&mut self) -> Result<()> {
+        if self.transaction.is_some() {
+            return Err(EngineError::TransactionError("已有进行中的事务".to_string()));
+        }
+
+        self.transaction = Some(ConfigSnapshot {
+            slots: self.slots.clone(),
+            slot_devices: self.slots.iter().map(|s| s.read().device_bindings.clone()).collect(),
+            device_types: self.device_types.clone(),
+            test_steps: self.test_steps.clone(),
+            test_plans: self.test_plans.clone(),
+            plan_assignment: self.plan_assignment.clone(),
+            limits: self.limits.clone(),
+            slot_bindings: self.slot_bindings.clone(),
+        });
+        Ok(())
+    }
+
+    /// 是否处于配置事务中
+    pub fn in_transaction(&self) -> bool {
+        self.transaction.is_some()
+    }
+
+    /// 提交配置事务
+    ///
+    /// 校验失败时自动回滚到事务开始前的配置
+    pub fn commit_transaction(&mut self) -> Result<()> {
+        if self.transaction.is_none() {
+            return Err(EngineError::TransactionError("没有进行中的事务".to_string()));
+        }
+
+        if let Err(e) = self.validate_config() {
+            self.abort_transaction()?;
+            return Err(e);
+        }
+
+        self.transaction = None;
+        self.config_warnings.clear();
+        self.save_to_storage()
+    }
+
+    /// 宽松提交配置事务（兼容旧版加载接口）
+    ///
+    /// 一致性问题不阻止提交，作为警告返回并保存，可通过 config_warnings 查询；
+    /// 正则、JSON 路径或表达式无法编译时仍回滚并报错，避免到启动槽位时才失败
+    pub fn commit_transaction_with_warnings(&mut self) -> Result<Vec<String>> {
+        if self.transaction.is_none() {
+            return Err(EngineError::TransactionError("没有进行中的事务".to_string()));
+        }
+
+        let mut plan_names: Vec<&String> = self.test_plans.keys().collect();
+        plan_names.sort();
+        let compiled = check_compilable("默认步骤", &self.test_steps).and_then(|_| {
+            plan_names
+                .into_iter()
+                .try_for_each(|name| check_compilable(&format!("方案 {}", name), &self.test_plans[name].steps))
+        });
+        if let Err(e) = compiled {
+            self.abort_transaction()?;
+            return Err(e);
+        }
+
+        self.transaction = None;
+        self.config_warnings = self.config_problems();
+        self.save_to_storage()?;
+        Ok(self.config_warnings.clone())
+    }
+
+    /// 最近一次宽松加载发现的配置问题
+    pub fn config_warnings(&self) -> &[String] {
+        &self.config_warnings
+    }
+
+    /// 放弃配置事务，恢复事务开始前的配置
+    pub fn abort_transaction(&mut self) -> Result<()> {
+        let snapshot = self.transaction
+            .take()
+            .ok_or_else(|| EngineError::TransactionError("没有进行中的事务".to_string()))?;
+
+        self.device_types = snapshot.device_types;
+        self.test_steps = snapshot.test_steps;
+        self.test_plans = snapshot.test_plans;
+        self.plan_assignment = snapshot.plan_assignment;
+        self.limits = snapshot.limits;
+        self.slot_bindings = snapshot.slot_bindings;
+        self.compiled.lock().clear();
+
+        // 恢复原有槽位对象及其设备绑定（事务中删除的槽位连同状态一并恢复）
+        self.slots = snapshot.slots;
+        for (slot, devices) in self.slots.iter().zip(snapshot.slot_devices) {
+            slot.write().device_bindings = devices;
+        }
+        Ok(())
+    }
+
+    /// 校验当前配置的一致性
+    ///
+    /// 检查步骤 ID 唯一、跳转目标存在、目标设备类型存在、限值引用已定义、槽位绑定引用的设备存在，
+    /// 所有问题合并为一个 ValidationError 返回
+    pub fn validate_config(&self) -> Result<()> {
+        let problems = self.config_problems();
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(EngineError::ValidationError(problems.join("; ")))
+        }
+    }
+
+    /// 收集当前配置的一致性问题
+    fn config_problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        self.validate_steps("默认步骤", &self.test_steps, &mut problems);
+        let mut plan_names: Vec<&String> = self.test_plans.keys().collect();
+        plan_names.sort();
+        for name in plan_names {
+            self.validate_steps(&format!("方案 {}", name), &self.test_plans[name].steps, &mut problems);
+        }
+
+        for binding in &self.slot_bindings {
+            if binding.slot_id >= self.slots.len() as u32 {
+                problems.push(format!("槽位绑定: 槽位 {} 不存在", binding.slot_id));
+            }
+            for (type_name, instance_ids) in &binding.devices {
+                match self.device_types.get(type_name) {
+                    Some(device_type) => {
+                        for id in instance_ids {
+                            if device_type.find_instance(id).is_none() {
+                                problems.push(format!("槽位 {} 绑定: 设备实例 {} 不存在", binding.slot_id, id));
+                            }
+                        }
+                    }
+                    None => problems.push(format!("槽位 {} 绑定: 设备类型 {} 不存在", binding.slot_id, type_name)),
+                }
+            }
+        }
+
+        problems
+    }
+
+    /// 校验单个步骤列表
+    fn validate_steps(&self, scope: &str, steps: &[TestStep], problems: &mut Vec<String>) {
+        let mut ids = std::collections::HashSet::new();
+        for step in steps {
+            if !ids.insert(step.step_id) {
+                problems.push(format!("{}: 步骤 ID {} 重复", scope, step.step_id));
+            }
+        }
+
+        for step in steps {
+            let jumps = [step.next_on_pass, step.next_on_fail, step.next_on_timeout, step.next_on_error];
+            for target in jumps.into_iter().flatten() {
+                if !ids.contains(&target) {
+                    problems.push(format!("{}: 步骤 {} 跳转目标 {} 不存在", scope, step.step_id, target));
+                }
+            }
+
+            for limit in step.check_rule.iter().flat_map(|r| r.limit_values()) {
+                match limit.source() {
+                    Ok(LimitSource::Named(name)) if !self.limits.defines(name) => {
+                        problems.push(format!("{}: 步骤 {} 限值 @{} 未定义", scope, step.step_id, name));
+                    }
+                    Err(e) => problems.push(format!("{}: 步骤 {} {}", scope, step.step_id, e)),
+                    _ => {}
+                }
+            }
+
+            if let Some(task) = &step.engine_task {
+                if !self.device_types.contains_key(&task.target_device) {
+                    problems.push(format!("{}: 步骤 {} 目标设备 {} 不存在", scope, step.step_id, task.target_device));
+                }
+                if let Some(Err(e)) = task.modbus.as_ref().map(crate::protocol::modbus::build_pdu) {
+                    problems.push(format!("{}: 步骤 {} {}", scope, step.step_id, e));
+                }
+            }
+        }
+
+        for problem in CompiledPlan::check(steps, &self.extensions) {
+            problems.push(format!("{}: {}", scope, problem));
+        }
+    }
+
+    // ========== 扩展注册 ==========
+
+    /// 注册自定义解析器（同名覆盖），方案中以 `{"type": "custom", "name": ...}` 引用
+    pub fn register_custom_parser(&mut self, name: &str, parser: Arc<dyn CustomParser>) {
+        Arc::make_mut(&mut self.extensions).register_parser(name, parser);
+        self.compiled.lock().clear();
+    }
+
+    /// 注册自定义检查器（同名覆盖），方案中以 `{"template": "custom", "name": ...}` 引用
+    pub fn register_custom_checker(&mut self, name: &str, checker: Arc<dyn CustomChecker>) {
+        Arc::make_mut(&mut self.extensions).register_checker(name, checker);
+        self.compiled.lock().clear();
+    }
+
+    /// 获取扩展注册表
+    pub fn extensions(&self) -> Arc<Extensions> {
+        Arc::clone(&self.extensions)
+    }
+
     // ========== 回调注册 ==========
 
     /// 注册 EngineTask 回调
@@ -521,6 +1164,13 @@ impl CatEngine {
         callbacks.host_task_user_data = user_data;
     }
 
+    /// 注册外部检查回调
+    pub fn register_external_check_callback(&self, callback: ExternalCheckCallback, user_data: *mut c_void) {
+        let mut callbacks = self.callbacks.write();
+        callbacks.external_check = Some(callback);
+        callbacks.external_check_user_data = user_data;
+    }
+
     /// 注册 UI 更新回调
     pub fn register_ui_callback(&self, callback: UIUpdateCallback, user_data: *mut c_void) {
         let mut callbacks = self.callbacks.write();
@@ -553,4 +1203,24 @@ impl CatEngine {
     pub fn task_registry(&self) -> Arc<crate::core::task::TaskRegistry> {
         Arc::clone(&self.task_registry)
     }
+
+    /// 获取 I/O 追踪记录器
+    pub fn trace_recorder(&self) -> Arc<TraceRecorder> {
+        Arc::clone(&self.trace)
+    }
+
+    /// 获取 SPC 监视器
+    pub fn spc_monitor(&self) -> Arc<SpcMonitor> {
+        Arc::clone(&self.spc)
+    }
+}
+
+/// 校验步骤中的正则、JSON 路径与表达式能否编译，问题合并为一个 ValidationError
+fn check_compilable(scope: &str, steps: &[TestStep]) -> Result<()> {
+    let problems = CompiledPlan::check_patterns(steps);
+    if problems.is_empty() {
+        return Ok(());
+    }
+    let problems: Vec<String> = problems.into_iter().map(|p| format!("{}: {}", scope, p)).collect();
+    Err(EngineError::ValidationError(problems.join("; ")))
 }
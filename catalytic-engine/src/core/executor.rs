@@ -1,5 +1,6 @@
 //! 测试执行器
 
+use std::borrow::Cow;
 use std::time::{Duration, Instant};
 use std::sync::Arc;
 use parking_lot::RwLock;
@@ -7,24 +8,56 @@ use parking_lot::RwLock;
 use crate::core::engine::{CatEngine, Callbacks};
 use crate::core::slot::SlotContext;
 use crate::core::task::{generate_task_id, TaskRegistry, TaskResult};
-use crate::model::{TestStep, ExecutionMode, CheckType, StepResult, StepStatus, Variable, CheckResultDetail, SlotStatus, DeviceType};
-use crate::parser::parse_response;
-use crate::checker::{execute_check, CheckOutput};
+use crate::core::spc::SpcMonitor;
+use crate::core::trace::TraceRecorder;
+use crate::core::replay::ReplayFeed;
+use crate::core::compiled::CompiledPlan;
+use crate::model::{TestStep, ExecutionMode, CheckType, ParseErrorPolicy, RawResponse, StepResult, StepStatus, Variable, VariableDisplay, VariablePool, CheckResultDetail, SlotStatus, DeviceType, TraceRecord, TraceKind, TraceOutcome, ModbusTransport, RunContext};
+use crate::parser::{ParseOutput, ParsedField};
+use crate::protocol::modbus;
+use crate::checker::{execute_check, CheckOutput, CompiledExpr, ExprScope};
+use crate::checker::external::ExternalCheckResult;
+use crate::checker::guard;
 use crate::error::{Result, EngineError};
 use std::collections::HashMap;
 
+/// 外部检查默认超时（毫秒）
+const DEFAULT_CHECK_TIMEOUT_MS: u32 = 5000;
+
+/// 运行结果的记录器（I/O 追踪与 SPC 统计）
+///
+/// 默认值为独立的空记录器，离线回放使用它以免影响引擎的追踪与统计
+#[derive(Default)]
+pub(crate) struct Recorders {
+    pub trace: Arc<TraceRecorder>,
+    pub spc: Arc<SpcMonitor>,
+}
+
+impl Recorders {
+    fn of(engine: &CatEngine) -> Self {
+        Self { trace: engine.trace_recorder(), spc: engine.spc_monitor() }
+    }
+}
+
 /// 执行单个槽位的所有测试步骤（阻塞版本）
 pub fn run_slot(engine: &CatEngine, slot_id: u32) -> Result<()> {
     let slot = engine.get_slot(slot_id)?;
     let callbacks = engine.callbacks();
     let task_registry = engine.task_registry();
-    let steps = engine.get_test_steps().to_vec();
+    let recorders = Recorders::of(engine);
+    let plan = engine.compiled_slot_plan(slot_id)?;
     let device_types = engine.get_device_types_map();
     
-    if steps.is_empty() { return Ok(()); }
+    if plan.steps.is_empty() { return Ok(()); }
+    let limits = engine.resolve_slot_limits(slot_id)?;
+    {
+        let mut g = slot.write();
+        g.plan_name = plan.name.clone();
+        g.limits = limits;
+    }
 
     engine.runtime().block_on(async { 
-        run_slot_async(slot, callbacks, task_registry, steps, device_types).await 
+        run_slot_async(slot, callbacks, task_registry, recorders, plan, device_types, None).await 
     })
 }
 
@@ -33,13 +66,20 @@ pub fn spawn_slot(engine: &CatEngine, slot_id: u32) -> Result<()> {
     let slot = engine.get_slot(slot_id)?;
     let callbacks = engine.callbacks();
     let task_registry = engine.task_registry();
-    let steps = engine.get_test_steps().to_vec();
+    let recorders = Recorders::of(engine);
+    let plan = engine.compiled_slot_plan(slot_id)?;
     let device_types = engine.get_device_types_map();
     
-    if steps.is_empty() { return Ok(()); }
+    if plan.steps.is_empty() { return Ok(()); }
+    let limits = engine.resolve_slot_limits(slot_id)?;
+    {
+        let mut g = slot.write();
+        g.plan_name = plan.name.clone();
+        g.limits = limits;
+    }
 
     engine.runtime().spawn(async move {
-        let _ = run_slot_async(slot, callbacks, task_registry, steps, device_types).await;
+        let _ = run_slot_async(slot, callbacks, task_registry, recorders, plan, device_types, None).await;
     });
 
     Ok(())
@@ -54,24 +94,38 @@ fn finish_slot(slot: &Arc<RwLock<SlotContext>>) {
 }
 
 /// 异步执行槽位测试
-/// 异步执行槽位测试
-async fn run_slot_async(
+pub(crate) async fn run_slot_async(
     slot: Arc<RwLock<SlotContext>>,
     callbacks: Arc<RwLock<Callbacks>>,
     task_registry: Arc<TaskRegistry>,
-    steps: Vec<TestStep>,
+    recorders: Recorders,
+    plan: Arc<CompiledPlan>,
     device_types: HashMap<String, DeviceType>,
+    replay: Option<Arc<ReplayFeed>>,
 ) -> Result<()> {
     use crate::core::slot::ControlSignal;
     use tokio::sync::mpsc;
 
     // [FIX] 启动时设置状态为 Running
-    {
+    let (slot_id, run_id, context) = {
         let mut g = slot.write();
         // 允许从 Idle/Completed/Error 重置为 Running
         g.state_machine.force_state(SlotStatus::Running);
         g.mark_start();
-    }
+        let run_id = g.run_id.clone().unwrap_or_default();
+        let context = RunContext {
+            run_id: run_id.clone(),
+            slot_id: g.slot_id,
+            plan_name: plan.name.clone(),
+            sn: g.sn.clone(),
+            limits: g.limits.clone(),
+        };
+        (g.slot_id, run_id, context)
+    };
+    let trace = &recorders.trace;
+    trace.record_run(context);
+    let scope = RunScope { recorder: trace, run_id: &run_id, replay: replay.as_deref(), plan: &plan };
+    let steps = &plan.steps;
     
     let total = steps.len();
     let mut idx = 0usize;
@@ -87,7 +141,7 @@ async fn run_slot_async(
         // 构造步骤执行的 Future
         // 注意：execute_step 内部包含 check/save 逻辑，如果被 Cancelled，这些逻辑也不会执行
         // 这符合 "Stop" 的语义，但对于 "Pause" 意味着该步骤未完成，Resume 后需要重试
-        let step_future = execute_step(&slot, step, &callbacks, &task_registry, &device_types);
+        let step_future = execute_step(&slot, step, &callbacks, &task_registry, &device_types, &scope);
 
         // 构造信号等待 Future
         let signal_future = async {
@@ -100,30 +154,42 @@ async fn run_slot_async(
 
         // [P0 FIX 1] 使用 select! 同时等待执行结果和控制信号
         tokio::select! {
-            result = step_future => {
+            mut result = step_future => {
                 // --- 步骤执行完成 ---
-                
+
+                // 更新 SPC 统计，过程失控时告警（即使本次仍通过）
+                result.spc_violations = recorders.spc.record(slot_id, &result);
+                if !result.spc_violations.is_empty() {
+                    emit_log(&callbacks, "warn", "spc", &format!(
+                        "Step {} out of statistical control: {:?}",
+                        step.step_id,
+                        result.spc_violations,
+                    ));
+                }
+
                 // 记录结果并推送 UI
-                {
+                let (run_id, seq) = {
                     let mut g = slot.write();
                     g.current_step_index = idx;
                     g.add_step_result(result.clone());
-                }
+                    (g.run_id.clone().unwrap_or_default(), g.step_results.len() as u64 - 1)
+                };
+                trace.record_verdict(&run_id, seq, &result);
                 push_ui_update(&slot, &callbacks, idx, total, Some(step));
 
                 // [P0 FIX 2] 跳转逻辑容错处理
                 let next_idx = match result.status {
                     StepStatus::Passed | StepStatus::Skipped => {
-                        resolve_jump(step.next_on_pass, idx + 1, &steps, &callbacks)
+                        resolve_jump(step.next_on_pass, idx + 1, steps, &callbacks)
                     }
                     StepStatus::Failed => {
-                        resolve_jump(step.next_on_fail, total, &steps, &callbacks)
+                        resolve_jump(step.next_on_fail, total, steps, &callbacks)
                     }
                     StepStatus::Timeout => {
-                        resolve_jump(step.next_on_timeout, total, &steps, &callbacks)
+                        resolve_jump(step.next_on_timeout, total, steps, &callbacks)
                     }
                     StepStatus::Error => {
-                        resolve_jump(step.next_on_error, total, &steps, &callbacks)
+                        resolve_jump(step.next_on_error, total, steps, &callbacks)
                     }
                     _ => total, 
                 };
@@ -222,6 +288,7 @@ async fn execute_step(
     callbacks: &Arc<RwLock<Callbacks>>,
     task_registry: &Arc<TaskRegistry>,
     device_types: &HashMap<String, DeviceType>,
+    scope: &RunScope<'_>,
 ) -> StepResult {
     let start = Instant::now();
     let (slot_id, device_bindings) = {
@@ -231,18 +298,20 @@ async fn execute_step(
 
     let raw_data = match step.execution_mode {
         ExecutionMode::EngineControlled => {
-            execute_engine_controlled(slot_id, step, callbacks, task_registry, &device_bindings, device_types).await
+            execute_engine_controlled(slot_id, step, callbacks, task_registry, &device_bindings, device_types, scope).await
         }
         ExecutionMode::HostControlled => {
-            execute_host_controlled(slot_id, step, callbacks, task_registry).await
+            execute_host_controlled(slot_id, step, callbacks, task_registry, scope).await
         }
+        // 计算步骤不下发任务，表达式在 process_response 中求值
+        ExecutionMode::Calculation => Ok(Vec::new()),
     };
 
     let elapsed_ms = start.elapsed().as_millis() as u32;
 
-    match raw_data {
+    let mut result = match raw_data {
         // [MODIFIED] 传入 callbacks 供 process_response 使用
-        Ok(data) => process_response(slot, step, data, elapsed_ms, callbacks),
+        Ok(data) => process_response(slot, step, data, elapsed_ms, callbacks, scope.plan),
         
         Err(EngineError::Timeout(_)) => {
             emit_log(callbacks, "warn", "executor", &format!("Step {} execution timeout", step.step_id));
@@ -266,6 +335,76 @@ async fn execute_step(
             emit_log(callbacks, "error", "executor", &format!("Step {} unknown error: {}", step.step_id, msg));
             StepResult::failed(step.step_id, step.step_name.clone(), elapsed_ms, msg.clone(), Some(msg))
         }
+    };
+
+    // 外部检查：解析成功后交由 Host 判定
+    if step.check_type == CheckType::External && result.status == StepStatus::Passed {
+        let check = execute_external_check(slot, step, result.final_value.as_ref(), callbacks, task_registry, scope).await;
+        apply_external_check(slot, step, &mut result, check, callbacks);
+        result.elapsed_ms = start.elapsed().as_millis() as u32;
+    }
+
+    // 未通过的步骤附加原始 I/O 追踪
+    if scope.recorder.is_enabled() && !matches!(result.status, StepStatus::Passed | StepStatus::Skipped) {
+        result.trace = Some(scope.recorder.get_step(scope.run_id, step.step_id));
+    }
+
+    result
+}
+
+/// 单次运行的上下文（追踪、回放与预编译方案）
+struct RunScope<'a> {
+    recorder: &'a TraceRecorder,
+    run_id: &'a str,
+    /// 离线回放数据源，存在时以记录的响应代替回调
+    replay: Option<&'a ReplayFeed>,
+    plan: &'a CompiledPlan,
+}
+
+/// 单个任务的追踪信息
+struct TaskTrace<'a> {
+    scope: &'a RunScope<'a>,
+    slot_id: u32,
+    step_id: u32,
+    kind: TraceKind,
+    device: &'a str,
+    address: &'a str,
+    payload: &'a [u8],
+}
+
+impl TaskTrace<'_> {
+    /// 记录任务结果（未开启追踪时不构造记录）
+    fn record(&self, task_id: u64, started: Instant, result: Option<&TaskResult>, rejected: Option<i32>) {
+        if !self.scope.recorder.is_enabled() {
+            return;
+        }
+
+        let (outcome, response, message) = match (rejected, result) {
+            (Some(ret), _) => (TraceOutcome::Rejected, vec![], Some(format!("回调返回错误: {}", ret))),
+            (None, Some(TaskResult::Ok(data))) => (TraceOutcome::Ok, data.clone(), None),
+            (None, Some(TaskResult::Error(msg))) => (TraceOutcome::Error, vec![], Some(msg.clone())),
+            (None, Some(TaskResult::Timeout)) => (TraceOutcome::Timeout, vec![], None),
+            (None, None) => (TraceOutcome::Timeout, vec![], Some("引擎等待超时".to_string())),
+        };
+
+        self.scope.recorder.record(TraceRecord {
+            run_id: self.scope.run_id.to_string(),
+            task_id,
+            slot_id: self.slot_id,
+            step_id: self.step_id,
+            kind: self.kind,
+            device: self.device.to_string(),
+            address: self.address.to_string(),
+            payload: self.payload.to_vec(),
+            response,
+            duration_ms: started.elapsed().as_millis() as u32,
+            outcome,
+            message,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+        });
     }
 }
 
@@ -279,6 +418,7 @@ async fn execute_engine_controlled(
     task_registry: &Arc<TaskRegistry>,
     device_bindings: &HashMap<String, crate::model::DeviceInstance>,
     device_types: &HashMap<String, DeviceType>,
+    trace: &RunScope<'_>,
 ) -> std::result::Result<Vec<u8>, crate::error::EngineError> {
     use crate::error::EngineError;
 
@@ -298,10 +438,37 @@ async fn execute_engine_controlled(
         (String::new(), String::new())
     };
 
+    // Modbus 请求由引擎构建帧，替代 payload
+    let payload = match &task.modbus {
+        Some(request) => Cow::Owned(modbus::build_frame(request, modbus::next_transaction_id())?),
+        None => Cow::Borrowed(task.payload.as_slice()),
+    };
+
+    let task_trace = TaskTrace {
+        scope: trace,
+        slot_id,
+        step_id: step.step_id,
+        kind: TraceKind::Engine,
+        device: device_type_name,
+        address: &device_address,
+        payload: &payload,
+    };
+
     let mut last_data = vec![];
 
     for i in 0..max_iter {
+        // 回放：直接取记录的响应
+        if let Some(feed) = trace.replay {
+            match feed.next(step.step_id, TraceKind::Engine)? {
+                TaskResult::Ok(data) => last_data = data,
+                TaskResult::Timeout => return Err(EngineError::Timeout(timeout as u64)),
+                TaskResult::Error(msg) => return Err(EngineError::ExecutionError(msg)),
+            }
+            continue;
+        }
+
         let task_id = generate_task_id();
+        let started = Instant::now();
         
         // 注册任务，获取接收端
         let rx = task_registry.register(task_id, slot_id);
@@ -309,11 +476,12 @@ async fn execute_engine_controlled(
         // 调用回调（传递真实设备信息）
         let ret = callbacks.read().call_engine_task(
             slot_id, task_id, device_type_name, &device_address, &plugin_id,
-            task.action_type.as_str(), &task.payload, timeout,
+            task.action_type.as_str(), &payload, timeout,
         );
         
         if ret != 0 {
             task_registry.cancel(task_id);
+            task_trace.record(task_id, started, None, Some(ret));
             return Err(EngineError::ExecutionError(format!("回调返回错误: {}", ret)));
         }
 
@@ -325,9 +493,13 @@ async fn execute_engine_controlled(
                 None
             }
         };
+        task_trace.record(task_id, started, result.as_ref(), None);
 
         match result {
             Some(TaskResult::Ok(data)) => {
+                if task.modbus.as_ref().is_some_and(|r| r.transport == ModbusTransport::Tcp) {
+                    modbus::check_transaction(&payload, &data)?;
+                }
                 last_data = data;
             }
 
@@ -351,6 +523,7 @@ async fn execute_host_controlled(
     step: &TestStep,
     callbacks: &Arc<RwLock<Callbacks>>,
     task_registry: &Arc<TaskRegistry>,
+    trace: &RunScope<'_>,
 ) -> std::result::Result<Vec<u8>, crate::error::EngineError> {
     use crate::error::EngineError;
 
@@ -358,6 +531,25 @@ async fn execute_host_controlled(
     let timeout = task.timeout_ms;
     let task_id = generate_task_id();
     let params = serde_json::to_vec(&task.params).unwrap_or_default();
+    let started = Instant::now();
+    let task_trace = TaskTrace {
+        scope: trace,
+        slot_id,
+        step_id: step.step_id,
+        kind: TraceKind::Host,
+        device: &task.task_name,
+        address: "",
+        payload: &params,
+    };
+
+    // 回放：直接取记录的响应
+    if let Some(feed) = trace.replay {
+        return match feed.next(step.step_id, TraceKind::Host)? {
+            TaskResult::Ok(data) => Ok(data),
+            TaskResult::Timeout => Err(EngineError::Timeout(timeout as u64)),
+            TaskResult::Error(msg) => Err(EngineError::ExecutionError(msg)),
+        };
+    }
 
     // 注册任务
     let rx = task_registry.register(task_id, slot_id);
@@ -367,6 +559,7 @@ async fn execute_host_controlled(
     
     if ret != 0 {
         task_registry.cancel(task_id);
+        task_trace.record(task_id, started, None, Some(ret));
         return Err(EngineError::ExecutionError(format!("回调返回错误: {}", ret)));
     }
 
@@ -378,6 +571,7 @@ async fn execute_host_controlled(
             None
         }
     };
+    task_trace.record(task_id, started, result.as_ref(), None);
 
     match result {
         Some(TaskResult::Ok(data)) => Ok(data),
@@ -387,6 +581,100 @@ async fn execute_host_controlled(
     }
 }
 
+/// 外部检查：将解析值与槽位变量交给 Host 判定，等待 cat_engine_submit_check_result
+async fn execute_external_check(
+    slot: &Arc<RwLock<SlotContext>>,
+    step: &TestStep,
+    value: Option<&serde_json::Value>,
+    callbacks: &Arc<RwLock<Callbacks>>,
+    task_registry: &Arc<TaskRegistry>,
+    scope: &RunScope<'_>,
+) -> Result<CheckOutput> {
+    let (slot_id, variables) = {
+        let g = slot.read();
+        (g.slot_id, g.variables.to_json())
+    };
+    let timeout = step.check_timeout_ms.unwrap_or(DEFAULT_CHECK_TIMEOUT_MS);
+    let value = value.cloned().unwrap_or_default();
+    let payload = serde_json::json!({"value": value, "variables": variables}).to_string().into_bytes();
+    let task_trace = TaskTrace {
+        scope,
+        slot_id,
+        step_id: step.step_id,
+        kind: TraceKind::Check,
+        device: &step.step_name,
+        address: "",
+        payload: &payload,
+    };
+
+    let result = match scope.replay {
+        // 回放：直接取记录的判定结果
+        Some(feed) => feed.next(step.step_id, TraceKind::Check)?,
+        None => {
+            let task_id = generate_task_id();
+            let started = Instant::now();
+            let rx = task_registry.register(task_id, slot_id);
+
+            let ret = callbacks.read().call_external_check(
+                slot_id, task_id, step.step_id, &value.to_string(), &variables.to_string(), timeout,
+            );
+            if ret != 0 {
+                task_registry.cancel(task_id);
+                task_trace.record(task_id, started, None, Some(ret));
+                return Err(EngineError::CheckError(format!("外部检查回调返回错误: {}", ret)));
+            }
+
+            let result = tokio::select! {
+                r = rx => r.ok(),
+                _ = tokio::time::sleep(Duration::from_millis(timeout as u64)) => {
+                    task_registry.cancel(task_id);
+                    None
+                }
+            };
+            task_trace.record(task_id, started, result.as_ref(), None);
+            result.unwrap_or(TaskResult::Timeout)
+        }
+    };
+
+    match result {
+        TaskResult::Ok(data) => Ok(ExternalCheckResult::from_slice(&data)?.into_output()),
+        TaskResult::Timeout => Err(EngineError::Timeout(timeout as u64)),
+        TaskResult::Error(msg) => Err(EngineError::CheckError(msg)),
+    }
+}
+
+/// 将外部检查结果写入步骤结果
+fn apply_external_check(
+    slot: &Arc<RwLock<SlotContext>>,
+    step: &TestStep,
+    result: &mut StepResult,
+    check: Result<CheckOutput>,
+    callbacks: &Arc<RwLock<Callbacks>>,
+) {
+    match check {
+        Ok(output) => {
+            result.marginal = marginal_check(step, Some(&output), callbacks);
+            result.status = if output.passed { StepStatus::Passed } else { StepStatus::Failed };
+            result.result_summary = output.summary.clone();
+            result.check_result = Some(CheckResultDetail::from(output));
+        }
+        Err(EngineError::Timeout(ms)) => {
+            emit_log(callbacks, "warn", "check", &format!("Step {} external check timeout ({} ms)", step.step_id, ms));
+            result.status = StepStatus::Timeout;
+            result.result_summary = "外部检查超时".to_string();
+            result.error_message = Some(format!("外部检查 {} ms 内未提交结果", ms));
+        }
+        Err(e) => {
+            let err_msg = e.to_string();
+            slot.write().set_error(err_msg.clone());
+            emit_log(callbacks, "error", "check", &format!("Check execution failed: {}", err_msg));
+            result.status = StepStatus::Error;
+            result.result_summary = format!("检查执行错误: {}", err_msg);
+            result.error_message = Some(err_msg);
+        }
+    }
+}
+
 /// 处理响应数据：解析 → 存变量 → 检查
 fn process_response(
     slot: &Arc<RwLock<SlotContext>>,
@@ -394,24 +682,49 @@ fn process_response(
     data: Vec<u8>,
     elapsed_ms: u32,
     callbacks: &Arc<RwLock<Callbacks>>,
+    plan: &CompiledPlan,
 ) -> StepResult {
     let mut g = slot.write();
-
-    // 解析（链式调用）
-    let parsed = step.engine_task.as_ref()
-        .and_then(|t| t.parse_rule.as_ref())
-        .and_then(|r| parse_response(&data, r).ok())
-        .map(|s| Variable::from_string(&s));
+    let sn = g.sn.clone();
+    let scope = ExprScope { slot_id: Some(g.slot_id), sn: sn.as_deref() };
+
+    // 解析（计算步骤为表达式求值）：失败时按步骤策略直接给出结果，不再进入检查
+    let parsed_output = match step.execution_mode {
+        ExecutionMode::Calculation => calculate(step, &mut g.variables, &scope, plan).map(Some),
+        _ => step.engine_task.as_ref()
+            .and_then(|t| t.parse_rule.as_ref())
+            .map(|r| plan.parse(&data, r))
+            .transpose(),
+    };
+    let output = match parsed_output {
+        Ok(output) => output.unwrap_or_default(),
+        Err(e) => {
+            let err_msg = e.to_string();
+            g.set_error(err_msg.clone());
+            emit_log(callbacks, "error", "parser", &format!("Step {} parse failed: {}", step.step_id, err_msg));
+            return parse_error_result(step, elapsed_ms, err_msg, &data);
+        }
+    };
+    for stage in &output.stages {
+        emit_log(callbacks, "debug", "parser", &format!("Step {} pipeline {}", step.step_id, stage));
+    }
+    let parsed = output.value;
+    let unit = output.unit;
 
     // 存变量
     if let (Some(name), Some(v)) = (&step.save_to, &parsed) {
-        g.variables.set(name, v.clone());
+        g.variables.set_with_unit(name, v.clone(), unit.clone());
+    }
+    let mut variables = HashMap::new();
+    for field in output.fields {
+        variables.insert(field.name.clone(), VariableDisplay::with_unit(&field.value, field.unit.as_deref()));
+        g.variables.set_with_unit(&field.name, field.value, field.unit);
     }
 
     // 执行检查
-    let check_result = if step.check_type != CheckType::None {
+    let check_result = if step.check_type == CheckType::Builtin {
         if let Some(rule) = step.check_rule.as_ref() {
-             match execute_check(rule, parsed.as_ref(), &g.variables) {
+             match execute_check(rule, &plan.check_context(parsed.as_ref(), unit.as_deref(), &g.variables, &g.limits, scope)) {
                  Ok(output) => Some(output),
                  Err(e) => {
                      let err_msg = e.to_string();
@@ -427,9 +740,15 @@ fn process_response(
                          status: StepStatus::Error,
                          elapsed_ms,
                          final_value: parsed.map(|v| serde_json::to_value(&v).unwrap_or_default()),
+                         unit,
                          check_result: None,
                          result_summary: format!("检查执行错误: {}", err_msg),
+                         marginal: false,
+                         spc_violations: Vec::new(),
                          error_message: Some(err_msg),
+                         variables,
+                         raw_response: None,
+                         trace: None,
                      };
                  }
              }
@@ -440,7 +759,45 @@ fn process_response(
         None
     };
 
-    build_result(step, elapsed_ms, parsed, check_result)
+    let marginal = marginal_check(step, check_result.as_ref(), callbacks);
+    let mut result = build_result(step, elapsed_ms, parsed, check_result);
+    result.marginal = marginal;
+    result.unit = unit;
+    result.variables = variables;
+    result
+}
+
+/// 保护带判定：通过但距限值过近时记录告警
+fn marginal_check(step: &TestStep, check: Option<&CheckOutput>, callbacks: &Arc<RwLock<Callbacks>>) -> bool {
+    let marginal = match (step.guard_band.as_ref(), check) {
+        (Some(band), Some(output)) => guard::is_marginal(output, band),
+        _ => false,
+    };
+    if marginal {
+        let summary = check.map(|c| c.summary.as_str()).unwrap_or_default();
+        emit_log(callbacks, "warn", "check", &format!("Step {} marginal pass: {}", step.step_id, summary));
+    }
+    marginal
+}
+
+/// 计算步骤：按顺序求值并写入变量池（后面的计算项可引用前面的结果），最后一项为主值
+fn calculate(step: &TestStep, variables: &mut VariablePool, scope: &ExprScope, plan: &CompiledPlan) -> Result<ParseOutput> {
+    let mut output = ParseOutput::default();
+    for calc in &step.calculations {
+        let compiled = match plan.expressions.get(&calc.expr) {
+            Some(c) => Cow::Borrowed(c),
+            None => Cow::Owned(CompiledExpr::compile(&calc.expr)?),
+        };
+        let value = compiled.eval(variables, scope)?;
+        variables.set_with_unit(&calc.target, value.clone(), calc.unit.clone());
+        output.stages.push(format!("{} = {} → {}", calc.target, calc.expr, value.as_string()));
+        output.fields.push(ParsedField { name: calc.target.clone(), value, unit: calc.unit.clone() });
+    }
+    if let Some(last) = output.fields.last() {
+        output.value = Some(last.value.clone());
+        output.unit = last.unit.clone();
+    }
+    Ok(output)
 }
 
 /// 专用通讯函数：emit_log
@@ -477,9 +834,45 @@ fn build_result(step: &TestStep, elapsed_ms: u32, value: Option<Variable>, check
         step_name: step.step_name.clone(),
         status, elapsed_ms,
         final_value: value.map(|v| serde_json::to_value(&v).unwrap_or_default()),
-        check_result: check.map(|c| CheckResultDetail { template: c.template, params: c.params, actual: c.actual, passed: c.passed }),
+        unit: None,
+        check_result: check.map(CheckResultDetail::from),
         result_summary: summary,
+        marginal: false,
+        spc_violations: Vec::new(),
         error_message: None,
+        variables: HashMap::new(),
+        raw_response: None,
+        trace: None,
+    }
+}
+
+/// 构建解析失败结果（附带截断后的原始响应）
+fn parse_error_result(step: &TestStep, elapsed_ms: u32, err_msg: String, data: &[u8]) -> StepResult {
+    let status = match step.on_parse_error {
+        ParseErrorPolicy::Error => StepStatus::Error,
+        ParseErrorPolicy::Fail => StepStatus::Failed,
+    };
+
+    // 计算步骤没有原始响应
+    let (stage, raw_response) = match step.execution_mode {
+        ExecutionMode::Calculation => ("计算失败", None),
+        _ => ("响应解析失败", Some(RawResponse::new(data))),
+    };
+
+    StepResult {
+        step_id: step.step_id,
+        step_name: step.step_name.clone(),
+        status, elapsed_ms,
+        final_value: None,
+        unit: None,
+        check_result: None,
+        result_summary: format!("{}: {}", stage, err_msg),
+        marginal: false,
+        spc_violations: Vec::new(),
+        error_message: Some(err_msg),
+        variables: HashMap::new(),
+        raw_response,
+        trace: None,
     }
 }
 
@@ -493,6 +886,11 @@ fn push_ui_update(
 ) {
     let g = slot.read();
     let variables = g.variables.to_display_map();
+    let marginal_steps: Vec<u32> = g.step_results.iter().filter(|r| r.marginal).map(|r| r.step_id).collect();
+    let spc_alert_steps: Vec<u32> = g.step_results.iter()
+        .filter(|r| !r.spc_violations.is_empty())
+        .map(|r| r.step_id)
+        .collect();
     
     let json = serde_json::json!({
         "type": "ui_snapshot",
@@ -507,7 +905,9 @@ fn push_ui_update(
             },
             "current_step_name": step.map(|s| s.step_name.clone()),
             "current_step_desc": step.map(|s| s.step_name.clone()),
-            "variables": variables
+            "variables": variables,
+            "marginal_steps": marginal_steps,
+            "spc_alert_steps": spc_alert_steps
         }]
     });
     callbacks.read().call_ui_update(&json.to_string());
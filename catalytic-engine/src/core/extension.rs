@@ -0,0 +1,172 @@
+//! 自定义解析器与检查器注册表
+//!
+//! 领域相关的解析与检查（眼图模板、CRC 校验等）以 trait 实现并按名称注册到引擎，
+//! 方案 JSON 中通过 `{"type": "custom", "name": ..., "params": ...}`（解析规则）
+//! 或 `{"template": "custom", "name": ..., "params": ...}`（检查规则）引用。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::checker::{CheckContext, CheckOutput};
+use crate::error::{EngineError, Result};
+use crate::model::{CheckRule, ParseRule};
+use crate::parser::ParseOutput;
+
+/// 自定义解析器
+pub trait CustomParser: Send + Sync {
+    /// 校验参数（配置校验与方案编译时调用）
+    fn validate(&self, _params: &serde_json::Value) -> Result<()> {
+        Ok(())
+    }
+
+    /// 解析响应数据
+    fn parse(&self, data: &[u8], params: &serde_json::Value) -> Result<ParseOutput>;
+}
+
+/// 自定义检查器
+pub trait CustomChecker: Send + Sync {
+    /// 校验参数（配置校验与方案编译时调用）
+    fn validate(&self, _params: &serde_json::Value) -> Result<()> {
+        Ok(())
+    }
+
+    /// 执行检查（ctx 提供当前值、变量池与限值）
+    fn check(&self, params: &serde_json::Value, ctx: &CheckContext) -> Result<CheckOutput>;
+}
+
+/// 扩展注册表
+#[derive(Clone, Default)]
+pub struct Extensions {
+    parsers: HashMap<String, Arc<dyn CustomParser>>,
+    checkers: HashMap<String, Arc<dyn CustomChecker>>,
+}
+
+impl std::fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parsers: Vec<&String> = self.parsers.keys().collect();
+        let mut checkers: Vec<&String> = self.checkers.keys().collect();
+        parsers.sort();
+        checkers.sort();
+        f.debug_struct("Extensions").field("parsers", &parsers).field("checkers", &checkers).finish()
+    }
+}
+
+impl Extensions {
+    /// 注册解析器（同名覆盖）
+    pub fn register_parser(&mut self, name: impl Into<String>, parser: Arc<dyn CustomParser>) {
+        self.parsers.insert(name.into(), parser);
+    }
+
+    /// 注册检查器（同名覆盖）
+    pub fn register_checker(&mut self, name: impl Into<String>, checker: Arc<dyn CustomChecker>) {
+        self.checkers.insert(name.into(), checker);
+    }
+
+    pub fn parser_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.parsers.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn checker_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.checkers.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn parser(&self, name: &str) -> Result<&dyn CustomParser> {
+        self.parsers
+            .get(name)
+            .map(|p| p.as_ref())
+            .ok_or_else(|| EngineError::ParseError(format!("自定义解析器 '{}' 未注册", name)))
+    }
+
+    pub fn checker(&self, name: &str) -> Result<&dyn CustomChecker> {
+        self.checkers
+            .get(name)
+            .map(|c| c.as_ref())
+            .ok_or_else(|| EngineError::CheckError(format!("自定义检查器 '{}' 未注册", name)))
+    }
+
+    /// 校验解析规则引用的自定义解析器
+    pub fn validate_parse_rule(&self, rule: &ParseRule) -> Result<()> {
+        match rule {
+            ParseRule::Custom { name, params } => self.parser(name)?
+                .validate(params)
+                .map_err(|e| EngineError::ValidationError(format!("自定义解析器 '{}' 参数无效: {}", name, e))),
+            _ => Ok(()),
+        }
+    }
+
+    /// 校验检查规则（含组合检查的子规则）引用的自定义检查器
+    pub fn validate_check_rule(&self, rule: &CheckRule) -> Result<()> {
+        match rule {
+            CheckRule::Custom { name, params } => self.checker(name)?
+                .validate(params)
+                .map_err(|e| EngineError::ValidationError(format!("自定义检查器 '{}' 参数无效: {}", name, e))),
+            CheckRule::All { rules } | CheckRule::Any { rules } => {
+                rules.iter().try_for_each(|r| self.validate_check_rule(r))
+            }
+            CheckRule::Not { rule } => self.validate_check_rule(rule),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Variable, VariablePool};
+
+    /// 字节和解析与校验
+    struct Checksum;
+
+    impl CustomParser for Checksum {
+        fn parse(&self, data: &[u8], _params: &serde_json::Value) -> Result<ParseOutput> {
+            let sum = data.iter().fold(0u8, |acc, b| acc.wrapping_add(*b));
+            Ok(ParseOutput::single(Variable::Int(sum as i64)))
+        }
+    }
+
+    impl CustomChecker for Checksum {
+        fn validate(&self, params: &serde_json::Value) -> Result<()> {
+            params["expected"].as_i64().map(|_| ()).ok_or_else(|| EngineError::CheckError("缺少 expected".into()))
+        }
+
+        fn check(&self, params: &serde_json::Value, ctx: &CheckContext) -> Result<CheckOutput> {
+            let actual = ctx.value.and_then(Variable::as_i64).unwrap_or_default();
+            let passed = Some(actual) == params["expected"].as_i64();
+            Ok(CheckOutput {
+                passed,
+                template: "checksum".to_string(),
+                params: params.clone(),
+                actual: serde_json::json!(actual),
+                summary: format!("checksum {} → {}", actual, if passed { "PASS" } else { "FAIL" }),
+                children: Vec::new(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_registry_validates_params() {
+        let mut ext = Extensions::default();
+        ext.register_parser("checksum", Arc::new(Checksum));
+        ext.register_checker("checksum", Arc::new(Checksum));
+        assert_eq!(ext.checker_names(), vec!["checksum".to_string()]);
+
+        let out = ext.parser("checksum").unwrap().parse(&[1, 2, 3], &serde_json::Value::Null).unwrap();
+        assert!(matches!(out.value, Some(Variable::Int(6))));
+
+        let good: CheckRule = serde_json::from_str(r#"{"template": "custom", "name": "checksum", "params": {"expected": 6}}"#).unwrap();
+        assert!(ext.validate_check_rule(&good).is_ok());
+        let bad = CheckRule::Not { rule: Box::new(CheckRule::Custom { name: "checksum".into(), params: serde_json::json!({}) }) };
+        assert!(ext.validate_check_rule(&bad).unwrap_err().to_string().contains("参数无效"));
+        let unknown = CheckRule::Custom { name: "crc".into(), params: serde_json::Value::Null };
+        assert!(ext.validate_check_rule(&unknown).is_err());
+
+        let pool = VariablePool::new();
+        let value = Variable::Int(6);
+        let ctx = CheckContext { value: Some(&value), ..CheckContext::new(&pool) };
+        assert!(ext.checker("checksum").unwrap().check(&serde_json::json!({"expected": 6}), &ctx).unwrap().passed);
+    }
+}
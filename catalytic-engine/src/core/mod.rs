@@ -5,6 +5,13 @@ pub mod slot;
 pub mod executor;
 pub mod state;
 pub mod task;
+pub mod trace;
+pub mod replay;
+pub mod compiled;
+pub mod extension;
+pub mod spc;
 
 pub use engine::CatEngine;
 pub use slot::SlotContext;
+pub use compiled::CompiledPlan;
+pub use extension::{CustomChecker, CustomParser, Extensions};
@@ -0,0 +1,225 @@
+//! 离线回放
+//!
+//! 使用已记录的 I/O 追踪代替 EngineTaskCallback/HostTaskCallback，
+//! 将（可能已修改的）测试方案重新走一遍解析与检查，并与原始运行的判定对比。
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use parking_lot::{Mutex, RwLock};
+
+use crate::core::engine::{CatEngine, Callbacks};
+use crate::core::compiled::CompiledPlan;
+use crate::core::executor;
+use crate::core::slot::SlotContext;
+use crate::core::task::{TaskRegistry, TaskResult};
+use crate::error::{EngineError, Result};
+use crate::model::{ReplayReport, ReplayStepDiff, StepResult, StepVerdict, TestStep, TraceKind, TraceOutcome, TraceRecord};
+
+/// 回放数据源：按 (步骤 ID, 任务类型) 依次取出记录的响应
+pub struct ReplayFeed {
+    records: Mutex<HashMap<(u32, TraceKind), VecDeque<TraceRecord>>>,
+}
+
+impl ReplayFeed {
+    pub fn new(records: Vec<TraceRecord>) -> Self {
+        let mut grouped: HashMap<(u32, TraceKind), VecDeque<TraceRecord>> = HashMap::new();
+        for record in records {
+            grouped.entry((record.step_id, record.kind)).or_default().push_back(record);
+        }
+        Self { records: Mutex::new(grouped) }
+    }
+
+    /// 取出下一条记录的任务结果
+    ///
+    /// 原始运行中回调被拒绝的任务回放为执行错误；缺少记录时同样返回错误
+    pub fn next(&self, step_id: u32, kind: TraceKind) -> Result<TaskResult> {
+        let record = self.records
+            .lock()
+            .get_mut(&(step_id, kind))
+            .and_then(|queue| queue.pop_front())
+            .ok_or_else(|| EngineError::ExecutionError(format!("回放数据缺失: 步骤 {}", step_id)))?;
+
+        match record.outcome {
+            TraceOutcome::Ok => Ok(TaskResult::Ok(record.response)),
+            TraceOutcome::Timeout => Ok(TaskResult::Timeout),
+            TraceOutcome::Error => Ok(TaskResult::Error(record.message.unwrap_or_default())),
+            TraceOutcome::Rejected => Err(EngineError::ExecutionError(
+                record.message.unwrap_or_else(|| "回调返回错误".to_string()),
+            )),
+        }
+    }
+}
+
+/// 回放一次已记录的运行
+///
+/// 方案、SN 与限值取自运行开始时记录的上下文，槽位此后切换 SKU 或方案不影响回放；
+/// 缺少上下文（旧记录）时退回使用槽位当前的方案、SN 与限值。steps 不为 None 时替换方案步骤。
+/// 回放在独立的槽位上下文中执行，不触发任何回调，也不写入追踪。
+pub fn replay_run(engine: &CatEngine, run_id: &str, steps: Option<Vec<TestStep>>) -> Result<ReplayReport> {
+    let recorder = engine.trace_recorder();
+    let records = recorder.get_run(run_id);
+    let slot_id = records
+        .first()
+        .map(|r| r.slot_id)
+        .ok_or_else(|| EngineError::RunNotFound(run_id.to_string()))?;
+    let context = recorder.get_run_context(run_id);
+
+    let plan = match (steps, &context) {
+        (Some(steps), _) => Arc::new(CompiledPlan::compile(None, steps, engine.extensions())?),
+        (None, Some(context)) => engine.compiled_plan(context.plan_name.as_deref())?,
+        (None, None) => engine.compiled_slot_plan(slot_id)?,
+    };
+
+    // 复制原槽位的设备绑定，保证设备解析一致
+    let mut scratch = SlotContext::new(slot_id);
+    let live = engine.get_slot(slot_id)?;
+    scratch.device_bindings = live.read().device_bindings.clone();
+    match context {
+        Some(context) => {
+            scratch.sn = context.sn;
+            scratch.limits = context.limits;
+        }
+        None => {
+            scratch.sn = live.read().sn.clone();
+            scratch.limits = engine.resolve_slot_limits(slot_id)?;
+        }
+    }
+    let scratch = Arc::new(RwLock::new(scratch));
+
+    engine.runtime().block_on(executor::run_slot_async(
+        Arc::clone(&scratch),
+        Arc::new(RwLock::new(Callbacks::default())),
+        Arc::new(TaskRegistry::new()),
+        executor::Recorders::default(),
+        plan,
+        engine.get_device_types_map(),
+        Some(Arc::new(ReplayFeed::new(records))),
+    ))?;
+
+    let replayed = scratch.read().step_results.clone();
+    Ok(build_report(run_id, slot_id, &recorder.get_verdicts(run_id), &replayed))
+}
+
+/// 按执行顺序对齐原始判定与回放结果（同一步骤多次执行时按出现次数配对）
+fn build_report(run_id: &str, slot_id: u32, original: &[StepVerdict], replayed: &[StepResult]) -> ReplayReport {
+    let mut pending: HashMap<u32, VecDeque<&StepResult>> = HashMap::new();
+    for result in replayed {
+        pending.entry(result.step_id).or_default().push_back(result);
+    }
+
+    let mut steps: Vec<ReplayStepDiff> = original
+        .iter()
+        .map(|verdict| {
+            let result = pending.get_mut(&verdict.step_id).and_then(|q| q.pop_front());
+            ReplayStepDiff {
+                step_id: verdict.step_id,
+                step_name: result.map(|r| r.step_name.clone()).unwrap_or_else(|| verdict.step_name.clone()),
+                original_status: Some(verdict.status),
+                replayed_status: result.map(|r| r.status),
+                original_value: verdict.final_value.clone(),
+                replayed_value: result.and_then(|r| r.final_value.clone()),
+                changed: result.map(|r| r.status) != Some(verdict.status),
+                summary: result.map(|r| r.result_summary.clone()).unwrap_or_default(),
+            }
+        })
+        .collect();
+
+    // 回放中新增执行的步骤
+    for result in replayed {
+        if let Some(extra) = pending.get_mut(&result.step_id).and_then(|q| q.pop_front()) {
+            steps.push(ReplayStepDiff {
+                step_id: extra.step_id,
+                step_name: extra.step_name.clone(),
+                original_status: None,
+                replayed_status: Some(extra.status),
+                original_value: None,
+                replayed_value: extra.final_value.clone(),
+                changed: true,
+                summary: extra.result_summary.clone(),
+            });
+        }
+    }
+
+    let changed = steps.iter().filter(|s| s.changed).count();
+    ReplayReport {
+        run_id: run_id.to_string(),
+        slot_id,
+        steps,
+        changed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::StepStatus;
+
+    fn trace(step_id: u32, outcome: TraceOutcome, response: &[u8]) -> TraceRecord {
+        TraceRecord {
+            run_id: "r1".to_string(),
+            task_id: 0,
+            slot_id: 0,
+            step_id,
+            kind: TraceKind::Engine,
+            device: "dut".to_string(),
+            address: String::new(),
+            payload: vec![],
+            response: response.to_vec(),
+            duration_ms: 0,
+            outcome,
+            message: None,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_feed_returns_records_in_order() {
+        let feed = ReplayFeed::new(vec![
+            trace(1, TraceOutcome::Ok, b"1.0"),
+            trace(1, TraceOutcome::Ok, b"2.0"),
+            trace(2, TraceOutcome::Timeout, b""),
+        ]);
+
+        assert!(matches!(feed.next(1, TraceKind::Engine), Ok(TaskResult::Ok(d)) if d == b"1.0"));
+        assert!(matches!(feed.next(1, TraceKind::Engine), Ok(TaskResult::Ok(d)) if d == b"2.0"));
+        assert!(feed.next(1, TraceKind::Engine).is_err());
+        assert!(matches!(feed.next(2, TraceKind::Engine), Ok(TaskResult::Timeout)));
+        assert!(feed.next(2, TraceKind::Host).is_err());
+    }
+
+    #[test]
+    fn test_build_report_marks_changes() {
+        let original = vec![
+            StepVerdict {
+                run_id: "r1".to_string(),
+                seq: 0,
+                step_id: 1,
+                step_name: "a".to_string(),
+                status: StepStatus::Failed,
+                marginal: false,
+                final_value: None,
+            },
+            StepVerdict {
+                run_id: "r1".to_string(),
+                seq: 1,
+                step_id: 2,
+                step_name: "b".to_string(),
+                status: StepStatus::Passed,
+                marginal: false,
+                final_value: None,
+            },
+        ];
+        let replayed = vec![
+            StepResult::passed(1, "a".to_string(), 0, String::new()),
+            StepResult::passed(2, "b".to_string(), 0, String::new()),
+            StepResult::passed(3, "c".to_string(), 0, String::new()),
+        ];
+
+        let report = build_report("r1", 0, &original, &replayed);
+        assert_eq!(report.steps.len(), 3);
+        assert!(report.steps[0].changed);
+        assert!(!report.steps[1].changed);
+        assert_eq!(report.steps[2].original_status, None);
+        assert_eq!(report.changed, 2);
+    }
+}
@@ -19,6 +19,12 @@ pub enum ControlSignal {
 pub struct SlotContext {
     pub slot_id: u32,
     pub sn: Option<String>,
+    /// 当前执行的命名方案（None 表示默认步骤列表）
+    pub plan_name: Option<String>,
+    /// 本次运行生效的限值表（开始测试时按槽位与 SN 解析）
+    pub limits: HashMap<String, f64>,
+    /// 当前运行 ID（"{开始时间戳}-{slot_id}"，每次开始测试时生成）
+    pub run_id: Option<String>,
     pub state_machine: StateMachine,
     pub device_bindings: HashMap<String, DeviceInstance>,
     pub current_step_index: usize,
@@ -38,6 +44,9 @@ impl SlotContext {
         Self {
             slot_id,
             sn: None,
+            plan_name: None,
+            limits: HashMap::new(),
+            run_id: None,
             state_machine: StateMachine::new(),
             device_bindings: HashMap::new(),
             current_step_index: 0,
@@ -117,12 +126,12 @@ impl SlotContext {
 
     /// [Restored] 标记开始测试
     pub fn mark_start(&mut self) {
-        self.start_time = Some(
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64
-        );
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        self.start_time = Some(now);
+        self.run_id = Some(format!("{}-{}", now, self.slot_id));
         self.end_time = None;
     }
 
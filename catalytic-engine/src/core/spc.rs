@@ -0,0 +1,464 @@
+//! 统计过程控制（SPC）
+//!
+//! 每个 (槽位, 步骤) 保留最近若干次数值测量的滚动窗口，据此计算均值、σ、
+//! 以检查规则导出的限值为规格限的 Cp/Cpk，并按 Western Electric 规则判异。
+//! 若已设置数据目录，样本窗口与窗口大小写入 redb，引擎重启后继续累计；
+//! 样本在锁外由 tokio 阻塞线程池批量落盘，同一序列的多次更新只写最新一次。
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+
+use crate::checker::guard;
+use crate::model::{CheckResultDetail, SpcRule, SpcStats, StepResult, StepStatus};
+use crate::storage::{SpcKey, Storage};
+
+/// 默认滚动窗口大小
+pub const DEFAULT_SPC_WINDOW: usize = 100;
+
+/// 判异所需的最少历史样本数（不含最新一点）
+pub const MIN_CONTROL_SAMPLES: usize = 10;
+
+/// 窗口大小在配置表中的键
+const SPC_WINDOW_KEY: &str = "spc_window";
+
+/// 单个 (槽位, 步骤) 的样本序列
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SpcSeries {
+    step_name: String,
+    count: u64,
+    samples: VecDeque<f64>,
+    #[serde(default)]
+    lsl: Option<f64>,
+    #[serde(default)]
+    usl: Option<f64>,
+    #[serde(default)]
+    violations: Vec<SpcRule>,
+    updated_at: u64,
+}
+
+impl SpcSeries {
+    fn stats(&self, slot_id: u32, step_id: u32) -> SpcStats {
+        let n = self.samples.len();
+        let mean = if n == 0 { 0.0 } else { self.samples.iter().sum::<f64>() / n as f64 };
+        let sigma = std_dev(self.samples.iter().copied(), mean, n);
+        let (cp, cpk) = capability(mean, sigma, self.lsl, self.usl);
+
+        SpcStats {
+            slot_id,
+            step_id,
+            step_name: self.step_name.clone(),
+            count: self.count,
+            samples: n,
+            mean,
+            sigma,
+            min: self.samples.iter().copied().fold(f64::INFINITY, f64::min),
+            max: self.samples.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            last_value: self.samples.back().copied().unwrap_or_default(),
+            lsl: self.lsl,
+            usl: self.usl,
+            cp,
+            cpk,
+            violations: self.violations.clone(),
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+/// 样本标准差（n - 1）
+fn std_dev(samples: impl Iterator<Item = f64>, mean: f64, n: usize) -> f64 {
+    if n < 2 {
+        return 0.0;
+    }
+    (samples.map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1) as f64).sqrt()
+}
+
+/// 过程能力：Cp 需双边规格限，Cpk 取可用一侧的较小值
+fn capability(mean: f64, sigma: f64, lsl: Option<f64>, usl: Option<f64>) -> (Option<f64>, Option<f64>) {
+    if sigma <= 0.0 {
+        return (None, None);
+    }
+    let cp = match (lsl, usl) {
+        (Some(lo), Some(hi)) => Some((hi - lo) / (6.0 * sigma)),
+        _ => None,
+    };
+    let cpu = usl.map(|hi| (hi - mean) / (3.0 * sigma));
+    let cpl = lsl.map(|lo| (mean - lo) / (3.0 * sigma));
+    let cpk = match (cpl, cpu) {
+        (Some(l), Some(u)) => Some(l.min(u)),
+        (one, other) => one.or(other),
+    };
+    (cp, cpk)
+}
+
+/// 对最新一点按 Western Electric 规则判异
+///
+/// 中心线与 σ 取自最新一点之前的样本；各规则只在最新一点参与构成时触发，
+/// 避免对同一段历史重复告警。
+pub fn western_electric(samples: &[f64]) -> Vec<SpcRule> {
+    let Some((&last, history)) = samples.split_last() else {
+        return Vec::new();
+    };
+    if history.len() < MIN_CONTROL_SAMPLES {
+        return Vec::new();
+    }
+    let mean = history.iter().sum::<f64>() / history.len() as f64;
+    let sigma = std_dev(history.iter().copied(), mean, history.len());
+    if sigma <= 0.0 {
+        return Vec::new();
+    }
+
+    let z_last = (last - mean) / sigma;
+    let side = z_last.signum();
+    // 最近 k 点中落在最新一点同侧 limit σ 之外的点数
+    let beyond = |k: usize, limit: f64| {
+        samples[samples.len() - k..].iter().filter(|v| (*v - mean) / sigma * side > limit).count()
+    };
+
+    let mut rules = Vec::new();
+    if z_last.abs() > 3.0 {
+        rules.push(SpcRule::Beyond3Sigma);
+    }
+    if z_last.abs() > 2.0 && beyond(3, 2.0) >= 2 {
+        rules.push(SpcRule::TwoOfThreeBeyond2Sigma);
+    }
+    if z_last.abs() > 1.0 && beyond(5, 1.0) >= 4 {
+        rules.push(SpcRule::FourOfFiveBeyond1Sigma);
+    }
+    if z_last != 0.0 && beyond(8, 0.0) == 8 {
+        rules.push(SpcRule::EightOnOneSide);
+    }
+    rules
+}
+
+/// 从检查结果提取规格限（组合检查取第一个导出数值限值的子项，取反检查不适用）
+fn spec_limits(detail: &CheckResultDetail) -> Option<(Option<f64>, Option<f64>)> {
+    if detail.template == "not" {
+        return None;
+    }
+    guard::spec_limits(&detail.params).or_else(|| detail.children.iter().find_map(spec_limits))
+}
+
+/// 最终值中的数值读数（最终值为带类型标签的变量 JSON）
+fn measured(value: &serde_json::Value) -> Option<f64> {
+    value.as_f64().or_else(|| match value["type"].as_str()? {
+        "int" | "float" => value["value"].as_f64(),
+        _ => None,
+    })
+}
+
+/// 批量写入器：序列更新先进入待写表，由阻塞线程统一提交
+#[derive(Default)]
+struct SpcWriter {
+    pending: Mutex<HashMap<SpcKey, Vec<u8>>>,
+    /// 串行化提交与删除，避免已清除的序列被滞后的提交写回
+    flushing: Mutex<()>,
+    scheduled: AtomicBool,
+}
+
+impl SpcWriter {
+    /// 提交全部待写序列
+    fn flush(&self, storage: &Storage) {
+        let _guard = self.flushing.lock();
+        self.scheduled.store(false, Ordering::SeqCst);
+        let rows: Vec<_> = self.pending.lock().drain().collect();
+        if rows.is_empty() {
+            return;
+        }
+        if let Err(e) = storage.save_spc_batch(&rows) {
+            eprintln!("[SPC] failed to persist {} series: {}", rows.len(), e);
+        }
+    }
+
+    /// 在阻塞线程池中安排一次提交（不在 tokio 运行时内时直接同步提交）
+    fn schedule(self: &Arc<Self>, storage: &Arc<Storage>) {
+        if self.scheduled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                let writer = Arc::clone(self);
+                let storage = Arc::clone(storage);
+                handle.spawn_blocking(move || writer.flush(&storage));
+            }
+            Err(_) => self.flush(storage),
+        }
+    }
+}
+
+/// 序列化样本序列并放入待写表
+fn stage(pending: &mut HashMap<SpcKey, Vec<u8>>, key: SpcKey, series: &SpcSeries) {
+    match serde_json::to_vec(series) {
+        Ok(bytes) => {
+            pending.insert(key, bytes);
+        }
+        Err(e) => eprintln!("[SPC] failed to serialize series {:?}: {}", key, e),
+    }
+}
+
+/// SPC 监视器
+pub struct SpcMonitor {
+    window: AtomicUsize,
+    series: Mutex<HashMap<SpcKey, SpcSeries>>,
+    storage: RwLock<Option<Arc<Storage>>>,
+    writer: Arc<SpcWriter>,
+}
+
+impl Default for SpcMonitor {
+    fn default() -> Self {
+        Self {
+            window: AtomicUsize::new(DEFAULT_SPC_WINDOW),
+            series: Mutex::new(HashMap::new()),
+            storage: RwLock::new(None),
+            writer: Arc::new(SpcWriter::default()),
+        }
+    }
+}
+
+impl Drop for SpcMonitor {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl SpcMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置滚动窗口大小（0 时使用默认值），超出的旧样本立即丢弃
+    ///
+    /// 窗口大小与裁剪后的序列均写入存储。
+    pub fn set_window(&self, window: usize) {
+        let window = if window == 0 { DEFAULT_SPC_WINDOW } else { window };
+        self.window.store(window, Ordering::SeqCst);
+        {
+            let mut all = self.series.lock();
+            let mut pending = self.writer.pending.lock();
+            for (&key, series) in all.iter_mut() {
+                if series.samples.len() > window {
+                    series.samples.drain(..series.samples.len() - window);
+                    stage(&mut pending, key, series);
+                }
+            }
+        }
+
+        if let Some(storage) = self.storage.read().as_ref() {
+            if let Err(e) = storage.save_config(SPC_WINDOW_KEY, &(window as u64).to_le_bytes()) {
+                eprintln!("[SPC] failed to persist window: {}", e);
+            }
+            self.writer.schedule(storage);
+        }
+    }
+
+    /// 当前滚动窗口大小
+    pub fn window(&self) -> usize {
+        self.window.load(Ordering::SeqCst)
+    }
+
+    /// 同步提交全部待写序列
+    pub fn flush(&self) {
+        if let Some(storage) = self.storage.read().as_ref() {
+            self.writer.flush(storage);
+        }
+    }
+
+    /// 设置持久化存储，并加载已保存的窗口大小与样本序列
+    pub fn set_storage(&self, storage: Option<Arc<Storage>>) {
+        self.flush();
+        if let Some(storage) = storage.as_ref() {
+            match storage.load_config(SPC_WINDOW_KEY) {
+                Ok(Some(bytes)) => match <[u8; 8]>::try_from(bytes.as_slice()) {
+                    Ok(raw) => self.window.store(u64::from_le_bytes(raw) as usize, Ordering::SeqCst),
+                    Err(_) => eprintln!("[SPC] invalid stored window"),
+                },
+                Ok(None) => {}
+                Err(e) => eprintln!("[SPC] failed to load window: {}", e),
+            }
+            match storage.load_spc() {
+                Ok(rows) => {
+                    let mut series = self.series.lock();
+                    for (key, bytes) in rows {
+                        match serde_json::from_slice::<SpcSeries>(&bytes) {
+                            Ok(mut s) => {
+                                let window = self.window();
+                                if s.samples.len() > window {
+                                    s.samples.drain(..s.samples.len() - window);
+                                }
+                                series.insert(key, s);
+                            }
+                            Err(e) => eprintln!("[SPC] failed to load series {:?}: {}", key, e),
+                        }
+                    }
+                }
+                Err(e) => eprintln!("[SPC] failed to load series: {}", e),
+            }
+        }
+        *self.storage.write() = storage;
+    }
+
+    /// 记录一次步骤结果，返回最新测量触发的判异规则
+    ///
+    /// 仅记录完成检查（通过或失败）且最终值为数值的结果
+    pub fn record(&self, slot_id: u32, result: &StepResult) -> Vec<SpcRule> {
+        if !matches!(result.status, StepStatus::Passed | StepStatus::Failed) {
+            return Vec::new();
+        }
+        let Some(value) = result.final_value.as_ref().and_then(measured) else {
+            return Vec::new();
+        };
+
+        let key = (slot_id, result.step_id);
+        let window = self.window();
+        let mut all = self.series.lock();
+        let series = all.entry(key).or_default();
+
+        series.step_name = result.step_name.clone();
+        series.count += 1;
+        series.samples.push_back(value);
+        while series.samples.len() > window {
+            series.samples.pop_front();
+        }
+        if let Some((lsl, usl)) = result.check_result.as_ref().and_then(spec_limits) {
+            series.lsl = lsl;
+            series.usl = usl;
+        }
+        series.violations = western_electric(series.samples.make_contiguous());
+        series.updated_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        let violations = series.violations.clone();
+
+        // 持锁时只放入待写表（保持同一序列的写入顺序），落盘在锁外进行
+        let storage = self.storage.read().clone();
+        if let Some(storage) = storage {
+            stage(&mut self.writer.pending.lock(), key, series);
+            drop(all);
+            self.writer.schedule(&storage);
+        }
+
+        violations
+    }
+
+    /// 获取单个 (槽位, 步骤) 的统计
+    pub fn stats(&self, slot_id: u32, step_id: u32) -> Option<SpcStats> {
+        self.series.lock().get(&(slot_id, step_id)).map(|s| s.stats(slot_id, step_id))
+    }
+
+    /// 获取全部统计（按槽位、步骤排序）
+    pub fn all_stats(&self) -> Vec<SpcStats> {
+        let mut stats: Vec<SpcStats> = self.series
+            .lock()
+            .iter()
+            .map(|(&(slot_id, step_id), s)| s.stats(slot_id, step_id))
+            .collect();
+        stats.sort_by_key(|s| (s.slot_id, s.step_id));
+        stats
+    }
+
+    /// 清除统计（key 为 None 时清除全部），用于治具校准或工艺变更后重新累计
+    pub fn reset(&self, key: Option<SpcKey>) {
+        match key {
+            Some(key) => {
+                self.series.lock().remove(&key);
+            }
+            None => self.series.lock().clear(),
+        }
+        if let Some(storage) = self.storage.read().as_ref() {
+            let _guard = self.writer.flushing.lock();
+            let mut pending = self.writer.pending.lock();
+            match key {
+                Some(key) => {
+                    pending.remove(&key);
+                }
+                None => pending.clear(),
+            }
+            drop(pending);
+            if let Err(e) = storage.remove_spc(key) {
+                eprintln!("[SPC] failed to remove series: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(step_id: u32, value: f64, min: f64, max: f64) -> StepResult {
+        let mut result = StepResult::passed(step_id, "vout".to_string(), 1, String::new());
+        result.final_value = Some(serde_json::json!({"type": "float", "value": value}));
+        result.check_result = Some(CheckResultDetail {
+            template: "range_check".to_string(),
+            params: serde_json::json!({"min": min, "max": max}),
+            actual: serde_json::json!(value),
+            passed: true,
+            children: Vec::new(),
+        });
+        result
+    }
+
+    /// 围绕 center 交替波动 ±0.01
+    fn stable(center: f64, n: usize) -> Vec<f64> {
+        (0..n).map(|i| if i % 2 == 0 { center + 0.01 } else { center - 0.01 }).collect()
+    }
+
+    #[test]
+    fn test_western_electric_rules() {
+        let mut samples = stable(3.3, 20);
+        assert!(western_electric(&samples).is_empty());
+
+        samples.push(3.4);
+        assert_eq!(western_electric(&samples), vec![SpcRule::Beyond3Sigma]);
+
+        // 连续 8 点略高于中心线（均在 1σ 内）
+        let mut drift = stable(3.3, 20);
+        drift.extend(std::iter::repeat_n(3.305, 8));
+        assert_eq!(western_electric(&drift), vec![SpcRule::EightOnOneSide]);
+
+        // 历史不足时不判异
+        assert!(western_electric(&[3.3, 3.31, 9.0]).is_empty());
+    }
+
+    #[test]
+    fn test_capability_and_window() {
+        let monitor = SpcMonitor::new();
+        monitor.set_window(10);
+        for v in stable(3.3, 12) {
+            assert!(monitor.record(0, &result(1, v, 3.0, 3.6)).is_empty());
+        }
+
+        let stats = monitor.stats(0, 1).unwrap();
+        assert_eq!((stats.count, stats.samples), (12, 10));
+        assert!((stats.mean - 3.3).abs() < 1e-9);
+        assert_eq!((stats.lsl, stats.usl), (Some(3.0), Some(3.6)));
+        let sigma = stats.sigma;
+        assert!((stats.cp.unwrap() - 0.6 / (6.0 * sigma)).abs() < 1e-9);
+        assert!((stats.cpk.unwrap() - stats.cp.unwrap()).abs() < 1e-9);
+
+        // 非数值与未完成检查的结果不计入
+        let mut error = result(1, 3.3, 3.0, 3.6);
+        error.status = StepStatus::Error;
+        monitor.record(0, &error);
+        let mut text = result(2, 0.0, 3.0, 3.6);
+        text.final_value = Some(serde_json::json!({"type": "string", "value": "A.03"}));
+        monitor.record(0, &text);
+        assert_eq!(monitor.stats(0, 1).unwrap().count, 12);
+        assert!(monitor.stats(0, 2).is_none());
+
+        monitor.reset(Some((0, 1)));
+        assert!(monitor.all_stats().is_empty());
+    }
+
+    #[test]
+    fn test_capability_one_sided() {
+        let (cp, cpk) = capability(9.0, 0.5, None, Some(10.0));
+        assert!(cp.is_none());
+        assert!((cpk.unwrap() - 1.0 / 1.5).abs() < 1e-12);
+        assert_eq!(capability(9.0, 0.0, Some(0.0), Some(10.0)), (None, None));
+    }
+}
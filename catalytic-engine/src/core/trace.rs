@@ -0,0 +1,447 @@
+//! I/O 追踪记录器
+//!
+//! 默认关闭。开启后每个 Engine/Host 任务的收发数据写入内存环形缓冲区，
+//! 若已设置数据目录，同时批量写入 redb 以便按运行 ID 回查。
+//! 每个步骤的判定与运行开始时的上下文（方案、SN、限值）同样被记录，供离线回放对比。
+//!
+//! 落盘在 tokio 阻塞线程池中进行，不占用执行器线程；库中各表分别只保留
+//! 最近 retention 行，超出部分从最早的运行开始删除。
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use parking_lot::{Mutex, RwLock};
+
+use crate::model::{RunContext, StepResult, StepVerdict, TraceRecord};
+use crate::storage::{RunEntry, Storage};
+
+/// 默认环形缓冲区容量
+pub const DEFAULT_TRACE_CAPACITY: usize = 1024;
+
+/// 默认库内保留行数（追踪表、判定表与运行上下文表各自计数）
+pub const DEFAULT_TRACE_RETENTION: usize = 100_000;
+
+/// 待落盘的记录
+#[derive(Default)]
+struct PendingRows {
+    traces: Vec<RunEntry>,
+    verdicts: Vec<RunEntry>,
+    runs: Vec<RunEntry>,
+}
+
+/// 批量写入器：记录先进入待写队列，由阻塞线程统一提交
+#[derive(Default)]
+struct TraceWriter {
+    pending: Mutex<PendingRows>,
+    /// 串行化提交，保证读取前的 flush 能等到正在进行的写入完成
+    flushing: Mutex<()>,
+    scheduled: AtomicBool,
+}
+
+impl TraceWriter {
+    /// 提交全部待写记录
+    fn flush(&self, storage: &Storage, retention: usize) {
+        let _guard = self.flushing.lock();
+        self.scheduled.store(false, Ordering::SeqCst);
+        let rows = std::mem::take(&mut *self.pending.lock());
+        let count = rows.traces.len() + rows.verdicts.len() + rows.runs.len();
+        if count == 0 {
+            return;
+        }
+        if let Err(e) = storage.save_trace_batch(&rows.traces, &rows.verdicts, &rows.runs, retention) {
+            eprintln!("[Trace] failed to persist {} records: {}", count, e);
+        }
+    }
+}
+
+/// I/O 追踪记录器
+pub struct TraceRecorder {
+    enabled: AtomicBool,
+    capacity: AtomicUsize,
+    retention: AtomicUsize,
+    buffer: Mutex<VecDeque<TraceRecord>>,
+    verdicts: Mutex<VecDeque<StepVerdict>>,
+    runs: Mutex<VecDeque<RunContext>>,
+    storage: RwLock<Option<Arc<Storage>>>,
+    writer: Arc<TraceWriter>,
+}
+
+impl Default for TraceRecorder {
+    fn default() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            capacity: AtomicUsize::new(DEFAULT_TRACE_CAPACITY),
+            retention: AtomicUsize::new(DEFAULT_TRACE_RETENTION),
+            buffer: Mutex::new(VecDeque::new()),
+            verdicts: Mutex::new(VecDeque::new()),
+            runs: Mutex::new(VecDeque::new()),
+            storage: RwLock::new(None),
+            writer: Arc::new(TraceWriter::default()),
+        }
+    }
+}
+
+impl Drop for TraceRecorder {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl TraceRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 开启/关闭追踪（capacity 为 0 时使用默认容量）
+    pub fn set_enabled(&self, enabled: bool, capacity: usize) {
+        let capacity = if capacity == 0 { DEFAULT_TRACE_CAPACITY } else { capacity };
+        self.capacity.store(capacity, Ordering::SeqCst);
+        self.enabled.store(enabled, Ordering::SeqCst);
+
+        let mut buffer = self.buffer.lock();
+        while buffer.len() > capacity {
+            buffer.pop_front();
+        }
+        let mut verdicts = self.verdicts.lock();
+        while verdicts.len() > capacity {
+            verdicts.pop_front();
+        }
+        let mut runs = self.runs.lock();
+        while runs.len() > capacity {
+            runs.pop_front();
+        }
+    }
+
+    /// 是否开启追踪
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    /// 设置库内保留行数（0 表示使用默认值）
+    pub fn set_retention(&self, retention: usize) {
+        let retention = if retention == 0 { DEFAULT_TRACE_RETENTION } else { retention };
+        self.retention.store(retention, Ordering::SeqCst);
+    }
+
+    /// 设置持久化存储（切换前先将待写记录写入原存储）
+    pub fn set_storage(&self, storage: Option<Arc<Storage>>) {
+        self.flush();
+        *self.storage.write() = storage;
+    }
+
+    /// 同步提交全部待写记录
+    pub fn flush(&self) {
+        if let Some(storage) = self.storage.read().as_ref() {
+            self.writer.flush(storage, self.retention.load(Ordering::SeqCst));
+        }
+    }
+
+    /// 将一条记录加入待写队列，并在阻塞线程池中安排一次提交
+    ///
+    /// 不在 tokio 运行时内调用时直接同步提交。
+    fn enqueue(&self, storage: &Arc<Storage>, push: impl FnOnce(&mut PendingRows)) {
+        push(&mut self.writer.pending.lock());
+        if self.writer.scheduled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let retention = self.retention.load(Ordering::SeqCst);
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                let writer = Arc::clone(&self.writer);
+                let storage = Arc::clone(storage);
+                handle.spawn_blocking(move || writer.flush(&storage, retention));
+            }
+            Err(_) => self.writer.flush(storage, retention),
+        }
+    }
+
+    /// 记录一次任务（未开启时忽略）
+    pub fn record(&self, record: TraceRecord) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        if let Some(storage) = self.storage.read().as_ref() {
+            match serde_json::to_vec(&record) {
+                Ok(bytes) => {
+                    let entry = (record.run_id.clone(), record.task_id, bytes);
+                    self.enqueue(storage, |rows| rows.traces.push(entry));
+                }
+                Err(e) => eprintln!("[Trace] failed to serialize task {}: {}", record.task_id, e),
+            }
+        }
+
+        let capacity = self.capacity.load(Ordering::SeqCst);
+        let mut buffer = self.buffer.lock();
+        while buffer.len() >= capacity {
+            buffer.pop_front();
+        }
+        buffer.push_back(record);
+    }
+
+    /// 获取某次运行的追踪记录（优先读取存储，否则读取环形缓冲区）
+    pub fn get_run(&self, run_id: &str) -> Vec<TraceRecord> {
+        self.flush();
+        if let Some(storage) = self.storage.read().as_ref() {
+            match storage.load_traces(run_id) {
+                Ok(rows) => {
+                    return rows
+                        .iter()
+                        .filter_map(|bytes| serde_json::from_slice(bytes).ok())
+                        .collect();
+                }
+                Err(e) => eprintln!("[Trace] failed to load run {}: {}", run_id, e),
+            }
+        }
+
+        self.buffer
+            .lock()
+            .iter()
+            .filter(|r| r.run_id == run_id)
+            .cloned()
+            .collect()
+    }
+
+    /// 记录步骤判定（未开启时忽略）
+    pub fn record_verdict(&self, run_id: &str, seq: u64, result: &StepResult) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let verdict = StepVerdict {
+            run_id: run_id.to_string(),
+            seq,
+            step_id: result.step_id,
+            step_name: result.step_name.clone(),
+            status: result.status,
+            marginal: result.marginal,
+            final_value: result.final_value.clone(),
+        };
+
+        if let Some(storage) = self.storage.read().as_ref() {
+            match serde_json::to_vec(&verdict) {
+                Ok(bytes) => {
+                    let entry = (run_id.to_string(), seq, bytes);
+                    self.enqueue(storage, |rows| rows.verdicts.push(entry));
+                }
+                Err(e) => eprintln!("[Trace] failed to serialize verdict {}#{}: {}", run_id, seq, e),
+            }
+        }
+
+        let capacity = self.capacity.load(Ordering::SeqCst);
+        let mut verdicts = self.verdicts.lock();
+        while verdicts.len() >= capacity {
+            verdicts.pop_front();
+        }
+        verdicts.push_back(verdict);
+    }
+
+    /// 获取某次运行的步骤判定（优先读取存储，否则读取环形缓冲区）
+    pub fn get_verdicts(&self, run_id: &str) -> Vec<StepVerdict> {
+        self.flush();
+        if let Some(storage) = self.storage.read().as_ref() {
+            match storage.load_verdicts(run_id) {
+                Ok(rows) => {
+                    return rows
+                        .iter()
+                        .filter_map(|bytes| serde_json::from_slice(bytes).ok())
+                        .collect();
+                }
+                Err(e) => eprintln!("[Trace] failed to load verdicts {}: {}", run_id, e),
+            }
+        }
+
+        self.verdicts
+            .lock()
+            .iter()
+            .filter(|v| v.run_id == run_id)
+            .cloned()
+            .collect()
+    }
+
+    /// 记录运行开始时的上下文（未开启时忽略）
+    pub fn record_run(&self, context: RunContext) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        if let Some(storage) = self.storage.read().as_ref() {
+            match serde_json::to_vec(&context) {
+                Ok(bytes) => {
+                    let entry = (context.run_id.clone(), 0, bytes);
+                    self.enqueue(storage, |rows| rows.runs.push(entry));
+                }
+                Err(e) => eprintln!("[Trace] failed to serialize run {}: {}", context.run_id, e),
+            }
+        }
+
+        let capacity = self.capacity.load(Ordering::SeqCst);
+        let mut runs = self.runs.lock();
+        while runs.len() >= capacity {
+            runs.pop_front();
+        }
+        runs.push_back(context);
+    }
+
+    /// 获取某次运行的上下文（优先读取存储，否则读取环形缓冲区）
+    pub fn get_run_context(&self, run_id: &str) -> Option<RunContext> {
+        self.flush();
+        if let Some(storage) = self.storage.read().as_ref() {
+            match storage.load_run_context(run_id) {
+                Ok(Some(bytes)) => return serde_json::from_slice(&bytes).ok(),
+                Ok(None) => {}
+                Err(e) => eprintln!("[Trace] failed to load run context {}: {}", run_id, e),
+            }
+        }
+
+        self.runs.lock().iter().find(|r| r.run_id == run_id).cloned()
+    }
+
+    /// 获取某次运行中指定步骤的追踪记录（仅环形缓冲区，用于附加到失败结果）
+    pub fn get_step(&self, run_id: &str, step_id: u32) -> Vec<TraceRecord> {
+        self.buffer
+            .lock()
+            .iter()
+            .filter(|r| r.run_id == run_id && r.step_id == step_id)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{TraceKind, TraceOutcome};
+
+    fn record(run_id: &str, task_id: u64, step_id: u32) -> TraceRecord {
+        TraceRecord {
+            run_id: run_id.to_string(),
+            task_id,
+            slot_id: 0,
+            step_id,
+            kind: TraceKind::Engine,
+            device: "dut".to_string(),
+            address: String::new(),
+            payload: vec![],
+            response: vec![],
+            duration_ms: 0,
+            outcome: TraceOutcome::Ok,
+            message: None,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_disabled_recorder_ignores_records() {
+        let recorder = TraceRecorder::new();
+        recorder.record(record("r1", 1, 1));
+        assert!(recorder.get_run("r1").is_empty());
+    }
+
+    #[test]
+    fn test_ring_buffer_capacity() {
+        let recorder = TraceRecorder::new();
+        recorder.set_enabled(true, 2);
+        recorder.record(record("r1", 1, 1));
+        recorder.record(record("r1", 2, 2));
+        recorder.record(record("r1", 3, 2));
+
+        let run = recorder.get_run("r1");
+        assert_eq!(run.len(), 2);
+        assert_eq!(run[0].task_id, 2);
+        assert_eq!(recorder.get_step("r1", 2).len(), 2);
+    }
+
+    #[test]
+    fn test_record_verdicts() {
+        let recorder = TraceRecorder::new();
+        let result = StepResult::failed(3, "vout".to_string(), 5, "超限".to_string(), None);
+        recorder.record_verdict("r1", 0, &result);
+        assert!(recorder.get_verdicts("r1").is_empty());
+
+        recorder.set_enabled(true, 0);
+        recorder.record_verdict("r1", 0, &result);
+        recorder.record_verdict("r2", 0, &result);
+
+        let verdicts = recorder.get_verdicts("r1");
+        assert_eq!(verdicts.len(), 1);
+        assert_eq!(verdicts[0].step_id, 3);
+        assert_eq!(verdicts[0].status, crate::model::StepStatus::Failed);
+    }
+
+    #[test]
+    fn test_record_run_context() {
+        let recorder = TraceRecorder::new();
+        let context = RunContext {
+            run_id: "r1".to_string(),
+            plan_name: Some("sku_a".to_string()),
+            sn: Some("SN-1".to_string()),
+            limits: [("vmax".to_string(), 3.6)].into(),
+            ..Default::default()
+        };
+        recorder.record_run(context.clone());
+        assert!(recorder.get_run_context("r1").is_none());
+
+        recorder.set_enabled(true, 0);
+        recorder.record_run(context);
+        let restored = recorder.get_run_context("r1").unwrap();
+        assert_eq!(restored.plan_name.as_deref(), Some("sku_a"));
+        assert_eq!(restored.limits["vmax"], 3.6);
+        assert!(recorder.get_run_context("r2").is_none());
+    }
+
+    #[test]
+    fn test_storage_retention_prunes_oldest_runs() {
+        let dir = std::env::temp_dir().join(format!("catalytic_trace_retention_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let storage = Arc::new(Storage::open(dir.join("trace.redb").to_str().unwrap()).unwrap());
+
+        let recorder = TraceRecorder::new();
+        recorder.set_enabled(true, 0);
+        recorder.set_retention(2);
+        recorder.set_storage(Some(Arc::clone(&storage)));
+        recorder.record(record("1000-0", 1, 1));
+        recorder.record(record("2000-0", 1, 1));
+        recorder.record(record("2000-0", 2, 2));
+
+        let result = StepResult::failed(3, "vout".to_string(), 5, "超限".to_string(), None);
+        for run_id in ["1000-0", "2000-0", "3000-0"] {
+            recorder.record_verdict(run_id, 0, &result);
+        }
+
+        // 最早的运行先被裁剪，库内每张表不超过 2 行
+        assert!(recorder.get_run("1000-0").is_empty());
+        assert_eq!(recorder.get_run("2000-0").len(), 2);
+        assert!(recorder.get_verdicts("1000-0").is_empty());
+        assert_eq!(recorder.get_verdicts("3000-0").len(), 1);
+
+        drop(recorder);
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_records_persist_off_executor() {
+        let dir = std::env::temp_dir().join(format!("catalytic_trace_async_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let storage = Arc::new(Storage::open(dir.join("trace.redb").to_str().unwrap()).unwrap());
+
+        let recorder = TraceRecorder::new();
+        recorder.set_enabled(true, 0);
+        recorder.set_storage(Some(Arc::clone(&storage)));
+        for task_id in 0..50 {
+            recorder.record(record("r1", task_id, 1));
+        }
+
+        // 读取前会等待后台提交完成
+        assert_eq!(recorder.get_run("r1").len(), 50);
+        assert_eq!(storage.load_traces("r1").unwrap().len(), 50);
+
+        drop(recorder);
+        drop(storage);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
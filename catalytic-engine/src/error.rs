@@ -27,6 +27,12 @@ pub enum EngineError {
     #[error("步骤 ID 不存在: {0}")]
     StepNotFound(u32),
 
+    #[error("测试方案不存在: {0}")]
+    PlanNotFound(String),
+
+    #[error("运行记录不存在: {0}")]
+    RunNotFound(String),
+
     #[error("解析失败: {0}")]
     ParseError(String),
 
@@ -45,6 +51,18 @@ pub enum EngineError {
     #[error("存储错误: {0}")]
     StorageError(String),
 
+    #[error("schema 版本错误: {0}")]
+    SchemaError(String),
+
+    #[error("方案包无效: {0}")]
+    PackageError(String),
+
+    #[error("配置事务错误: {0}")]
+    TransactionError(String),
+
+    #[error("配置校验失败: {0}")]
+    ValidationError(String),
+
     #[error("内部错误: {0}")]
     InternalError(String),
 
@@ -74,12 +92,18 @@ impl From<&EngineError> for i32 {
             EngineError::DeviceTypeNotFound(_) => ERR_INVALID_PARAM,
             EngineError::DeviceInstanceNotFound(_) => ERR_INVALID_PARAM,
             EngineError::StepNotFound(_) => ERR_INVALID_PARAM,
+            EngineError::PlanNotFound(_) => ERR_INVALID_PARAM,
+            EngineError::RunNotFound(_) => ERR_INVALID_PARAM,
             EngineError::ParseError(_) => ERR_INTERNAL,
             EngineError::CheckError(_) => ERR_INTERNAL,
             EngineError::ExpressionError(_) => ERR_INTERNAL,
             EngineError::CallbackNotRegistered => ERR_INVALID_STATE,
             EngineError::TaskTimeout => ERR_INTERNAL,
             EngineError::StorageError(_) => ERR_INTERNAL,
+            EngineError::SchemaError(_) => ERR_INVALID_PARAM,
+            EngineError::PackageError(_) => ERR_INVALID_PARAM,
+            EngineError::TransactionError(_) => ERR_INVALID_STATE,
+            EngineError::ValidationError(_) => ERR_INVALID_PARAM,
             EngineError::InternalError(_) => ERR_INTERNAL,
             EngineError::ExecutionError(_) => ERR_INTERNAL,
             EngineError::Interrupted => ERR_INTERNAL, // 或其他特定码
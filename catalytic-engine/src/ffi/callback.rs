@@ -27,6 +27,21 @@ pub type HostTaskCallback = extern "C" fn(
     user_data: *mut c_void,
 ) -> i32;
 
+/// 外部检查回调
+///
+/// value_json 为解析值 `{"type", "value"}`（无值时为 null），
+/// variables_json 为槽位变量的显示映射 `{名称: {value, type, unit}}`。
+/// Host 判定完成后通过 cat_engine_submit_check_result 提交结果。
+pub type ExternalCheckCallback = extern "C" fn(
+    slot_id: u32,
+    task_id: u64,
+    step_id: u32,
+    value_json: *const c_char,
+    variables_json: *const c_char,
+    timeout_ms: u32,
+    user_data: *mut c_void,
+) -> i32;
+
 /// UI 更新回调
 pub type UIUpdateCallback = extern "C" fn(
     update_json: *const c_char,
@@ -75,6 +90,23 @@ pub unsafe extern "C" fn cat_engine_register_host_task_callback(
     engine.register_host_task_callback(callback, user_data);
 }
 
+/// 注册外部检查回调
+///
+/// # Safety
+/// engine 必须是有效指针，user_data 由 Host 保证在回调期间有效
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_register_external_check_callback(
+    engine: *mut crate::core::CatEngine,
+    callback: ExternalCheckCallback,
+    user_data: *mut c_void,
+) {
+    if engine.is_null() {
+        return;
+    }
+    let engine = &*engine;
+    engine.register_external_check_callback(callback, user_data);
+}
+
 /// 注册 UI 更新回调
 #[no_mangle]
 pub unsafe extern "C" fn cat_engine_register_ui_callback(
@@ -2,9 +2,10 @@
 
 use std::ffi::{c_char, CStr};
 use crate::core::CatEngine;
-use crate::model::{DeviceType, TestStep, SlotBinding};
+use crate::model::{DeviceType, TestStep, SlotBinding, TestPlan, PlanAssignment, LimitConfig};
 use crate::ffi::helpers::to_cstring_ptr;
-use crate::error::{SUCCESS, ERR_INVALID_PARAM, ERR_INTERNAL};
+use crate::storage::migration::{self, CURRENT_SCHEMA_VERSION};
+use crate::error::{SUCCESS, ERR_INVALID_PARAM, ERR_INVALID_STATE, ERR_INTERNAL};
 
 /// 全局配置结构
 #[derive(serde::Deserialize)]
@@ -14,6 +15,12 @@ struct GlobalConfig {
     #[serde(default)]
     test_steps: Vec<TestStep>,
     #[serde(default)]
+    test_plans: std::collections::HashMap<String, TestPlan>,
+    #[serde(default)]
+    plan_assignment: PlanAssignment,
+    #[serde(default)]
+    limits: Option<LimitConfig>,
+    #[serde(default)]
     slot_bindings: Vec<SlotBinding>,
 }
 
@@ -38,29 +45,44 @@ pub unsafe extern "C" fn cat_engine_load_config(
             Err(_) => return ERR_INVALID_PARAM,
         };
     
-        let config: GlobalConfig = match serde_json::from_str(config_str) {
+        let mut doc: serde_json::Value = match serde_json::from_str(config_str) {
+            Ok(v) => v,
+            Err(_) => return ERR_INVALID_PARAM,
+        };
+
+        // 升级旧版本配置
+        let report = match migration::migrate(&mut doc) {
+            Ok(r) => r,
+            Err(_) => return ERR_INVALID_PARAM,
+        };
+        if report.migrated() {
+            engine.record_migration_report(report);
+        }
+
+        let config: GlobalConfig = match serde_json::from_value(doc) {
             Ok(c) => c,
             Err(_) => return ERR_INVALID_PARAM,
         };
     
-        // 加载设备类型
-        for (name, device_type) in config.device_types {
-            if engine.add_device_type(name, device_type).is_err() {
-                return ERR_INTERNAL;
-            }
+        // 整个加载过程作为一个事务：只写入一次，应用失败时回滚
+        // 配置一致性问题不阻止加载（兼容旧配置），记为警告，见 cat_engine_get_config_warnings_json；
+        // 正则、JSON 路径或表达式无法编译时仍拒绝加载
+        // 调用方已开启事务时并入该事务，由调用方提交（严格校验）
+        let owns_transaction = !engine.in_transaction();
+        if owns_transaction && engine.begin_transaction().is_err() {
+            return ERR_INVALID_STATE;
         }
-    
-        // 加载测试步骤
-        for step in config.test_steps {
-            if engine.add_test_step(step).is_err() {
-                return ERR_INTERNAL;
+
+        if let Err(code) = apply_config(engine, config) {
+            if owns_transaction {
+                let _ = engine.abort_transaction();
             }
+            return code;
         }
-    
-        // 加载槽位绑定
-        for binding in config.slot_bindings {
-            if engine.set_slot_binding(binding.slot_id, binding.devices).is_err() {
-                return ERR_INTERNAL;
+
+        if owns_transaction {
+            if let Err(e) = engine.commit_transaction_with_warnings() {
+                return (&e).into();
             }
         }
     
@@ -68,6 +90,128 @@ pub unsafe extern "C" fn cat_engine_load_config(
     })
 }
 
+/// 获取最近一次 cat_engine_load_config 发现的配置问题（JSON 字符串数组，无问题时为空数组）
+///
+/// 问题包括跳转目标不存在、目标设备类型不存在、限值引用未定义等，
+/// 这些问题不阻止加载，但相关步骤执行时可能失败
+///
+/// # Safety
+/// engine 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_get_config_warnings_json(
+    engine: *const CatEngine,
+) -> *mut c_char {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() {
+            return std::ptr::null_mut();
+        }
+
+        let engine = &*engine;
+        to_cstring_ptr(&engine.config_warnings())
+    }, std::ptr::null_mut())
+}
+
+/// 将配置逐项应用到引擎，返回失败时的 FFI 返回码
+fn apply_config(engine: &mut CatEngine, config: GlobalConfig) -> std::result::Result<(), i32> {
+    // 加载设备类型
+    for (name, device_type) in config.device_types {
+        engine.add_device_type(name, device_type).map_err(|_| ERR_INTERNAL)?;
+    }
+
+    // 加载测试步骤（正则、JSON 路径或表达式无法编译时拒绝加载）
+    for step in config.test_steps {
+        engine.add_test_step(step).map_err(|e| i32::from(&e))?;
+    }
+
+    // 加载命名方案
+    for (name, plan) in config.test_plans {
+        engine.add_test_plan(name, plan).map_err(|e| i32::from(&e))?;
+    }
+
+    // 加载方案分配（方案必须已存在）
+    for (slot_id, plan) in config.plan_assignment.slots {
+        engine.set_slot_plan(slot_id, Some(plan)).map_err(|_| ERR_INVALID_PARAM)?;
+    }
+    for rule in config.plan_assignment.sn_rules {
+        engine.set_sn_plan_rule(rule.prefix, Some(rule.plan)).map_err(|_| ERR_INVALID_PARAM)?;
+    }
+
+    // 加载限值表（提供时整体替换）
+    if let Some(limits) = config.limits {
+        engine.set_limit_config(limits).map_err(|_| ERR_INTERNAL)?;
+    }
+
+    // 加载槽位绑定
+    for binding in config.slot_bindings {
+        engine.set_slot_binding(binding.slot_id, binding.devices).map_err(|_| ERR_INTERNAL)?;
+    }
+
+    Ok(())
+}
+
+/// 开始配置事务
+///
+/// 事务期间的配置修改不写入存储，直到 cat_engine_commit_transaction
+///
+/// # Safety
+/// engine 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_begin_transaction(engine: *mut CatEngine) -> i32 {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() {
+            return ERR_INVALID_PARAM;
+        }
+
+        let engine = &mut *engine;
+        match engine.begin_transaction() {
+            Ok(_) => SUCCESS,
+            Err(e) => (&e).into(),
+        }
+    })
+}
+
+/// 提交配置事务（校验配置并写入存储，校验失败时自动回滚）
+///
+/// # Safety
+/// engine 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_commit_transaction(engine: *mut CatEngine) -> i32 {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() {
+            return ERR_INVALID_PARAM;
+        }
+
+        let engine = &mut *engine;
+        match engine.commit_transaction() {
+            Ok(_) => SUCCESS,
+            Err(e) => (&e).into(),
+        }
+    })
+}
+
+/// 放弃配置事务，恢复事务开始前的配置
+///
+/// # Safety
+/// engine 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_abort_transaction(engine: *mut CatEngine) -> i32 {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() {
+            return ERR_INVALID_PARAM;
+        }
+
+        let engine = &mut *engine;
+        match engine.abort_transaction() {
+            Ok(_) => SUCCESS,
+            Err(e) => (&e).into(),
+        }
+    })
+}
+
 /// 获取配置 JSON（返回的字符串需要调用 cat_engine_free_json 释放）
 ///
 /// # Safety
@@ -107,10 +251,14 @@ pub unsafe extern "C" fn cat_engine_get_config_json(
         
         // 构建完整配置对象
         let config = serde_json::json!({
+            "schema_version": CURRENT_SCHEMA_VERSION,
             "slot_count": engine.slot_count(),
             "device_types": device_types_array,
             "devices": devices_map,
             "test_steps": engine.get_test_steps(),
+            "test_plans": engine.get_test_plans(),
+            "plan_assignment": engine.get_plan_assignment(),
+            "limits": engine.get_limit_config(),
             "slot_bindings": &engine.slot_bindings,
         });
     
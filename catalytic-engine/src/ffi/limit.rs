@@ -0,0 +1,86 @@
+//! 限值表 FFI
+
+use std::ffi::c_char;
+use crate::core::CatEngine;
+use crate::model::LimitConfig;
+use crate::ffi::helpers::{to_cstring_ptr, parse_json_from_ptr, str_from_ptr};
+use crate::error::{SUCCESS, ERR_INVALID_PARAM};
+
+/// 获取限值配置 JSON（返回的字符串需要调用 cat_engine_free_json 释放）
+///
+/// # Safety
+/// engine 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_get_limits_json(
+    engine: *const CatEngine,
+) -> *mut c_char {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() {
+            return std::ptr::null_mut();
+        }
+
+        let engine = &*engine;
+        to_cstring_ptr(engine.get_limit_config())
+    }, std::ptr::null_mut())
+}
+
+/// 替换限值配置
+///
+/// # Safety
+/// engine 和 limits_json 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_set_limits(
+    engine: *mut CatEngine,
+    limits_json: *const c_char,
+) -> i32 {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() || limits_json.is_null() {
+            return ERR_INVALID_PARAM;
+        }
+
+        let engine = &mut *engine;
+
+        let limits: LimitConfig = match parse_json_from_ptr(limits_json) {
+            Some(l) => l,
+            None => return ERR_INVALID_PARAM,
+        };
+
+        match engine.set_limit_config(limits) {
+            Ok(_) => SUCCESS,
+            Err(e) => (&e).into(),
+        }
+    })
+}
+
+/// 从 CSV 导入限值（表头 name,value[,variant][,slot]），合并到现有配置
+///
+/// 成功返回导入的行数，失败返回负的错误码
+///
+/// # Safety
+/// engine 和 csv 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_import_limits_csv(
+    engine: *mut CatEngine,
+    csv: *const c_char,
+) -> i32 {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() || csv.is_null() {
+            return ERR_INVALID_PARAM;
+        }
+
+        let engine = &mut *engine;
+
+        let csv = match str_from_ptr(csv) {
+            Some(s) => s,
+            None => return ERR_INVALID_PARAM,
+        };
+
+        match engine.import_limits_csv(&csv) {
+            Ok(count) => count as i32,
+            Err(e) => (&e).into(),
+        }
+    })
+}
@@ -7,6 +7,11 @@ pub mod engine;
 pub mod config;
 pub mod device;
 pub mod step;
+pub mod plan;
+pub mod limit;
+pub mod package;
+pub mod trace;
+pub mod spc;
 pub mod slot;
 pub mod control;
 pub mod result;
@@ -17,6 +22,11 @@ pub use engine::*;
 pub use config::*;
 pub use device::*;
 pub use step::*;
+pub use plan::*;
+pub use limit::*;
+pub use package::*;
+pub use trace::*;
+pub use spc::*;
 pub use slot::*;
 pub use control::*;
 pub use result::*;
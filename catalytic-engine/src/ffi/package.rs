@@ -0,0 +1,89 @@
+//! 方案包导入导出 FFI
+
+use std::ffi::c_char;
+use crate::core::CatEngine;
+use crate::model::PackageMetadata;
+use crate::ffi::helpers::{to_cstring_ptr, parse_json_from_ptr, str_from_ptr};
+use crate::error::{SUCCESS, ERR_INVALID_PARAM};
+
+/// 导出方案包 JSON（返回的字符串需要调用 cat_engine_free_json 释放）
+///
+/// - plan_name: 方案名称，NULL 表示默认步骤列表
+/// - metadata_json: PackageMetadata JSON，可为 NULL
+///
+/// # Safety
+/// engine 必须是有效指针，plan_name / metadata_json 为 NULL 或有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_export_package(
+    engine: *const CatEngine,
+    plan_name: *const c_char,
+    metadata_json: *const c_char,
+) -> *mut c_char {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() {
+            return std::ptr::null_mut();
+        }
+
+        let engine = &*engine;
+        let plan_name = str_from_ptr(plan_name);
+        let metadata: PackageMetadata = parse_json_from_ptr(metadata_json).unwrap_or_default();
+
+        match engine.export_package(plan_name.as_deref(), metadata) {
+            Ok(package) => to_cstring_ptr(&package),
+            Err(_) => std::ptr::null_mut(),
+        }
+    }, std::ptr::null_mut())
+}
+
+/// 导入方案包
+///
+/// 成功后可通过 cat_engine_get_migration_report_json 获取改动报告
+///
+/// # Safety
+/// engine 和 package_json 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_import_package(
+    engine: *mut CatEngine,
+    package_json: *const c_char,
+) -> i32 {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() || package_json.is_null() {
+            return ERR_INVALID_PARAM;
+        }
+
+        let engine = &mut *engine;
+
+        let json = match str_from_ptr(package_json) {
+            Some(s) => s,
+            None => return ERR_INVALID_PARAM,
+        };
+
+        match engine.import_package(&json) {
+            Ok(_) => SUCCESS,
+            Err(e) => (&e).into(),
+        }
+    })
+}
+
+/// 获取最近一次迁移报告 JSON（无报告时返回 NULL）
+///
+/// # Safety
+/// engine 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_get_migration_report_json(
+    engine: *const CatEngine,
+) -> *mut c_char {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() {
+            return std::ptr::null_mut();
+        }
+
+        let engine = &*engine;
+        engine.last_migration_report()
+            .map(to_cstring_ptr)
+            .unwrap_or(std::ptr::null_mut())
+    }, std::ptr::null_mut())
+}
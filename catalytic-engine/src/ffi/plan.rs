@@ -0,0 +1,155 @@
+//! 测试方案管理 FFI
+
+use std::ffi::c_char;
+use crate::core::CatEngine;
+use crate::model::TestPlan;
+use crate::ffi::helpers::{to_cstring_ptr, parse_json_from_ptr, str_from_ptr};
+use crate::error::{SUCCESS, ERR_INVALID_PARAM};
+
+/// 获取所有命名方案及分配配置 JSON
+///
+/// 返回格式: {"plans": {name: TestPlan}, "assignment": PlanAssignment}
+///
+/// # Safety
+/// engine 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_get_test_plans_json(
+    engine: *const CatEngine,
+) -> *mut c_char {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() {
+            return std::ptr::null_mut();
+        }
+
+        let engine = &*engine;
+        let json = serde_json::json!({
+            "plans": engine.get_test_plans(),
+            "assignment": engine.get_plan_assignment(),
+        });
+        to_cstring_ptr(&json)
+    }, std::ptr::null_mut())
+}
+
+/// 添加或替换命名方案
+///
+/// # Safety
+/// engine、name 和 plan_json 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_add_test_plan(
+    engine: *mut CatEngine,
+    name: *const c_char,
+    plan_json: *const c_char,
+) -> i32 {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() || name.is_null() || plan_json.is_null() {
+            return ERR_INVALID_PARAM;
+        }
+
+        let engine = &mut *engine;
+
+        let name = match str_from_ptr(name) {
+            Some(s) => s,
+            None => return ERR_INVALID_PARAM,
+        };
+
+        let plan: TestPlan = match parse_json_from_ptr(plan_json) {
+            Some(p) => p,
+            None => return ERR_INVALID_PARAM,
+        };
+
+        match engine.add_test_plan(name, plan) {
+            Ok(_) => SUCCESS,
+            Err(e) => (&e).into(),
+        }
+    })
+}
+
+/// 移除命名方案
+///
+/// # Safety
+/// engine 和 name 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_remove_test_plan(
+    engine: *mut CatEngine,
+    name: *const c_char,
+) -> i32 {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() || name.is_null() {
+            return ERR_INVALID_PARAM;
+        }
+
+        let engine = &mut *engine;
+
+        let name = match str_from_ptr(name) {
+            Some(s) => s,
+            None => return ERR_INVALID_PARAM,
+        };
+
+        match engine.remove_test_plan(&name) {
+            Ok(_) => SUCCESS,
+            Err(e) => (&e).into(),
+        }
+    })
+}
+
+/// 为槽位分配方案
+///
+/// plan_name 为 NULL 时清除槽位的显式分配
+///
+/// # Safety
+/// engine 必须是有效指针，plan_name 为 NULL 或有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_set_slot_plan(
+    engine: *mut CatEngine,
+    slot_id: u32,
+    plan_name: *const c_char,
+) -> i32 {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() {
+            return ERR_INVALID_PARAM;
+        }
+
+        let engine = &mut *engine;
+
+        match engine.set_slot_plan(slot_id, str_from_ptr(plan_name)) {
+            Ok(_) => SUCCESS,
+            Err(e) => (&e).into(),
+        }
+    })
+}
+
+/// 设置 SN 前缀方案规则
+///
+/// plan_name 为 NULL 时移除该前缀规则
+///
+/// # Safety
+/// engine 和 prefix 必须是有效指针，plan_name 为 NULL 或有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_set_sn_plan_rule(
+    engine: *mut CatEngine,
+    prefix: *const c_char,
+    plan_name: *const c_char,
+) -> i32 {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() || prefix.is_null() {
+            return ERR_INVALID_PARAM;
+        }
+
+        let engine = &mut *engine;
+
+        let prefix = match str_from_ptr(prefix) {
+            Some(s) => s,
+            None => return ERR_INVALID_PARAM,
+        };
+
+        match engine.set_sn_plan_rule(prefix, str_from_ptr(plan_name)) {
+            Ok(_) => SUCCESS,
+            Err(e) => (&e).into(),
+        }
+    })
+}
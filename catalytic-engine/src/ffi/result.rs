@@ -7,6 +7,8 @@ use crate::ffi::helpers::to_cstring_ptr;
 /// 返回的 JSON 包含：
 /// - slot_id: 槽位 ID
 /// - sn: 序列号
+/// - plan_name: 当前执行的方案名称
+/// - run_id: 当前运行 ID（用于查询 I/O 追踪）
 /// - status: 状态字符串
 /// - current_step: 当前步骤索引
 /// - start_time: 开始时间戳
@@ -29,6 +31,8 @@ pub unsafe extern "C" fn cat_engine_get_slot_status_json(engine: *const CatEngin
     let json = serde_json::json!({
         "slot_id": g.slot_id,
         "sn": g.sn,
+        "plan_name": g.plan_name,
+        "run_id": g.run_id,
         "status": g.status(),
         "current_step": g.current_step_index,
         "start_time": g.start_time,
@@ -0,0 +1,114 @@
+//! SPC 统计 FFI
+
+use std::ffi::c_char;
+use crate::core::CatEngine;
+use crate::ffi::helpers::to_cstring_ptr;
+use crate::error::{SUCCESS, ERR_INVALID_PARAM};
+
+/// 设置 SPC 滚动窗口大小（0 表示使用默认窗口）
+///
+/// # Safety
+/// engine 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_set_spc_window(
+    engine: *const CatEngine,
+    window: u32,
+) -> i32 {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() {
+            return ERR_INVALID_PARAM;
+        }
+
+        let engine = &*engine;
+        engine.spc_monitor().set_window(window as usize);
+        SUCCESS
+    })
+}
+
+/// 获取单个槽位上单个步骤的 SPC 统计 JSON
+///
+/// 包含均值、σ、Cp/Cpk 与最近一次测量触发的判异规则；尚无数值测量时返回 NULL。
+///
+/// # Safety
+/// engine 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_get_spc_stats_json(
+    engine: *const CatEngine,
+    slot_id: u32,
+    step_id: u32,
+) -> *mut c_char {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() {
+            return std::ptr::null_mut();
+        }
+
+        let engine = &*engine;
+        match engine.spc_monitor().stats(slot_id, step_id) {
+            Some(stats) => to_cstring_ptr(&stats),
+            None => std::ptr::null_mut(),
+        }
+    }, std::ptr::null_mut())
+}
+
+/// 获取全部 SPC 统计 JSON 数组（按槽位、步骤排序）
+///
+/// # Safety
+/// engine 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_get_all_spc_stats_json(
+    engine: *const CatEngine,
+) -> *mut c_char {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() {
+            return std::ptr::null_mut();
+        }
+
+        let engine = &*engine;
+        to_cstring_ptr(&engine.spc_monitor().all_stats())
+    }, std::ptr::null_mut())
+}
+
+/// 清除单个槽位上单个步骤的 SPC 统计（治具校准等工艺变更后重新累计）
+///
+/// # Safety
+/// engine 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_reset_spc(
+    engine: *const CatEngine,
+    slot_id: u32,
+    step_id: u32,
+) -> i32 {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() {
+            return ERR_INVALID_PARAM;
+        }
+
+        let engine = &*engine;
+        engine.spc_monitor().reset(Some((slot_id, step_id)));
+        SUCCESS
+    })
+}
+
+/// 清除全部 SPC 统计
+///
+/// # Safety
+/// engine 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_clear_spc(
+    engine: *const CatEngine,
+) -> i32 {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() {
+            return ERR_INVALID_PARAM;
+        }
+
+        let engine = &*engine;
+        engine.spc_monitor().reset(None);
+        SUCCESS
+    })
+}
@@ -5,6 +5,8 @@
 use std::ffi::{c_char, CStr};
 use crate::core::CatEngine;
 use crate::core::task::TaskResult;
+use crate::checker::external::ExternalCheckResult;
+use crate::ffi::helpers::{parse_json_from_ptr, str_from_ptr};
 use crate::error::{SUCCESS, ERR_INVALID_PARAM, ERR_INTERNAL};
 
 /// 提交成功结果
@@ -125,3 +127,64 @@ pub unsafe extern "C" fn cat_engine_submit_error(
         ERR_INTERNAL
     }
 }
+
+/// 提交外部检查结果
+/// 
+/// # 参数
+/// - engine: Engine 实例指针
+/// - slot_id: 槽位 ID
+/// - task_id: 任务 ID (由 ExternalCheckCallback 传入)
+/// - passed: 是否通过
+/// - actual_json: 实际值 JSON (可为 NULL)
+/// - summary: 结果摘要 (UTF-8 字符串，可为 NULL)
+/// 
+/// 判定过程出错时可对同一 task_id 调用 cat_engine_submit_error / cat_engine_submit_timeout。
+/// 
+/// # 返回
+/// - 0: 成功
+/// - -2: 参数无效（actual_json 不是合法 JSON）
+/// - -3: 任务未找到
+/// 
+/// # Safety
+/// engine 必须是有效指针，actual_json 与 summary 可为 NULL
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_submit_check_result(
+    engine: *const CatEngine,
+    slot_id: u32,
+    task_id: u64,
+    passed: bool,
+    actual_json: *const c_char,
+    summary: *const c_char,
+) -> i32 {
+    if engine.is_null() {
+        return ERR_INVALID_PARAM;
+    }
+    
+    let engine = &*engine;
+    
+    let actual = if actual_json.is_null() {
+        serde_json::Value::Null
+    } else {
+        match parse_json_from_ptr(actual_json) {
+            Some(v) => v,
+            None => return ERR_INVALID_PARAM,
+        }
+    };
+    let result = ExternalCheckResult {
+        passed,
+        actual,
+        summary: str_from_ptr(summary).unwrap_or_default(),
+    };
+    let data = match serde_json::to_vec(&result) {
+        Ok(data) => data,
+        Err(_) => return ERR_INTERNAL,
+    };
+    
+    let success = engine.task_registry().submit(task_id, slot_id, TaskResult::Ok(data));
+    
+    if success {
+        SUCCESS
+    } else {
+        ERR_INTERNAL
+    }
+}
@@ -0,0 +1,126 @@
+//! I/O 追踪 FFI
+
+use std::ffi::c_char;
+use crate::core::CatEngine;
+use crate::core::replay::replay_run;
+use crate::model::TestPlan;
+use crate::ffi::helpers::{to_cstring_ptr, parse_json_from_ptr, str_from_ptr};
+use crate::error::{SUCCESS, ERR_INVALID_PARAM};
+
+/// 开启/关闭原始 I/O 追踪
+///
+/// - enabled: 是否开启
+/// - capacity: 环形缓冲区容量（0 表示使用默认容量）
+///
+/// # Safety
+/// engine 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_set_trace_enabled(
+    engine: *const CatEngine,
+    enabled: bool,
+    capacity: u32,
+) -> i32 {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() {
+            return ERR_INVALID_PARAM;
+        }
+
+        let engine = &*engine;
+        engine.trace_recorder().set_enabled(enabled, capacity as usize);
+        SUCCESS
+    })
+}
+
+/// 设置追踪数据在库内保留的行数（追踪表与判定表各自计数，0 表示使用默认值）
+///
+/// 超出部分从最早的运行开始删除。
+///
+/// # Safety
+/// engine 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_set_trace_retention(
+    engine: *const CatEngine,
+    retention: u32,
+) -> i32 {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() {
+            return ERR_INVALID_PARAM;
+        }
+
+        let engine = &*engine;
+        engine.trace_recorder().set_retention(retention as usize);
+        SUCCESS
+    })
+}
+
+/// 获取某次运行的 I/O 追踪记录 JSON 数组
+///
+/// run_id 见槽位状态 JSON 中的 run_id 字段。payload/response 为十六进制字符串。
+///
+/// # Safety
+/// engine 和 run_id 必须是有效指针
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_get_run_trace_json(
+    engine: *const CatEngine,
+    run_id: *const c_char,
+) -> *mut c_char {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() || run_id.is_null() {
+            return std::ptr::null_mut();
+        }
+
+        let engine = &*engine;
+        let run_id = match str_from_ptr(run_id) {
+            Some(s) => s,
+            None => return std::ptr::null_mut(),
+        };
+
+        to_cstring_ptr(&engine.trace_recorder().get_run(&run_id))
+    }, std::ptr::null_mut())
+}
+
+/// 离线回放一次已记录的运行，返回 ReplayReport JSON
+///
+/// - run_id: 需在开启追踪时运行过
+/// - plan_json: 修改后的 TestPlan JSON，可为 NULL（使用该槽位当前方案）
+///
+/// 失败（运行记录不存在、方案 JSON 无效等）返回 NULL。
+///
+/// # Safety
+/// engine 和 run_id 必须是有效指针，plan_json 可为 NULL
+#[no_mangle]
+pub unsafe extern "C" fn cat_engine_replay_run(
+    engine: *const CatEngine,
+    run_id: *const c_char,
+    plan_json: *const c_char,
+) -> *mut c_char {
+    use crate::ffi_guard;
+    ffi_guard!({
+        if engine.is_null() || run_id.is_null() {
+            return std::ptr::null_mut();
+        }
+
+        let engine = &*engine;
+        let run_id = match str_from_ptr(run_id) {
+            Some(s) => s,
+            None => return std::ptr::null_mut(),
+        };
+
+        let steps = if plan_json.is_null() {
+            None
+        } else {
+            match parse_json_from_ptr::<TestPlan>(plan_json) {
+                Some(plan) => Some(plan.steps),
+                None => return std::ptr::null_mut(),
+            }
+        };
+
+        match replay_run(engine, &run_id, steps) {
+            Ok(report) => to_cstring_ptr(&report),
+            Err(_) => std::ptr::null_mut(),
+        }
+    }, std::ptr::null_mut())
+}
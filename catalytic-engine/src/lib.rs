@@ -7,6 +7,7 @@ pub mod error;
 pub mod ffi;
 pub mod model;
 pub mod parser;
+pub mod protocol;
 pub mod checker;
 pub mod ui;
 pub mod storage;
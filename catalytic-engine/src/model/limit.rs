@@ -0,0 +1,368 @@
+//! 限值引用与外部限值表
+//!
+//! 检查规则的限值可以是字面数值，也可以引用槽位变量（`$name`）或限值表中的常量（`@name`）。
+//! 限值表按产品变体（SN 前缀匹配）与槽位分层覆盖，同一方案可服务多个型号。
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use crate::error::{EngineError, Result};
+
+/// 限值：字面数值或引用
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum LimitValue {
+    /// 字面数值
+    Number(f64),
+    /// 引用：`$变量名` 或 `@限值名`
+    Ref(String),
+}
+
+/// 限值来源
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LimitSource<'a> {
+    /// 字面数值
+    Number(f64),
+    /// 槽位变量
+    Variable(&'a str),
+    /// 限值表常量
+    Named(&'a str),
+}
+
+impl LimitValue {
+    /// 解析限值来源
+    pub fn source(&self) -> Result<LimitSource<'_>> {
+        let text = match self {
+            LimitValue::Number(v) => return Ok(LimitSource::Number(*v)),
+            LimitValue::Ref(text) => text,
+        };
+        match text.split_at_checked(1) {
+            Some(("$", name)) if !name.is_empty() => Ok(LimitSource::Variable(name)),
+            Some(("@", name)) if !name.is_empty() => Ok(LimitSource::Named(name)),
+            _ => Err(EngineError::CheckError(format!("限值引用 '{}' 无效（应为 $变量名 或 @限值名）", text))),
+        }
+    }
+}
+
+impl Default for LimitValue {
+    fn default() -> Self {
+        LimitValue::Number(0.0)
+    }
+}
+
+impl From<f64> for LimitValue {
+    fn from(value: f64) -> Self {
+        LimitValue::Number(value)
+    }
+}
+
+impl std::fmt::Display for LimitValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LimitValue::Number(v) => write!(f, "{}", v),
+            LimitValue::Ref(r) => f.write_str(r),
+        }
+    }
+}
+
+/// 产品变体限值表
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct LimitTable {
+    /// 适用的 SN 前缀（多个变体匹配时取最长前缀）
+    #[serde(default)]
+    pub sn_prefixes: Vec<String>,
+    /// 限值 {名称: 值}
+    #[serde(default)]
+    pub values: HashMap<String, f64>,
+    /// 槽位覆盖 {slot_id: {名称: 值}}
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub slots: HashMap<u32, HashMap<String, f64>>,
+}
+
+/// 限值配置
+///
+/// 同名限值的优先级（高 → 低）：
+/// 1. 匹配变体的槽位覆盖
+/// 2. 匹配变体的限值
+/// 3. 公共槽位覆盖
+/// 4. 公共常量
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct LimitConfig {
+    /// 公共常量
+    #[serde(default)]
+    pub constants: HashMap<String, f64>,
+    /// 公共槽位覆盖 {slot_id: {名称: 值}}
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub slots: HashMap<u32, HashMap<String, f64>>,
+    /// 变体限值表 {变体名: 限值表}
+    #[serde(default)]
+    pub variants: HashMap<String, LimitTable>,
+}
+
+impl LimitConfig {
+    pub fn is_empty(&self) -> bool {
+        self.constants.is_empty() && self.slots.is_empty() && self.variants.is_empty()
+    }
+
+    /// 按 SN 选择变体（最长前缀匹配）
+    pub fn resolve_variant(&self, sn: Option<&str>) -> Option<&str> {
+        let sn = sn?;
+        self.variants
+            .iter()
+            .flat_map(|(name, table)| table.sn_prefixes.iter().map(move |p| (name, p)))
+            .filter(|(_, prefix)| sn.starts_with(prefix.as_str()))
+            .max_by(|a, b| a.1.len().cmp(&b.1.len()).then_with(|| b.0.cmp(a.0)))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// 合并出槽位生效的限值 {名称: 值}
+    pub fn resolve(&self, slot_id: u32, sn: Option<&str>) -> HashMap<String, f64> {
+        let mut limits = self.constants.clone();
+        if let Some(values) = self.slots.get(&slot_id) {
+            limits.extend(values.iter().map(|(k, v)| (k.clone(), *v)));
+        }
+        if let Some(table) = self.resolve_variant(sn).and_then(|name| self.variants.get(name)) {
+            limits.extend(table.values.iter().map(|(k, v)| (k.clone(), *v)));
+            if let Some(values) = table.slots.get(&slot_id) {
+                limits.extend(values.iter().map(|(k, v)| (k.clone(), *v)));
+            }
+        }
+        limits
+    }
+
+    /// 任一层级是否定义了该限值
+    pub fn defines(&self, name: &str) -> bool {
+        self.constants.contains_key(name)
+            || self.slots.values().any(|m| m.contains_key(name))
+            || self.variants.values().any(|t| {
+                t.values.contains_key(name) || t.slots.values().any(|m| m.contains_key(name))
+            })
+    }
+
+    /// 只保留指定名称的限值（各层级均裁剪，不含任何所需限值的变体整体去掉）
+    pub fn subset(&self, names: &HashSet<&str>) -> LimitConfig {
+        let pick = |values: &HashMap<String, f64>| -> HashMap<String, f64> {
+            values
+                .iter()
+                .filter(|(k, _)| names.contains(k.as_str()))
+                .map(|(k, v)| (k.clone(), *v))
+                .collect()
+        };
+        let pick_slots = |slots: &HashMap<u32, HashMap<String, f64>>| -> HashMap<u32, HashMap<String, f64>> {
+            slots
+                .iter()
+                .map(|(slot, values)| (*slot, pick(values)))
+                .filter(|(_, values)| !values.is_empty())
+                .collect()
+        };
+
+        LimitConfig {
+            constants: pick(&self.constants),
+            slots: pick_slots(&self.slots),
+            variants: self.variants
+                .iter()
+                .map(|(name, table)| (name.clone(), LimitTable {
+                    sn_prefixes: table.sn_prefixes.clone(),
+                    values: pick(&table.values),
+                    slots: pick_slots(&table.slots),
+                }))
+                .filter(|(_, table)| !table.values.is_empty() || !table.slots.is_empty())
+                .collect(),
+        }
+    }
+
+    /// 合并另一份限值配置（同名限值覆盖已有值），返回合并的限值个数
+    ///
+    /// 变体的 SN 前缀取并集
+    pub fn merge(&mut self, other: LimitConfig) -> usize {
+        fn extend(target: &mut HashMap<String, f64>, values: HashMap<String, f64>) -> usize {
+            let count = values.len();
+            target.extend(values);
+            count
+        }
+        fn extend_slots(target: &mut HashMap<u32, HashMap<String, f64>>, slots: HashMap<u32, HashMap<String, f64>>) -> usize {
+            slots.into_iter().map(|(slot, values)| extend(target.entry(slot).or_default(), values)).sum()
+        }
+
+        let mut count = extend(&mut self.constants, other.constants);
+        count += extend_slots(&mut self.slots, other.slots);
+        for (name, table) in other.variants {
+            let target = self.variants.entry(name).or_default();
+            for prefix in table.sn_prefixes {
+                if !target.sn_prefixes.contains(&prefix) {
+                    target.sn_prefixes.push(prefix);
+                }
+            }
+            count += extend(&mut target.values, table.values);
+            count += extend_slots(&mut target.slots, table.slots);
+        }
+        count
+    }
+
+    /// 从 CSV 导入限值，返回导入的行数
+    ///
+    /// 表头必须包含 name 与 value，可选 variant（空为公共）与 slot（空为所有槽位）；
+    /// 空行与 # 开头的行忽略。同名限值覆盖已有值。
+    /// 单元格可用双引号包裹（内含逗号，`""` 表示引号本身），但不能跨行。
+    pub fn import_csv(&mut self, csv: &str) -> Result<usize> {
+        let mut rows = csv
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let (_, header) = rows
+            .next()
+            .ok_or_else(|| EngineError::ValidationError("限值 CSV 为空".to_string()))?;
+        let columns: Vec<String> = split_csv_row(header)
+            .ok_or_else(|| EngineError::ValidationError("限值 CSV 表头引号未闭合".to_string()))?
+            .iter()
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        let column = |name: &str| columns.iter().position(|c| c == name);
+        let (name_col, value_col) = match (column("name"), column("value")) {
+            (Some(n), Some(v)) => (n, v),
+            _ => return Err(EngineError::ValidationError("限值 CSV 表头缺少 name 或 value 列".to_string())),
+        };
+        let (variant_col, slot_col) = (column("variant"), column("slot"));
+
+        let mut count = 0;
+        for (line_no, line) in rows {
+            let bad_row = |msg: String| EngineError::ValidationError(format!("限值 CSV 第 {} 行: {}", line_no, msg));
+            let cells = split_csv_row(line).ok_or_else(|| bad_row("引号未闭合".to_string()))?;
+            let cell = |col: Option<usize>| col.and_then(|c| cells.get(c)).map(String::as_str).filter(|s| !s.is_empty());
+
+            let name = cell(Some(name_col)).ok_or_else(|| bad_row("缺少 name".to_string()))?;
+            let raw = cell(Some(value_col)).ok_or_else(|| bad_row("缺少 value".to_string()))?;
+            let value: f64 = raw.parse().map_err(|_| bad_row(format!("value '{}' 不是数值", raw)))?;
+            let slot = cell(slot_col)
+                .map(|s| s.parse::<u32>().map_err(|_| bad_row(format!("slot '{}' 无效", s))))
+                .transpose()?;
+
+            let target = match (cell(variant_col), slot) {
+                (None, None) => &mut self.constants,
+                (None, Some(slot)) => self.slots.entry(slot).or_default(),
+                (Some(variant), None) => &mut self.variants.entry(variant.to_string()).or_default().values,
+                (Some(variant), Some(slot)) => self.variants
+                    .entry(variant.to_string())
+                    .or_default()
+                    .slots
+                    .entry(slot)
+                    .or_default(),
+            };
+            target.insert(name.to_string(), value);
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+/// 拆分一行 CSV（支持双引号包裹与 `""` 转义），引号未闭合时返回 None
+fn split_csv_row(line: &str) -> Option<Vec<String>> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if cell.trim().is_empty() => {
+                cell.clear();
+                quoted = true;
+            }
+            (',', false) => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            _ => cell.push(c),
+        }
+    }
+    if quoted {
+        return None;
+    }
+    cells.push(cell.trim().to_string());
+    Some(cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_value_serde() {
+        let values: Vec<LimitValue> = serde_json::from_str(r#"[3.3, "$vref", "@vmax"]"#).unwrap();
+        assert_eq!(values[0].source().unwrap(), LimitSource::Number(3.3));
+        assert_eq!(values[1].source().unwrap(), LimitSource::Variable("vref"));
+        assert_eq!(values[2].source().unwrap(), LimitSource::Named("vmax"));
+        assert!(LimitValue::Ref("vmax".into()).source().is_err());
+        assert!(LimitValue::Ref("$".into()).source().is_err());
+        assert_eq!(serde_json::to_string(&values).unwrap(), r#"[3.3,"$vref","@vmax"]"#);
+    }
+
+    #[test]
+    fn test_resolve_layers() {
+        let mut config = LimitConfig::default();
+        config.import_csv(
+            "name,value,variant,slot\n\
+             # 公共\n\
+             vmax,3.6,,\n\
+             vmin,3.0,,\n\
+             vmin,3.05,,1\n\
+             vmax,5.5,SKU5V,\n\
+             vmax,5.4,SKU5V,1\n",
+        ).unwrap();
+        config.variants.get_mut("SKU5V").unwrap().sn_prefixes.push("P5".into());
+
+        let slot0 = config.resolve(0, Some("P5-0001"));
+        assert_eq!((slot0["vmin"], slot0["vmax"]), (3.0, 5.5));
+        let slot1 = config.resolve(1, Some("P5-0002"));
+        assert_eq!((slot1["vmin"], slot1["vmax"]), (3.05, 5.4));
+        let other = config.resolve(0, Some("P3-0001"));
+        assert_eq!(other["vmax"], 3.6);
+
+        assert!(config.defines("vmin") && !config.defines("imax"));
+    }
+
+    #[test]
+    fn test_import_csv_errors() {
+        let mut config = LimitConfig::default();
+        assert!(config.import_csv("limit,value\nx,1").is_err());
+        let err = config.import_csv("name,value\nvmax,abc").unwrap_err().to_string();
+        assert!(err.contains("第 2 行"), "{}", err);
+        assert!(config.import_csv("name,value\n\"vmax,3.6").is_err());
+    }
+
+    #[test]
+    fn test_import_csv_quoted_fields() {
+        let mut config = LimitConfig::default();
+        let count = config.import_csv(
+            "name,description,value,variant\n\
+             vmax,\"上限, 含 \"\"余量\"\"\",3.6,\n\
+             \"vmin\",\"下限\",\"3.0\",\"SKU, 5V\"\n",
+        ).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(config.constants["vmax"], 3.6);
+        assert_eq!(config.variants["SKU, 5V"].values["vmin"], 3.0);
+        assert_eq!(split_csv_row(r#"a,"b ""c"", d",e"#).unwrap(), vec!["a", r#"b "c", d"#, "e"]);
+    }
+
+    #[test]
+    fn test_subset_and_merge() {
+        let mut config = LimitConfig::default();
+        config.import_csv("name,value,variant,slot\nvmax,3.6,,\nimax,2,,\nvmax,3.5,,1\nvmax,5.5,SKU5V,\nimax,1,SKU3V,\n").unwrap();
+        config.variants.get_mut("SKU5V").unwrap().sn_prefixes.push("P5".into());
+
+        let subset = config.subset(&HashSet::from(["vmax"]));
+        assert_eq!(subset.constants.len(), 1);
+        assert_eq!(subset.slots[&1]["vmax"], 3.5);
+        assert_eq!(subset.variants.len(), 1);
+        assert_eq!(subset.variants["SKU5V"].sn_prefixes, vec!["P5".to_string()]);
+
+        let mut station = LimitConfig::default();
+        station.import_csv("name,value\nvmax,9.9\nlocal,1").unwrap();
+        assert_eq!(station.merge(subset), 3);
+        assert_eq!(station.constants["vmax"], 3.6);
+        assert_eq!(station.constants["local"], 1.0);
+        assert_eq!(station.resolve(1, Some("P5-1"))["vmax"], 5.5);
+    }
+}
@@ -2,12 +2,28 @@
 
 pub mod device;
 pub mod step;
+pub mod plan;
+pub mod package;
 pub mod variable;
 pub mod result;
 pub mod status;
+pub mod trace;
+pub mod replay;
+pub mod modbus;
+pub mod unit;
+pub mod limit;
+pub mod spc;
 
 pub use device::*;
 pub use step::*;
+pub use plan::*;
+pub use package::*;
 pub use variable::*;
 pub use result::*;
 pub use status::*;
+pub use trace::*;
+pub use replay::*;
+pub use modbus::*;
+pub use unit::*;
+pub use limit::*;
+pub use spc::*;
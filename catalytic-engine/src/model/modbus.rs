@@ -0,0 +1,62 @@
+//! Modbus 请求描述
+
+use serde::{Deserialize, Serialize};
+
+/// Modbus 传输方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ModbusTransport {
+    /// RTU：地址 + PDU + CRC16
+    #[default]
+    Rtu,
+    /// TCP：MBAP 头 + PDU
+    Tcp,
+}
+
+/// Modbus 功能码
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModbusFunction {
+    ReadCoils,
+    ReadDiscreteInputs,
+    ReadHoldingRegisters,
+    ReadInputRegisters,
+    WriteSingleCoil,
+    WriteSingleRegister,
+    WriteMultipleCoils,
+    WriteMultipleRegisters,
+}
+
+impl ModbusFunction {
+    /// 功能码数值
+    pub fn code(&self) -> u8 {
+        match self {
+            ModbusFunction::ReadCoils => 0x01,
+            ModbusFunction::ReadDiscreteInputs => 0x02,
+            ModbusFunction::ReadHoldingRegisters => 0x03,
+            ModbusFunction::ReadInputRegisters => 0x04,
+            ModbusFunction::WriteSingleCoil => 0x05,
+            ModbusFunction::WriteSingleRegister => 0x06,
+            ModbusFunction::WriteMultipleCoils => 0x0F,
+            ModbusFunction::WriteMultipleRegisters => 0x10,
+        }
+    }
+}
+
+/// Modbus 请求（设置后由引擎构建帧并替代 EngineTask.payload）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModbusRequest {
+    #[serde(default)]
+    pub transport: ModbusTransport,
+    /// 从站地址 / 单元标识
+    pub unit_id: u8,
+    pub function: ModbusFunction,
+    /// 起始地址
+    pub address: u16,
+    /// 读取数量（寄存器或线圈个数）
+    #[serde(default)]
+    pub count: u16,
+    /// 写入值（寄存器值，线圈为 0/1）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<u16>,
+}
@@ -0,0 +1,207 @@
+//! 方案包（跨工位 / 跨引擎版本迁移用的导出格式）
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::model::{CheckRule, Command, DeviceType, LimitConfig, TestPlan};
+
+/// 方案包元数据
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PackageMetadata {
+    /// 包名称
+    #[serde(default)]
+    pub name: String,
+    /// 描述
+    #[serde(default)]
+    pub description: String,
+    /// 导出时间（RFC 3339）
+    #[serde(default)]
+    pub created_at: String,
+    /// 导出时的引擎版本
+    #[serde(default)]
+    pub engine_version: String,
+}
+
+/// 步骤限值（便于审阅与单独修改）
+///
+/// 导入时按 step_id 覆盖方案中对应步骤的 check_rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepLimit {
+    pub step_id: u32,
+    #[serde(default)]
+    pub step_name: String,
+    pub check_rule: CheckRule,
+}
+
+/// 方案包
+///
+/// 设备类型只携带模板信息（名称、插件），不含工位相关的设备实例；
+/// 命令库按设备类型单独存放。
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PlanPackage {
+    /// schema 版本
+    pub schema_version: u32,
+    /// 元数据
+    #[serde(default)]
+    pub metadata: PackageMetadata,
+    /// 测试方案（名称为空表示默认步骤列表）
+    pub plan: TestPlan,
+    /// 设备类型 {type_name: DeviceType}
+    #[serde(default)]
+    pub device_types: HashMap<String, DeviceType>,
+    /// 命令库 {type_name: [Command]}
+    #[serde(default)]
+    pub commands: HashMap<String, Vec<Command>>,
+    /// 步骤限值
+    #[serde(default)]
+    pub limits: Vec<StepLimit>,
+    /// 步骤以 `@名称` 引用的限值（只含被引用的名称，导入时合并到本工位限值表）
+    #[serde(default, skip_serializing_if = "LimitConfig::is_empty")]
+    pub named_limits: LimitConfig,
+    /// 内容校验和（FNV-1a 64，十六进制）
+    #[serde(default)]
+    pub checksum: String,
+}
+
+impl PlanPackage {
+    /// 计算内容校验和
+    pub fn compute_checksum(&self) -> String {
+        checksum_of(&serde_json::to_value(self).unwrap_or_default())
+    }
+
+    /// 填充校验和
+    pub fn seal(&mut self) {
+        self.checksum = self.compute_checksum();
+    }
+
+    /// 校验和是否匹配
+    pub fn verify(&self) -> bool {
+        self.checksum.eq_ignore_ascii_case(&self.compute_checksum())
+    }
+
+    /// 将限值应用到方案步骤，返回被覆盖的步骤数
+    pub fn apply_limits(&mut self) -> usize {
+        let mut applied = 0;
+        for limit in &self.limits {
+            if let Some(step) = self.plan.steps.iter_mut().find(|s| s.step_id == limit.step_id) {
+                step.check_rule = Some(limit.check_rule.clone());
+                applied += 1;
+            }
+        }
+        applied
+    }
+}
+
+/// 计算 JSON 文档的校验和（不含 checksum 字段本身）
+///
+/// 对键排序后的规范化文本计算，结果与 HashMap 迭代顺序及 serde_json 的
+/// preserve_order 特性无关；导入时直接对原始文档计算，避免新版本结构体
+/// 补齐的默认字段影响结果
+pub fn checksum_of(doc: &serde_json::Value) -> String {
+    let mut value = doc.clone();
+    if let Some(obj) = value.as_object_mut() {
+        obj.remove("checksum");
+    }
+    let mut canonical = String::new();
+    write_canonical(&value, &mut canonical);
+    format!("{:016x}", fnv1a64(canonical.as_bytes()))
+}
+
+/// 规范化序列化：对象键按字典序排列，其余与紧凑 JSON 相同
+fn write_canonical(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::Value::from(key.as_str()).to_string());
+                out.push(':');
+                write_canonical(item, out);
+            }
+            out.push('}');
+        }
+        serde_json::Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+/// FNV-1a 64 位哈希
+fn fnv1a64(data: &[u8]) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    data.iter().fold(OFFSET, |hash, b| (hash ^ *b as u64).wrapping_mul(PRIME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::TestStep;
+
+    fn sample_package() -> PlanPackage {
+        PlanPackage {
+            schema_version: 2,
+            plan: TestPlan {
+                name: "product_a".to_string(),
+                steps: vec![TestStep { step_id: 1, step_name: "v".to_string(), ..Default::default() }],
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_checksum_roundtrip() {
+        let mut package = sample_package();
+        package.seal();
+        assert!(package.verify());
+
+        let json = serde_json::to_string(&package).unwrap();
+        let restored: PlanPackage = serde_json::from_str(&json).unwrap();
+        assert!(restored.verify());
+    }
+
+    #[test]
+    fn test_checksum_detects_tampering() {
+        let mut package = sample_package();
+        package.seal();
+        package.plan.steps[0].step_name = "tampered".to_string();
+        assert!(!package.verify());
+    }
+
+    #[test]
+    fn test_checksum_ignores_key_order() {
+        let a: serde_json::Value = serde_json::from_str(r#"{"b": {"y": 1, "x": [2, {"q": 1, "p": 0}]}, "a": "s"}"#).unwrap();
+        let b: serde_json::Value = serde_json::from_str(r#"{"a": "s", "b": {"x": [2, {"p": 0, "q": 1}], "y": 1}}"#).unwrap();
+        assert_eq!(checksum_of(&a), checksum_of(&b));
+
+        let mut canonical = String::new();
+        write_canonical(&a, &mut canonical);
+        assert_eq!(canonical, r#"{"a":"s","b":{"x":[2,{"p":0,"q":1}],"y":1}}"#);
+    }
+
+    #[test]
+    fn test_apply_limits() {
+        let mut package = sample_package();
+        package.limits.push(StepLimit {
+            step_id: 1,
+            step_name: "v".to_string(),
+            check_rule: CheckRule::RangeCheck {
+                variable: None, min: 1.0.into(), max: 2.0.into(), include_min: true, include_max: true, unit: None,
+            },
+        });
+        assert_eq!(package.apply_limits(), 1);
+        assert!(package.plan.steps[0].check_rule.is_some());
+    }
+}
@@ -0,0 +1,120 @@
+//! 测试方案（配方）定义
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::model::step::TestStep;
+
+/// 命名测试方案
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TestPlan {
+    /// 方案名称（与存储 key 一致）
+    #[serde(default)]
+    pub name: String,
+    /// 方案描述
+    #[serde(default)]
+    pub description: String,
+    /// 测试步骤列表
+    #[serde(default)]
+    pub steps: Vec<TestStep>,
+}
+
+/// SN 前缀匹配规则
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SnPlanRule {
+    /// SN 前缀
+    pub prefix: String,
+    /// 方案名称
+    pub plan: String,
+}
+
+/// 方案分配配置
+///
+/// 槽位执行时按以下顺序选择方案：
+/// 1. 槽位显式分配的方案
+/// 2. 与槽位 SN 匹配的最长前缀规则
+/// 3. 默认步骤列表（`test_steps`）
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PlanAssignment {
+    /// 槽位 ID -> 方案名称
+    #[serde(default)]
+    pub slots: HashMap<u32, String>,
+    /// SN 前缀规则
+    #[serde(default)]
+    pub sn_rules: Vec<SnPlanRule>,
+}
+
+impl PlanAssignment {
+    /// 解析槽位应使用的方案名称（None 表示使用默认步骤）
+    pub fn resolve(&self, slot_id: u32, sn: Option<&str>) -> Option<&str> {
+        if let Some(name) = self.slots.get(&slot_id) {
+            return Some(name.as_str());
+        }
+
+        let sn = sn?;
+        self.sn_rules
+            .iter()
+            .filter(|r| sn.starts_with(&r.prefix))
+            .max_by_key(|r| r.prefix.len())
+            .map(|r| r.plan.as_str())
+    }
+
+    /// 设置 SN 前缀规则（同前缀覆盖）
+    pub fn set_sn_rule(&mut self, prefix: String, plan: String) {
+        if let Some(rule) = self.sn_rules.iter_mut().find(|r| r.prefix == prefix) {
+            rule.plan = plan;
+        } else {
+            self.sn_rules.push(SnPlanRule { prefix, plan });
+        }
+    }
+
+    /// 移除 SN 前缀规则
+    pub fn remove_sn_rule(&mut self, prefix: &str) -> bool {
+        let before = self.sn_rules.len();
+        self.sn_rules.retain(|r| r.prefix != prefix);
+        self.sn_rules.len() != before
+    }
+
+    /// 移除所有引用指定方案的分配
+    pub fn remove_plan(&mut self, plan: &str) {
+        self.slots.retain(|_, name| name != plan);
+        self.sn_rules.retain(|r| r.plan != plan);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_slot_before_sn() {
+        let mut assignment = PlanAssignment::default();
+        assignment.slots.insert(0, "plan_a".to_string());
+        assignment.set_sn_rule("SN".to_string(), "plan_b".to_string());
+
+        assert_eq!(assignment.resolve(0, Some("SN001")), Some("plan_a"));
+        assert_eq!(assignment.resolve(1, Some("SN001")), Some("plan_b"));
+        assert_eq!(assignment.resolve(1, None), None);
+    }
+
+    #[test]
+    fn test_resolve_longest_prefix() {
+        let mut assignment = PlanAssignment::default();
+        assignment.set_sn_rule("AB".to_string(), "short".to_string());
+        assignment.set_sn_rule("AB12".to_string(), "long".to_string());
+
+        assert_eq!(assignment.resolve(0, Some("AB1234")), Some("long"));
+        assert_eq!(assignment.resolve(0, Some("AB99")), Some("short"));
+        assert_eq!(assignment.resolve(0, Some("XY")), None);
+    }
+
+    #[test]
+    fn test_remove_plan_clears_assignments() {
+        let mut assignment = PlanAssignment::default();
+        assignment.slots.insert(0, "plan_a".to_string());
+        assignment.set_sn_rule("SN".to_string(), "plan_a".to_string());
+        assignment.remove_plan("plan_a");
+
+        assert!(assignment.slots.is_empty());
+        assert!(assignment.sn_rules.is_empty());
+    }
+}
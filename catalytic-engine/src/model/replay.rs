@@ -0,0 +1,38 @@
+//! 离线回放结果定义
+
+use serde::{Deserialize, Serialize};
+use crate::model::status::StepStatus;
+
+/// 单个步骤的回放对比
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayStepDiff {
+    pub step_id: u32,
+    pub step_name: String,
+    /// 原始运行的状态（None 表示原始运行未执行该步骤）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_status: Option<StepStatus>,
+    /// 回放状态（None 表示回放未执行该步骤）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replayed_status: Option<StepStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_value: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replayed_value: Option<serde_json::Value>,
+    /// 判定是否变化
+    pub changed: bool,
+    /// 回放时的结果摘要
+    #[serde(default)]
+    pub summary: String,
+}
+
+/// 离线回放报告
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayReport {
+    /// 被回放的运行 ID
+    pub run_id: String,
+    pub slot_id: u32,
+    /// 按执行顺序排列的步骤对比
+    pub steps: Vec<ReplayStepDiff>,
+    /// 判定发生变化的步骤数
+    pub changed: usize,
+}
@@ -1,7 +1,41 @@
 //! 步骤执行结果定义
 
+use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use crate::model::status::StepStatus;
+use crate::model::variable::VariableDisplay;
+use crate::model::trace::TraceRecord;
+use crate::model::spc::SpcRule;
+use crate::parser::hex;
+
+/// 原始响应最多保留的字节数
+pub const RAW_RESPONSE_LIMIT: usize = 256;
+
+/// 原始响应（解析失败时附加，便于排查）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawResponse {
+    /// UTF-8（有损）文本
+    pub text: String,
+    /// 十六进制
+    pub hex: String,
+    /// 原始总长度
+    pub length: usize,
+    /// 是否被截断
+    pub truncated: bool,
+}
+
+impl RawResponse {
+    /// 截取前 RAW_RESPONSE_LIMIT 字节
+    pub fn new(data: &[u8]) -> Self {
+        let kept = &data[..data.len().min(RAW_RESPONSE_LIMIT)];
+        Self {
+            text: String::from_utf8_lossy(kept).into_owned(),
+            hex: hex::encode(kept),
+            length: data.len(),
+            truncated: kept.len() < data.len(),
+        }
+    }
+}
 
 /// 检查结果详情
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +48,9 @@ pub struct CheckResultDetail {
     pub actual: serde_json::Value,
     /// 检查结果
     pub passed: bool,
+    /// 组合检查（all/any/not）的子项结果
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<CheckResultDetail>,
 }
 
 /// 步骤执行结果
@@ -30,14 +67,32 @@ pub struct StepResult {
     /// 最终检查用的值
     #[serde(skip_serializing_if = "Option::is_none")]
     pub final_value: Option<serde_json::Value>,
+    /// 最终值的单位
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
     /// 检查结果详情
     #[serde(skip_serializing_if = "Option::is_none")]
     pub check_result: Option<CheckResultDetail>,
     /// 结果摘要
     pub result_summary: String,
+    /// 通过但落在保护带内（临界）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub marginal: bool,
+    /// 测量值触发的 SPC 判异规则（过程失控预警，与通过与否无关）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spc_violations: Vec<SpcRule>,
     /// 错误信息
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_message: Option<String>,
+    /// 解析规则提取出的命名变量
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub variables: HashMap<String, VariableDisplay>,
+    /// 原始响应（仅在解析失败时附加）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_response: Option<RawResponse>,
+    /// 原始 I/O 追踪（仅在开启追踪且步骤未通过时附加）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<Vec<TraceRecord>>,
 }
 
 impl StepResult {
@@ -49,9 +104,15 @@ impl StepResult {
             status: StepStatus::Passed,
             elapsed_ms,
             final_value: None,
+            unit: None,
             check_result: None,
             result_summary: summary,
+            marginal: false,
+            spc_violations: Vec::new(),
             error_message: None,
+            variables: HashMap::new(),
+            raw_response: None,
+            trace: None,
         }
     }
 
@@ -63,9 +124,15 @@ impl StepResult {
             status: StepStatus::Failed,
             elapsed_ms,
             final_value: None,
+            unit: None,
             check_result: None,
             result_summary: summary,
+            marginal: false,
+            spc_violations: Vec::new(),
             error_message: error,
+            variables: HashMap::new(),
+            raw_response: None,
+            trace: None,
         }
     }
 
@@ -77,9 +144,15 @@ impl StepResult {
             status: StepStatus::Timeout,
             elapsed_ms,
             final_value: None,
+            unit: None,
             check_result: None,
             result_summary: "执行超时".to_string(),
+            marginal: false,
+            spc_violations: Vec::new(),
             error_message: Some("任务超时".to_string()),
+            variables: HashMap::new(),
+            raw_response: None,
+            trace: None,
         }
     }
 
@@ -91,9 +164,15 @@ impl StepResult {
             status: StepStatus::Skipped,
             elapsed_ms: 0,
             final_value: None,
+            unit: None,
             check_result: None,
             result_summary: "已跳过".to_string(),
+            marginal: false,
+            spc_violations: Vec::new(),
             error_message: None,
+            variables: HashMap::new(),
+            raw_response: None,
+            trace: None,
         }
     }
 }
@@ -0,0 +1,52 @@
+//! 统计过程控制（SPC）
+
+use serde::{Deserialize, Serialize};
+
+/// Western Electric 判异规则
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SpcRule {
+    /// 1 点落在 3σ 之外
+    Beyond3Sigma,
+    /// 连续 3 点中有 2 点落在同侧 2σ 之外
+    TwoOfThreeBeyond2Sigma,
+    /// 连续 5 点中有 4 点落在同侧 1σ 之外
+    FourOfFiveBeyond1Sigma,
+    /// 连续 8 点落在中心线同侧
+    EightOnOneSide,
+}
+
+/// 单个槽位上单个步骤的滚动统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpcStats {
+    pub slot_id: u32,
+    pub step_id: u32,
+    pub step_name: String,
+    /// 累计记录的测量次数
+    pub count: u64,
+    /// 滚动窗口内的样本数
+    pub samples: usize,
+    pub mean: f64,
+    /// 样本标准差（窗口内少于 2 个样本时为 0）
+    pub sigma: f64,
+    pub min: f64,
+    pub max: f64,
+    pub last_value: f64,
+    /// 规格下限（来自检查规则）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lsl: Option<f64>,
+    /// 规格上限（来自检查规则）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usl: Option<f64>,
+    /// 过程能力指数（需双边限值）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cp: Option<f64>,
+    /// 考虑偏移的过程能力指数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpk: Option<f64>,
+    /// 最近一次测量触发的判异规则
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<SpcRule>,
+    /// 最近更新时间戳（毫秒）
+    pub updated_at: u64,
+}
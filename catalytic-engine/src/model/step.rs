@@ -1,6 +1,9 @@
 //! 测试步骤定义
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::model::limit::LimitValue;
+use crate::model::modbus::{ModbusRequest, ModbusTransport};
 
 /// serde 默认值辅助函数
 fn default_true() -> bool { true }
@@ -14,6 +17,8 @@ pub enum ExecutionMode {
     EngineControlled,
     /// Host 控制模式
     HostControlled,
+    /// 计算模式（仅求值表达式，不调用回调）
+    Calculation,
 }
 
 /// 动作类型
@@ -60,12 +65,149 @@ impl Default for CheckType {
     }
 }
 
+/// 解析失败时的处理策略
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ParseErrorPolicy {
+    /// 步骤结果为 Error（走 next_on_error）
+    #[default]
+    Error,
+    /// 视为检查不通过（走 next_on_fail）
+    Fail,
+}
+
+/// 计算项：求值表达式并写入变量
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Calculation {
+    /// 目标变量名
+    pub target: String,
+    /// 表达式（可引用槽位变量及之前计算项的结果）
+    pub expr: String,
+    /// 结果单位
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+}
+
+/// 保护带：通过但距限值过近的读数判为临界（marginal）
+///
+/// 带宽取 absolute 与 percent 折算值中的较大者。双边限值按区间宽度折算，
+/// 单边限值（阈值）按限值绝对值折算。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub struct GuardBand {
+    /// 百分比带宽
+    #[serde(default)]
+    pub percent: f64,
+    /// 绝对带宽（规则单位）
+    #[serde(default)]
+    pub absolute: f64,
+}
+
 /// 数据解析规则
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ParseRule {
-    /// 数字提取
+    /// 数字提取（识别 SI 词头与单位，保持原值并记录识别出的单位）
     Number,
+    /// 带单位数字提取：换算到目标单位，量纲不同时解析失败
+    Quantity {
+        unit: String,
+    },
+    /// 正则提取
+    Regex {
+        pattern: String,
+        #[serde(default)]
+        group: usize,
+        /// 结果类型（默认自动推断）
+        #[serde(default)]
+        value_type: ValueType,
+    },
+    /// JSON 路径
+    Json {
+        path: String,
+        /// 结果类型（默认自动推断）
+        #[serde(default)]
+        value_type: ValueType,
+    },
+    /// 正则多变量提取（命名捕获组或组序号 -> 变量）
+    RegexFields {
+        pattern: String,
+        fields: Vec<FieldMapping>,
+    },
+    /// JSON 多路径提取（JSON 路径 -> 变量）
+    JsonFields {
+        fields: Vec<FieldMapping>,
+    },
+    /// 二进制结构提取（按字节偏移，直接作用于原始字节）
+    Binary {
+        fields: Vec<BinaryField>,
+    },
+    /// SCPI 逗号分隔列表（指定 index 取单个元素，否则转为浮点数组）
+    ScpiList {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        index: Option<usize>,
+        /// 单个元素的类型（默认自动推断）
+        #[serde(default)]
+        value_type: ValueType,
+    },
+    /// SCPI 引号字符串
+    ScpiString,
+    /// IEEE 488.2 二进制块（#<n><长度><数据>）转为浮点数组
+    ScpiBlock {
+        /// 元素格式（默认 f32）
+        #[serde(default = "default_block_format")]
+        format: BinaryType,
+        /// 字节序（默认大端，对应 :FORMat:BORDer NORMal）
+        #[serde(default)]
+        byte_order: Endian,
+    },
+    /// 变换流水线：从原始响应开始依次执行各变换，最后一步的结果为主值
+    Pipeline {
+        steps: Vec<Transform>,
+    },
+    /// 自定义解析器（按名称引用引擎注册的实现）
+    Custom {
+        name: String,
+        #[serde(default)]
+        params: serde_json::Value,
+    },
+    /// Modbus 响应：校验 CRC/MBAP 与异常码后按字段提取数据区
+    ///
+    /// 字段 offset 为数据区内的字节偏移（寄存器 n 对应 offset 2n）；
+    /// 未定义字段时主值为数据区原始字节
+    Modbus {
+        #[serde(default)]
+        transport: ModbusTransport,
+        /// 期望的从站地址（None 表示不校验）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unit_id: Option<u8>,
+        #[serde(default)]
+        fields: Vec<BinaryField>,
+    },
+}
+
+fn default_block_format() -> BinaryType {
+    BinaryType::F32
+}
+
+/// 流水线变换
+///
+/// 文本类变换作用于上一步结果的文本形式（字节按 UTF-8 有损解码，数值取十进制表示）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Transform {
+    /// 去除首尾空白
+    Trim,
+    /// 按分隔符拆分后取第 index 项（负数从末尾计数）
+    Split {
+        separator: String,
+        index: i64,
+    },
+    /// 按字符截取子串
+    Substring {
+        start: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        len: Option<usize>,
+    },
     /// 正则提取
     Regex {
         pattern: String,
@@ -74,6 +216,146 @@ pub enum ParseRule {
     },
     /// JSON 路径
     Json { path: String },
+    /// 提取第一个数字
+    Number,
+    /// 十六进制文本解码为字节
+    HexDecode,
+    /// 按进制解析整数（允许 0x / 0o / 0b 前缀）
+    ParseInt {
+        #[serde(default = "default_radix")]
+        radix: u32,
+    },
+    /// 线性换算: value * scale + bias
+    Scale {
+        #[serde(default = "default_scale")]
+        scale: f64,
+        #[serde(default)]
+        bias: f64,
+    },
+    /// 四舍五入到指定小数位
+    Round {
+        #[serde(default)]
+        digits: u32,
+    },
+    /// 查表映射（按文本匹配，未命中时使用 default，无 default 则报错）
+    Lookup {
+        table: HashMap<String, String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        default: Option<String>,
+    },
+}
+
+fn default_radix() -> u32 {
+    10
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+/// 二进制字段定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinaryField {
+    /// 目标变量名
+    pub save_to: String,
+    /// 字节偏移
+    pub offset: usize,
+    /// 数据类型
+    #[serde(rename = "type")]
+    pub data_type: BinaryType,
+    /// 字节序（默认大端）
+    #[serde(default)]
+    pub endian: Endian,
+    /// 字节长度（仅 bytes 类型，缺省取到末尾）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub length: Option<usize>,
+    /// 位段（仅整数类型）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bits: Option<BitRange>,
+    /// 线性换算系数（设置 scale 或 bias 时结果为浮点数）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f64>,
+    /// 线性换算偏移: value * scale + bias
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bias: Option<f64>,
+    /// 单位：随变量记录，检查规则声明单位时据此换算，量纲不同时检查报错
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+}
+
+/// 二进制字段类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BinaryType {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    F32,
+    F64,
+    /// 原始字节切片
+    Bytes,
+}
+
+impl BinaryType {
+    /// 固定字节长度（bytes 类型为 0）
+    pub fn size(&self) -> usize {
+        match self {
+            BinaryType::U8 | BinaryType::I8 => 1,
+            BinaryType::U16 | BinaryType::I16 => 2,
+            BinaryType::U32 | BinaryType::I32 | BinaryType::F32 => 4,
+            BinaryType::F64 => 8,
+            BinaryType::Bytes => 0,
+        }
+    }
+}
+
+/// 字节序
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Endian {
+    #[default]
+    Big,
+    Little,
+}
+
+/// 位段 [start, start + len)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitRange {
+    pub start: u8,
+    pub len: u8,
+}
+
+/// 多变量提取的字段映射
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldMapping {
+    /// 数据来源（正则命名组名 / 组序号，或 JSON 路径）
+    pub source: String,
+    /// 目标变量名
+    pub save_to: String,
+    /// 值类型
+    #[serde(default)]
+    pub value_type: ValueType,
+    /// 单位：随变量记录，检查规则声明单位时据此换算，量纲不同时检查报错
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+}
+
+/// 提取值的类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueType {
+    /// 自动推断（同 Variable::from_string）
+    #[default]
+    Auto,
+    Int,
+    Float,
+    Bytes,
+    String,
+    /// true/false、1/0、on/off、yes/no
+    Bool,
 }
 
 /// 比较运算符
@@ -120,6 +402,8 @@ impl CompareOp {
 }
 
 /// 检查规则
+///
+/// 范围、阈值与容差类规则的限值为 [`LimitValue`]，可写作数值、`$变量名` 或 `@限值名`
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "template", rename_all = "snake_case")]
 pub enum CheckRule {
@@ -127,14 +411,17 @@ pub enum CheckRule {
     RangeCheck {
         #[serde(skip_serializing_if = "Option::is_none")]
         variable: Option<String>,
-        min: f64,
-        max: f64,
+        min: LimitValue,
+        max: LimitValue,
         /// 是否包含最小值（默认 true，即 >=）
         #[serde(default = "default_true")]
         include_min: bool,
         /// 是否包含最大值（默认 true，即 <=）
         #[serde(default = "default_true")]
         include_max: bool,
+        /// 限值单位：变量带单位时先换算到该单位，量纲不同时检查报错
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unit: Option<String>,
     },
     /// 双变量比较
     Compare {
@@ -146,13 +433,62 @@ pub enum CheckRule {
     Threshold {
         variable: String,
         operator: CompareOp,
-        value: f64,
+        value: LimitValue,
+        /// 阈值单位（同 RangeCheck）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unit: Option<String>,
     },
     /// 字符串包含
     Contains {
         variable: String,
         substring: String,
     },
+    /// 字符串相等
+    StringEquals {
+        variable: String,
+        expected: String,
+        /// 忽略大小写
+        #[serde(default)]
+        ignore_case: bool,
+    },
+    /// 正则匹配（在字符串任意位置匹配，需整串匹配时使用 ^...$）
+    RegexMatch {
+        variable: String,
+        pattern: String,
+    },
+    /// 前缀匹配
+    StartsWith {
+        variable: String,
+        prefix: String,
+        #[serde(default)]
+        ignore_case: bool,
+    },
+    /// 后缀匹配
+    EndsWith {
+        variable: String,
+        suffix: String,
+        #[serde(default)]
+        ignore_case: bool,
+    },
+    /// 枚举：值属于给定集合
+    OneOf {
+        variable: String,
+        values: Vec<String>,
+        #[serde(default)]
+        ignore_case: bool,
+    },
+    /// 字符串长度范围（按字符计，闭区间）
+    LengthRange {
+        variable: String,
+        min: usize,
+        max: usize,
+    },
+    /// 版本号比较（如 firmware >= 2.3.1）
+    Version {
+        variable: String,
+        operator: CompareOp,
+        version: String,
+    },
     /// 位检查
     BitCheck {
         variable: String,
@@ -161,6 +497,161 @@ pub enum CheckRule {
     },
     /// 表达式检查
     Expression { expr: String },
+    /// 标称值 ± 绝对容差（如 100 Ω ± 0.5 Ω）
+    Tolerance {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        variable: Option<String>,
+        nominal: LimitValue,
+        tolerance: LimitValue,
+        /// 标称值与容差的单位（同 RangeCheck）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unit: Option<String>,
+    },
+    /// 标称值 ± 百分比（如 5.0 V ± 2%）
+    TolerancePercent {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        variable: Option<String>,
+        nominal: LimitValue,
+        percent: LimitValue,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unit: Option<String>,
+    },
+    /// 近似相等：满足绝对容差、相对容差或 ULP 距离任一条件即通过（均为 0 时要求精确相等）
+    ApproxEqual {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        variable: Option<String>,
+        expected: LimitValue,
+        /// 绝对容差（与 expected 同单位）
+        #[serde(default)]
+        abs_tol: LimitValue,
+        /// 相对容差，以两者绝对值较大者为基准
+        #[serde(default)]
+        rel_tol: LimitValue,
+        #[serde(default)]
+        max_ulps: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unit: Option<String>,
+    },
+    /// 波形统计量范围检查（FloatArray）
+    WaveformStat {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        variable: Option<String>,
+        statistic: WaveformStatistic,
+        min: LimitValue,
+        max: LimitValue,
+        /// 限值单位（同 RangeCheck）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unit: Option<String>,
+    },
+    /// 逐点限值模板：上下限曲线与波形等长，缺省表示不限
+    WaveformMask {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        variable: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        upper: Option<Vec<f64>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        lower: Option<Vec<f64>>,
+    },
+    /// 单调性检查
+    Monotonic {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        variable: Option<String>,
+        direction: MonotonicDirection,
+        /// 严格单调（相邻点不允许相等）
+        #[serde(default)]
+        strict: bool,
+    },
+    /// 稳定时间：此后所有点都落在 target ± tolerance 内
+    SettlingTime {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        variable: Option<String>,
+        target: f64,
+        tolerance: f64,
+        /// 采样间隔 (s)
+        sample_interval: f64,
+        /// 允许的最大稳定时间 (s)
+        max_time: f64,
+    },
+    /// 上升时间：从幅度的 low_pct 到 high_pct 所用时间
+    RiseTime {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        variable: Option<String>,
+        /// 采样间隔 (s)
+        sample_interval: f64,
+        /// 允许的最大上升时间 (s)
+        max_time: f64,
+        /// 低阈值百分比（默认 10）
+        #[serde(default = "default_low_pct")]
+        low_pct: f64,
+        /// 高阈值百分比（默认 90）
+        #[serde(default = "default_high_pct")]
+        high_pct: f64,
+    },
+    /// 组合检查：全部子规则通过（AND）
+    All { rules: Vec<CheckRule> },
+    /// 组合检查：任一子规则通过（OR）
+    Any { rules: Vec<CheckRule> },
+    /// 组合检查：子规则取反
+    Not { rule: Box<CheckRule> },
+    /// 自定义检查器（按名称引用引擎注册的实现）
+    Custom {
+        name: String,
+        #[serde(default)]
+        params: serde_json::Value,
+    },
+}
+
+impl CheckRule {
+    /// 规则（含组合检查的子规则）引用的所有限值
+    pub fn limit_values(&self) -> Vec<&LimitValue> {
+        match self {
+            CheckRule::RangeCheck { min, max, .. } => vec![min, max],
+            CheckRule::Threshold { value, .. } => vec![value],
+            CheckRule::Tolerance { nominal, tolerance, .. } => vec![nominal, tolerance],
+            CheckRule::TolerancePercent { nominal, percent, .. } => vec![nominal, percent],
+            CheckRule::ApproxEqual { expected, abs_tol, rel_tol, .. } => vec![expected, abs_tol, rel_tol],
+            CheckRule::WaveformStat { min, max, .. } => vec![min, max],
+            CheckRule::All { rules } | CheckRule::Any { rules } => rules.iter().flat_map(|r| r.limit_values()).collect(),
+            CheckRule::Not { rule } => rule.limit_values(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn default_low_pct() -> f64 { 10.0 }
+fn default_high_pct() -> f64 { 90.0 }
+
+/// 波形统计量
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WaveformStatistic {
+    Min,
+    Max,
+    Mean,
+    Rms,
+    StdDev,
+    PeakToPeak,
+}
+
+impl WaveformStatistic {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WaveformStatistic::Min => "min",
+            WaveformStatistic::Max => "max",
+            WaveformStatistic::Mean => "mean",
+            WaveformStatistic::Rms => "rms",
+            WaveformStatistic::StdDev => "std_dev",
+            WaveformStatistic::PeakToPeak => "peak_to_peak",
+        }
+    }
+}
+
+/// 单调方向
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MonotonicDirection {
+    Increasing,
+    Decreasing,
 }
 
 /// 自定义 payload 反序列化：支持字符串或字节数组
@@ -213,6 +704,9 @@ pub struct EngineTask {
     /// 发送载荷 - 支持字符串 "*IDN?" 或字节数组 [42, 73, ...]
     #[serde(default, deserialize_with = "deserialize_payload")]
     pub payload: Vec<u8>,
+    /// Modbus 请求描述（设置后由引擎构建请求帧，忽略 payload）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modbus: Option<ModbusRequest>,
     /// 超时时间（毫秒）
     pub timeout_ms: u32,
     /// 数据解析规则
@@ -253,6 +747,9 @@ pub struct TestStep {
     /// Host 控制任务数据
     #[serde(skip_serializing_if = "Option::is_none")]
     pub host_task: Option<HostTask>,
+    /// 计算项（Calculation 模式按顺序求值，最后一项为步骤主值）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calculations: Vec<Calculation>,
     /// 存储结果的变量名
     #[serde(skip_serializing_if = "Option::is_none")]
     pub save_to: Option<String>,
@@ -262,6 +759,12 @@ pub struct TestStep {
     /// 检查规则
     #[serde(skip_serializing_if = "Option::is_none")]
     pub check_rule: Option<CheckRule>,
+    /// 外部检查等待 Host 判定的超时（毫秒，缺省 5000）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check_timeout_ms: Option<u32>,
+    /// 检查保护带（未设置时不判定临界）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guard_band: Option<GuardBand>,
     /// 成功后跳转
     #[serde(skip_serializing_if = "Option::is_none")]
     pub next_on_pass: Option<u32>,
@@ -277,6 +780,9 @@ pub struct TestStep {
     /// 预设跳过（true 表示始终跳过此步骤）
     #[serde(default)]
     pub skip: bool,
+    /// 响应无法解析时的处理策略
+    #[serde(default)]
+    pub on_parse_error: ParseErrorPolicy,
 }
 
 #[cfg(test)]
@@ -342,6 +848,56 @@ mod tests {
         assert!(step.host_task.is_some());
     }
 
+    #[test]
+    fn test_calculation_step_deserialization() {
+        let json = r#"{
+            "step_id": 3,
+            "step_name": "功率",
+            "execution_mode": "calculation",
+            "calculations": [
+                {"target": "p", "expr": "vout * iout", "unit": "W"}
+            ]
+        }"#;
+
+        let step: TestStep = serde_json::from_str(json).unwrap();
+        assert_eq!(step.execution_mode, ExecutionMode::Calculation);
+        assert_eq!(step.calculations[0].unit.as_deref(), Some("W"));
+        assert!(step.engine_task.is_none());
+    }
+
+    #[test]
+    fn test_regex_fields_deserialization() {
+        let json = r#"{
+            "type": "regex_fields",
+            "pattern": "V=(?P<v>[0-9.]+) I=(?P<i>[0-9.]+)",
+            "fields": [
+                {"source": "v", "save_to": "vout", "value_type": "float", "unit": "V"},
+                {"source": "i", "save_to": "iout"}
+            ]
+        }"#;
+
+        let rule: ParseRule = serde_json::from_str(json).unwrap();
+        match rule {
+            ParseRule::RegexFields { fields, .. } => {
+                assert_eq!(fields.len(), 2);
+                assert_eq!(fields[0].unit.as_deref(), Some("V"));
+                assert_eq!(fields[1].value_type, ValueType::Auto);
+            }
+            _ => panic!("expected regex_fields"),
+        }
+    }
+
+    #[test]
+    fn test_number_parse_rule_serde() {
+        let number: ParseRule = serde_json::from_str(r#"{"type": "number"}"#).unwrap();
+        assert!(matches!(number, ParseRule::Number));
+        assert_eq!(serde_json::to_string(&number).unwrap(), r#"{"type":"number"}"#);
+
+        let quantity: ParseRule = serde_json::from_str(r#"{"type": "quantity", "unit": "mV"}"#).unwrap();
+        assert!(matches!(quantity, ParseRule::Quantity { ref unit } if unit == "mV"));
+        assert!(serde_json::from_str::<ParseRule>(r#"{"type": "quantity"}"#).is_err());
+    }
+
     #[test]
     fn test_compare_op() {
         assert!(CompareOp::Gt.compare(5.0, 3.0));
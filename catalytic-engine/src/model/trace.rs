@@ -0,0 +1,125 @@
+//! 原始 I/O 追踪记录
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use crate::parser::hex::serde_hex;
+use crate::model::status::StepStatus;
+
+/// 任务类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceKind {
+    /// EngineControlled 设备通讯
+    Engine,
+    /// HostControlled 任务
+    Host,
+    /// 外部检查（Host 判定）
+    Check,
+}
+
+/// 任务结果
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceOutcome {
+    /// 收到响应
+    Ok,
+    /// 超时（Host 提交超时或引擎等待超时）
+    Timeout,
+    /// Host 提交错误
+    Error,
+    /// 回调返回非 0，任务未下发
+    Rejected,
+}
+
+/// 单次任务的原始收发记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceRecord {
+    /// 运行 ID（见 SlotContext::run_id）
+    pub run_id: String,
+    pub task_id: u64,
+    pub slot_id: u32,
+    pub step_id: u32,
+    pub kind: TraceKind,
+    /// 设备类型名（Host 任务为任务名）
+    pub device: String,
+    /// 设备地址
+    #[serde(default)]
+    pub address: String,
+    /// 发送数据（十六进制）
+    #[serde(with = "serde_hex")]
+    pub payload: Vec<u8>,
+    /// 响应数据（十六进制）
+    #[serde(with = "serde_hex")]
+    pub response: Vec<u8>,
+    /// 耗时（毫秒）
+    pub duration_ms: u32,
+    pub outcome: TraceOutcome,
+    /// 错误信息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// 记录时间戳（毫秒）
+    pub timestamp: u64,
+}
+
+/// 步骤判定记录（用于回放时与原始结果对比）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepVerdict {
+    pub run_id: String,
+    /// 在本次运行中的执行序号
+    pub seq: u64,
+    pub step_id: u32,
+    pub step_name: String,
+    pub status: StepStatus,
+    /// 通过但落在保护带内
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub marginal: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_value: Option<serde_json::Value>,
+}
+
+/// 一次运行开始时的上下文，离线回放据此还原方案、SN 与限值
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunContext {
+    pub run_id: String,
+    pub slot_id: u32,
+    /// 执行的方案名称（None 表示默认步骤列表）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sn: Option<String>,
+    /// 解析后的限值 {名称: 数值}
+    #[serde(default)]
+    pub limits: HashMap<String, f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_record_hex_serialization() {
+        let record = TraceRecord {
+            run_id: "1-0".to_string(),
+            task_id: 7,
+            slot_id: 0,
+            step_id: 1,
+            kind: TraceKind::Engine,
+            device: "dut".to_string(),
+            address: "COM3".to_string(),
+            payload: b"*IDN?".to_vec(),
+            response: vec![0x01, 0xff],
+            duration_ms: 12,
+            outcome: TraceOutcome::Ok,
+            message: None,
+            timestamp: 0,
+        };
+
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(json["payload"], "2a49444e3f");
+        assert_eq!(json["response"], "01ff");
+
+        let restored: TraceRecord = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.response, vec![0x01, 0xff]);
+    }
+}
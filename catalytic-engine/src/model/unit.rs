@@ -0,0 +1,93 @@
+//! 物理单位与 SI 词头
+
+/// 可带 SI 词头的基本单位
+const PREFIXABLE: &[&str] = &["V", "A", "W", "Hz", "s", "F", "H", "Ω", "J", "VA", "K"];
+
+/// 不带词头的单位
+const PLAIN: &[&str] = &["dB", "dBm", "%", "°C", "ppm"];
+
+/// SI 词头及倍率
+const PREFIXES: &[(&str, f64)] = &[
+    ("p", 1e-12),
+    ("n", 1e-9),
+    ("u", 1e-6),
+    ("µ", 1e-6),
+    ("μ", 1e-6),
+    ("m", 1e-3),
+    ("k", 1e3),
+    ("M", 1e6),
+    ("G", 1e9),
+    ("T", 1e12),
+];
+
+/// 解析后的单位：symbol = 词头 + 基本单位，值 × factor 即为基本单位下的值
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unit {
+    pub symbol: String,
+    pub base: &'static str,
+    pub factor: f64,
+}
+
+impl Unit {
+    /// 解析单位符号（如 "mV"、"kΩ"、"MHz"），无法识别时返回 None
+    pub fn parse(symbol: &str) -> Option<Unit> {
+        let symbol = symbol.trim();
+        let unit = |base: &'static str, factor: f64| Unit { symbol: symbol.to_string(), base, factor };
+
+        if let Some(base) = find_base(symbol) {
+            return Some(unit(base, 1.0));
+        }
+        PREFIXES.iter().find_map(|(prefix, factor)| {
+            let rest = symbol.strip_prefix(prefix)?;
+            let base = find_base(rest).filter(|b| PREFIXABLE.contains(b))?;
+            Some(unit(base, *factor))
+        })
+    }
+
+    /// 是否与另一单位量纲相同
+    pub fn compatible(&self, other: &Unit) -> bool {
+        self.base == other.base
+    }
+}
+
+/// 查找基本单位（ohm/Ohm 视为 Ω）
+fn find_base(symbol: &str) -> Option<&'static str> {
+    if symbol.eq_ignore_ascii_case("ohm") {
+        return Some("Ω");
+    }
+    PREFIXABLE.iter().chain(PLAIN).find(|b| **b == symbol).copied()
+}
+
+/// 单位换算：无法识别的单位仅在符号相同时视为兼容，量纲不同返回 None
+pub fn convert_unit(value: f64, from: &str, to: &str) -> Option<f64> {
+    if from == to {
+        return Some(value);
+    }
+    let (from, to) = (Unit::parse(from)?, Unit::parse(to)?);
+    from.compatible(&to).then(|| value * from.factor / to.factor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_unit() {
+        assert_eq!(Unit::parse("mV").map(|u| (u.base, u.factor)), Some(("V", 1e-3)));
+        assert_eq!(Unit::parse("kohm").map(|u| u.base), Some("Ω"));
+        assert_eq!(Unit::parse("MHz").map(|u| u.factor), Some(1e6));
+        assert_eq!(Unit::parse("dBm").map(|u| u.base), Some("dBm"));
+        assert_eq!(Unit::parse("K").map(|u| u.factor), Some(1.0));
+        assert!(Unit::parse("mdB").is_none());
+        assert!(Unit::parse("items").is_none());
+    }
+
+    #[test]
+    fn test_convert_unit() {
+        assert!((convert_unit(3.3, "mV", "V").unwrap() - 0.0033).abs() < 1e-12);
+        assert!((convert_unit(2.4, "GHz", "MHz").unwrap() - 2400.0).abs() < 1e-9);
+        assert_eq!(convert_unit(1.0, "rpm", "rpm"), Some(1.0));
+        assert_eq!(convert_unit(1.0, "mA", "V"), None);
+        assert_eq!(convert_unit(1.0, "rpm", "V"), None);
+    }
+}
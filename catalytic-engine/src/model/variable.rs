@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use crate::model::step::ValueType;
 
 /// 变量类型
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +16,10 @@ pub enum Variable {
     Bytes(Vec<u8>),
     /// 浮点数组（波形等）
     FloatArray(Vec<f64>),
+    /// 字符串（版本号、IDN 等文本）
+    String(String),
+    /// 布尔值
+    Bool(bool),
 }
 
 impl Variable {
@@ -23,6 +28,15 @@ impl Variable {
         match self {
             Variable::Int(v) => Some(*v as f64),
             Variable::Float(v) => Some(*v),
+            Variable::Bool(v) => Some(if *v { 1.0 } else { 0.0 }),
+            _ => None,
+        }
+    }
+
+    /// 尝试获取浮点数组（用于波形检查）
+    pub fn as_f64_slice(&self) -> Option<&[f64]> {
+        match self {
+            Variable::FloatArray(v) => Some(v),
             _ => None,
         }
     }
@@ -32,6 +46,7 @@ impl Variable {
         match self {
             Variable::Int(v) => Some(*v),
             Variable::Float(v) => Some(*v as i64),
+            Variable::Bool(v) => Some(*v as i64),
             _ => None,
         }
     }
@@ -43,21 +58,50 @@ impl Variable {
             Variable::Float(v) => v.to_string(),
             Variable::Bytes(v) => format!("{:?}", v),
             Variable::FloatArray(v) => format!("{:?}", v),
+            Variable::String(v) => v.clone(),
+            Variable::Bool(v) => v.to_string(),
         }
     }
 
-    /// 从字符串解析变量（自动推断类型）
+    /// 是否为文本类变量（字符串或布尔）
+    pub fn is_text(&self) -> bool {
+        matches!(self, Variable::String(_) | Variable::Bool(_))
+    }
+
+    /// 按指定类型从字符串解析变量
+    pub fn parse_as(s: &str, value_type: ValueType) -> Option<Variable> {
+        let s = s.trim();
+        match value_type {
+            ValueType::Auto => Some(Variable::from_string(s)),
+            ValueType::Int => s.parse::<i64>().ok().map(Variable::Int),
+            ValueType::Float => s.parse::<f64>().ok().map(Variable::Float),
+            ValueType::Bytes => Some(Variable::Bytes(s.as_bytes().to_vec())),
+            ValueType::String => Some(Variable::String(s.to_string())),
+            ValueType::Bool => parse_bool(s).map(Variable::Bool),
+        }
+    }
+
+    /// 从字符串解析变量（自动推断类型，非数值文本存为字符串）
     pub fn from_string(s: &str) -> Variable {
         if let Ok(f) = s.parse::<f64>() {
             Variable::Float(f)
         } else if let Ok(i) = s.parse::<i64>() {
             Variable::Int(i)
         } else {
-            Variable::Bytes(s.as_bytes().to_vec())
+            Variable::String(s.to_string())
         }
     }
 }
 
+/// 解析布尔文本（true/false、1/0、on/off、yes/no，不区分大小写）
+fn parse_bool(s: &str) -> Option<bool> {
+    match s.to_ascii_lowercase().as_str() {
+        "true" | "1" | "on" | "yes" => Some(true),
+        "false" | "0" | "off" | "no" => Some(false),
+        _ => None,
+    }
+}
+
 /// 变量显示信息（用于 UI）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VariableDisplay {
@@ -75,6 +119,8 @@ impl From<&Variable> for VariableDisplay {
             Variable::Float(v) => (format!("{:.6}", v), "float"),
             Variable::Bytes(v) => (format!("{} bytes", v.len()), "bytes"),
             Variable::FloatArray(v) => (format!("{} points", v.len()), "float_array"),
+            Variable::String(v) => (v.clone(), "string"),
+            Variable::Bool(v) => (v.to_string(), "bool"),
         };
         VariableDisplay {
             value,
@@ -84,10 +130,21 @@ impl From<&Variable> for VariableDisplay {
     }
 }
 
+impl VariableDisplay {
+    /// 带单位的显示信息
+    pub fn with_unit(var: &Variable, unit: Option<&str>) -> Self {
+        let mut display = Self::from(var);
+        display.unit = unit.map(str::to_string);
+        display
+    }
+}
+
 /// 变量池
 #[derive(Debug, Default)]
 pub struct VariablePool {
     variables: HashMap<String, Variable>,
+    /// 变量单位：用于显示，并在检查时换算到规则单位、拒绝量纲不同的比较
+    units: HashMap<String, String>,
 }
 
 impl VariablePool {
@@ -96,9 +153,23 @@ impl VariablePool {
         Self::default()
     }
 
-    /// 设置变量
+    /// 设置变量（不带单位，清除之前记录的单位）
     pub fn set(&mut self, name: &str, value: Variable) {
+        self.set_with_unit(name, value, None);
+    }
+
+    /// 设置变量及其单位（unit 为 None 时清除旧单位）
+    pub fn set_with_unit(&mut self, name: &str, value: Variable, unit: Option<String>) {
         self.variables.insert(name.to_string(), value);
+        match unit {
+            Some(unit) => self.units.insert(name.to_string(), unit),
+            None => self.units.remove(name),
+        };
+    }
+
+    /// 获取变量单位
+    pub fn unit(&self, name: &str) -> Option<&str> {
+        self.units.get(name).map(String::as_str)
     }
 
     /// 获取变量
@@ -108,12 +179,14 @@ impl VariablePool {
 
     /// 移除变量
     pub fn remove(&mut self, name: &str) -> Option<Variable> {
+        self.units.remove(name);
         self.variables.remove(name)
     }
 
     /// 清空所有变量
     pub fn clear(&mut self) {
         self.variables.clear();
+        self.units.clear();
     }
 
     /// 获取所有变量名
@@ -123,19 +196,14 @@ impl VariablePool {
 
     /// 转换为 JSON 值
     pub fn to_json(&self) -> serde_json::Value {
-        let map: HashMap<String, VariableDisplay> = self
-            .variables
-            .iter()
-            .map(|(k, v)| (k.clone(), VariableDisplay::from(v)))
-            .collect();
-        serde_json::to_value(map).unwrap_or(serde_json::Value::Object(Default::default()))
+        serde_json::to_value(self.to_display_map()).unwrap_or(serde_json::Value::Object(Default::default()))
     }
 
     /// 转换为 HashMap<String, VariableDisplay>
     pub fn to_display_map(&self) -> HashMap<String, VariableDisplay> {
         self.variables
             .iter()
-            .map(|(k, v)| (k.clone(), VariableDisplay::from(v)))
+            .map(|(k, v)| (k.clone(), VariableDisplay::with_unit(v, self.unit(k))))
             .collect()
     }
 }
@@ -154,6 +222,42 @@ mod tests {
         assert_eq!(pool.get("count").unwrap().as_i64(), Some(42));
     }
 
+    #[test]
+    fn test_variable_units() {
+        let mut pool = VariablePool::new();
+        pool.set_with_unit("vout", Variable::Float(3.31), Some("V".to_string()));
+        assert_eq!(pool.to_display_map()["vout"].unit.as_deref(), Some("V"));
+
+        // 覆盖为不带单位的值时不保留旧单位
+        pool.set("vout", Variable::Float(3.30));
+        assert_eq!(pool.unit("vout"), None);
+        assert_eq!(pool.to_display_map()["vout"].unit, None);
+
+        pool.set_with_unit("vout", Variable::Float(3.31), Some("V".to_string()));
+        pool.remove("vout");
+        assert_eq!(pool.unit("vout"), None);
+
+        assert!(matches!(Variable::parse_as("42", ValueType::Int), Some(Variable::Int(42))));
+        assert!(Variable::parse_as("4.2", ValueType::Int).is_none());
+    }
+
+    #[test]
+    fn test_string_and_bool_variables() {
+        let idn = Variable::from_string("KEYSIGHT,34465A,MY123,A.03.01");
+        assert!(matches!(idn, Variable::String(ref s) if s.starts_with("KEYSIGHT")));
+        assert_eq!(VariableDisplay::from(&idn).var_type, "string");
+        assert_eq!(VariableDisplay::from(&idn).value, "KEYSIGHT,34465A,MY123,A.03.01");
+
+        assert!(matches!(Variable::parse_as("ON", ValueType::Bool), Some(Variable::Bool(true))));
+        assert!(matches!(Variable::parse_as("0", ValueType::Bool), Some(Variable::Bool(false))));
+        assert!(Variable::parse_as("maybe", ValueType::Bool).is_none());
+        assert!(matches!(Variable::parse_as("42", ValueType::String), Some(Variable::String(ref s)) if s == "42"));
+        assert_eq!(Variable::Bool(true).as_i64(), Some(1));
+
+        let json = serde_json::to_string(&Variable::Bool(true)).unwrap();
+        assert_eq!(json, r#"{"type":"bool","value":true}"#);
+    }
+
     #[test]
     fn test_variable_serialization() {
         let var = Variable::Float(3.31);
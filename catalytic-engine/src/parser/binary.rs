@@ -0,0 +1,204 @@
+//! 二进制结构解析器
+//!
+//! 按字节偏移从原始响应中提取字段，支持整数/浮点类型、字节序、位段与线性换算。
+
+use crate::error::{EngineError, Result};
+use crate::model::{BinaryField, BinaryType, Endian, Variable};
+use crate::parser::ParsedField;
+
+/// 按字段定义提取二进制数据
+pub fn extract_binary(data: &[u8], fields: &[BinaryField]) -> Result<Vec<ParsedField>> {
+    fields
+        .iter()
+        .map(|field| {
+            Ok(ParsedField {
+                name: field.save_to.clone(),
+                value: extract_field(data, field)?,
+                unit: field.unit.clone(),
+            })
+        })
+        .collect()
+}
+
+/// 提取单个字段
+fn extract_field(data: &[u8], field: &BinaryField) -> Result<Variable> {
+    let size = match field.data_type {
+        BinaryType::Bytes => field.length.unwrap_or(data.len().saturating_sub(field.offset)),
+        other => other.size(),
+    };
+    let bytes = slice(data, field.offset, size, &field.save_to)?;
+
+    // 位段只对整数类型有意义，浮点/字节类型配置位段视为配置错误
+    if field.bits.is_some() && matches!(field.data_type, BinaryType::F32 | BinaryType::F64 | BinaryType::Bytes) {
+        return Err(EngineError::ParseError(format!(
+            "字段 {} 的类型 {:?} 不支持位段", field.save_to, field.data_type
+        )));
+    }
+
+    let raw = match field.data_type {
+        BinaryType::Bytes => return Ok(Variable::Bytes(bytes.to_vec())),
+        BinaryType::F32 | BinaryType::F64 => {
+            let v = decode_number(bytes, field.data_type, field.endian).unwrap_or_default();
+            return Ok(Variable::Float(apply_linear(v, field)));
+        }
+        BinaryType::U8 | BinaryType::U16 | BinaryType::U32 => read_uint(bytes, field.endian) as i64,
+        BinaryType::I8 => read_uint(bytes, field.endian) as u8 as i8 as i64,
+        BinaryType::I16 => read_uint(bytes, field.endian) as u16 as i16 as i64,
+        BinaryType::I32 => read_uint(bytes, field.endian) as u32 as i32 as i64,
+    };
+
+    // 位段：从整数中取出 [start, start + len) 位
+    let raw = match &field.bits {
+        Some(bits) => {
+            if bits.len == 0 || bits.start as usize + bits.len as usize > size * 8 {
+                return Err(EngineError::ParseError(format!(
+                    "字段 {} 的位段超出范围: start={}, len={}", field.save_to, bits.start, bits.len
+                )));
+            }
+            let mask = if bits.len >= 64 { u64::MAX } else { (1u64 << bits.len) - 1 };
+            (((raw as u64) >> bits.start) & mask) as i64
+        }
+        None => raw,
+    };
+
+    if field.scale.is_some() || field.bias.is_some() {
+        Ok(Variable::Float(apply_linear(raw as f64, field)))
+    } else {
+        Ok(Variable::Int(raw))
+    }
+}
+
+/// 按类型解码单个数值（bytes 类型或长度不符时返回 None）
+pub(crate) fn decode_number(bytes: &[u8], data_type: BinaryType, endian: Endian) -> Option<f64> {
+    if data_type == BinaryType::Bytes || bytes.len() != data_type.size() {
+        return None;
+    }
+
+    let raw = read_uint(bytes, endian);
+    Some(match data_type {
+        BinaryType::U8 | BinaryType::U16 | BinaryType::U32 => raw as f64,
+        BinaryType::I8 => raw as u8 as i8 as f64,
+        BinaryType::I16 => raw as u16 as i16 as f64,
+        BinaryType::I32 => raw as u32 as i32 as f64,
+        BinaryType::F32 => f32::from_bits(raw as u32) as f64,
+        BinaryType::F64 => f64::from_bits(raw),
+        BinaryType::Bytes => return None,
+    })
+}
+
+/// 取出 [offset, offset + size) 字节（offset/size 来自配置，需防止溢出）
+fn slice<'a>(data: &'a [u8], offset: usize, size: usize, name: &str) -> Result<&'a [u8]> {
+    let end = offset.checked_add(size).ok_or_else(|| EngineError::ParseError(format!(
+        "字段 {} 的偏移溢出: offset={}, size={}", name, offset, size
+    )))?;
+    data.get(offset..end).ok_or_else(|| EngineError::ParseError(format!(
+        "字段 {} 超出数据长度: offset={}, size={}, len={}", name, offset, size, data.len()
+    )))
+}
+
+/// 按字节序读取无符号整数（最多 8 字节）
+fn read_uint(bytes: &[u8], endian: Endian) -> u64 {
+    let fold = |acc: u64, b: &u8| (acc << 8) | *b as u64;
+    match endian {
+        Endian::Big => bytes.iter().fold(0, fold),
+        Endian::Little => bytes.iter().rev().fold(0, fold),
+    }
+}
+
+/// 线性换算: value * scale + bias
+fn apply_linear(value: f64, field: &BinaryField) -> f64 {
+    value * field.scale.unwrap_or(1.0) + field.bias.unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::BitRange;
+
+    fn field(save_to: &str, offset: usize, data_type: BinaryType) -> BinaryField {
+        BinaryField {
+            save_to: save_to.to_string(),
+            offset,
+            data_type,
+            endian: Endian::Big,
+            length: None,
+            bits: None,
+            scale: None,
+            bias: None,
+            unit: None,
+        }
+    }
+
+    #[test]
+    fn test_extract_integer_types() {
+        let data = [0x12, 0x34, 0xff, 0xfe, 0x00, 0x00, 0x01, 0x00];
+        let fields = vec![
+            field("u16", 0, BinaryType::U16),
+            field("i16", 2, BinaryType::I16),
+            BinaryField { endian: Endian::Little, ..field("u32le", 4, BinaryType::U32) },
+            field("u8", 1, BinaryType::U8),
+        ];
+
+        let result = extract_binary(&data, &fields).unwrap();
+        assert!(matches!(result[0].value, Variable::Int(0x1234)));
+        assert!(matches!(result[1].value, Variable::Int(-2)));
+        assert!(matches!(result[2].value, Variable::Int(0x0001_0000)));
+        assert!(matches!(result[3].value, Variable::Int(0x34)));
+    }
+
+    #[test]
+    fn test_extract_float_and_scale() {
+        let mut data = 3.5f32.to_be_bytes().to_vec();
+        data.extend_from_slice(&[0x01, 0xf4]); // 500
+
+        let fields = vec![
+            field("f", 0, BinaryType::F32),
+            BinaryField { scale: Some(0.01), bias: Some(-1.0), ..field("scaled", 4, BinaryType::U16) },
+        ];
+
+        let result = extract_binary(&data, &fields).unwrap();
+        assert_eq!(result[0].value.as_f64(), Some(3.5));
+        assert_eq!(result[1].value.as_f64(), Some(4.0));
+    }
+
+    #[test]
+    fn test_extract_bits_and_bytes() {
+        let data = [0b1010_0110, 0xaa, 0xbb, 0xcc];
+        let fields = vec![
+            BinaryField { bits: Some(BitRange { start: 1, len: 2 }), ..field("mode", 0, BinaryType::U8) },
+            BinaryField { length: Some(2), ..field("payload", 1, BinaryType::Bytes) },
+            field("rest", 2, BinaryType::Bytes),
+        ];
+
+        let result = extract_binary(&data, &fields).unwrap();
+        assert!(matches!(result[0].value, Variable::Int(0b11)));
+        assert!(matches!(&result[1].value, Variable::Bytes(b) if b == &vec![0xaa, 0xbb]));
+        assert!(matches!(&result[2].value, Variable::Bytes(b) if b == &vec![0xbb, 0xcc]));
+    }
+
+    #[test]
+    fn test_extract_out_of_range() {
+        let data = [0x00, 0x01];
+        assert!(extract_binary(&data, &[field("x", 1, BinaryType::U16)]).is_err());
+
+        let bad_bits = BinaryField { bits: Some(BitRange { start: 6, len: 4 }), ..field("b", 0, BinaryType::U8) };
+        assert!(extract_binary(&data, &[bad_bits]).is_err());
+
+        // 配置中的超大偏移不得溢出
+        let err = extract_binary(&data, &[field("huge", usize::MAX, BinaryType::U16)]).unwrap_err();
+        assert!(err.to_string().contains("溢出"), "{}", err);
+        let huge_bytes = BinaryField { length: Some(usize::MAX), ..field("blob", 1, BinaryType::Bytes) };
+        assert!(extract_binary(&data, &[huge_bytes]).is_err());
+    }
+
+    #[test]
+    fn test_bits_rejected_for_non_integer_types() {
+        let data = 1.0f32.to_be_bytes();
+        let float_bits = BinaryField { bits: Some(BitRange { start: 0, len: 4 }), ..field("f", 0, BinaryType::F32) };
+        let err = extract_binary(&data, &[float_bits]).unwrap_err();
+        assert!(err.to_string().contains("不支持位段"), "{}", err);
+
+        let bytes_bits = BinaryField { bits: Some(BitRange { start: 0, len: 4 }), ..field("raw", 0, BinaryType::Bytes) };
+        assert!(extract_binary(&data, &[bytes_bits]).is_err());
+    }
+}
@@ -0,0 +1,64 @@
+//! 十六进制编解码
+
+use crate::error::{EngineError, Result};
+
+/// 编码为小写十六进制字符串（无分隔符）
+pub fn encode(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 解码十六进制字符串（忽略空白与 "0x" 前缀）
+pub fn decode(text: &str) -> Result<Vec<u8>> {
+    let cleaned: String = text
+        .trim()
+        .trim_start_matches("0x")
+        .trim_start_matches("0X")
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+
+    if !cleaned.len().is_multiple_of(2) {
+        return Err(EngineError::ParseError(format!("十六进制长度必须为偶数: {}", text)));
+    }
+
+    (0..cleaned.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&cleaned[i..i + 2], 16)
+                .map_err(|_| EngineError::ParseError(format!("无效的十六进制: {}", text)))
+        })
+        .collect()
+}
+
+/// serde 辅助：字节数组以十六进制字符串序列化
+pub mod serde_hex {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::encode(data))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        super::decode(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_roundtrip() {
+        let data = vec![0x00, 0x1f, 0xab, 0xff];
+        assert_eq!(encode(&data), "001fabff");
+        assert_eq!(decode("001fabff").unwrap(), data);
+        assert_eq!(decode("0x00 1F AB FF").unwrap(), data);
+    }
+
+    #[test]
+    fn test_hex_invalid() {
+        assert!(decode("abc").is_err());
+        assert!(decode("zz").is_err());
+    }
+}
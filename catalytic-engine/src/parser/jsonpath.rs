@@ -1,27 +1,44 @@
 //! JSON 路径解析器
 
 use crate::error::{EngineError, Result};
-use jsonpath_rust::JsonPathQuery;
+use crate::model::FieldMapping;
+use crate::parser::{convert_field, ParsedField, Patterns};
+use jsonpath_rust::path::config::JsonPathConfig;
 
 /// 使用 JSON 路径提取数据
-pub fn extract_json(text: &str, path: &str) -> Result<String> {
+pub fn extract_json(text: &str, path: &str, patterns: &Patterns) -> Result<String> {
     let json: serde_json::Value = serde_json::from_str(text)
         .map_err(|e| EngineError::ParseError(format!("JSON 解析失败: {}", e)))?;
     
-    let result = json.path(path)
-        .map_err(|e| EngineError::ParseError(format!("JSON 路径查询失败: {}", e)))?;
-    
+    query_first(&json, path, patterns)
+}
+
+/// 解析一次 JSON，按多个路径提取字段
+pub fn extract_json_fields(text: &str, fields: &[FieldMapping], patterns: &Patterns) -> Result<Vec<ParsedField>> {
+    let json: serde_json::Value = serde_json::from_str(text)
+        .map_err(|e| EngineError::ParseError(format!("JSON 解析失败: {}", e)))?;
+
+    fields
+        .iter()
+        .map(|field| {
+            let value = query_first(&json, &field.source, patterns)?;
+            convert_field(field, &value)
+        })
+        .collect()
+}
+
+/// 查询 JSON 路径的第一个结果
+fn query_first(json: &serde_json::Value, path: &str, patterns: &Patterns) -> Result<String> {
+    let inst = patterns.json_path(path)?;
+    let result = inst.find_slice(json, JsonPathConfig::default());
+
     // 取第一个结果
-    match result.as_array() {
-        Some(arr) if !arr.is_empty() => {
-            match &arr[0] {
-                serde_json::Value::String(s) => Ok(s.clone()),
-                serde_json::Value::Number(n) => Ok(n.to_string()),
-                serde_json::Value::Bool(b) => Ok(b.to_string()),
-                other => Ok(other.to_string()),
-            }
-        }
-        _ => Err(EngineError::ParseError(format!(
+    match result.first().map(|v| &**v) {
+        Some(serde_json::Value::String(s)) => Ok(s.clone()),
+        Some(serde_json::Value::Number(n)) => Ok(n.to_string()),
+        Some(serde_json::Value::Bool(b)) => Ok(b.to_string()),
+        Some(other) => Ok(other.to_string()),
+        None => Err(EngineError::ParseError(format!(
             "JSON 路径 '{}' 未找到结果", path
         ))),
     }
@@ -34,14 +51,31 @@ mod tests {
     #[test]
     fn test_extract_json() {
         let json = r#"{"measurement": {"voltage": 3.31, "unit": "V"}}"#;
-        let result = extract_json(json, "$.measurement.voltage").unwrap();
+        let result = extract_json(json, "$.measurement.voltage", &Patterns::new()).unwrap();
         assert_eq!(result, "3.31");
     }
 
     #[test]
     fn test_extract_json_string() {
         let json = r#"{"device": {"name": "Device_A"}}"#;
-        let result = extract_json(json, "$.device.name").unwrap();
+        let result = extract_json(json, "$.device.name", &Patterns::new()).unwrap();
         assert_eq!(result, "Device_A");
     }
+
+    #[test]
+    fn test_extract_json_fields() {
+        use crate::model::ValueType;
+
+        let json = r#"{"v": 3.31, "i": 0.52, "status": {"code": 7}}"#;
+        let fields = vec![
+            FieldMapping { source: "$.v".into(), save_to: "vout".into(), value_type: ValueType::Float, unit: Some("V".into()) },
+            FieldMapping { source: "$.status.code".into(), save_to: "code".into(), value_type: ValueType::Int, unit: None },
+        ];
+        let result = extract_json_fields(json, &fields, &Patterns::new()).unwrap();
+        assert_eq!(result[0].value.as_f64(), Some(3.31));
+        assert_eq!(result[1].value.as_i64(), Some(7));
+
+        let missing = vec![FieldMapping { source: "$.x".into(), save_to: "x".into(), value_type: ValueType::Auto, unit: None }];
+        assert!(extract_json_fields(json, &missing, &Patterns::new()).is_err());
+    }
 }
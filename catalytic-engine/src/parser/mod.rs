@@ -3,17 +3,117 @@
 pub mod number;
 pub mod regex_parser;
 pub mod jsonpath;
+pub mod hex;
+pub mod binary;
+pub mod scpi;
+pub mod pipeline;
+pub mod patterns;
 
-use crate::model::ParseRule;
-use crate::error::Result;
+use crate::model::{FieldMapping, ParseRule, ValueType, Variable};
+use crate::protocol::modbus;
+use crate::error::{EngineError, Result};
+
+pub use patterns::Patterns;
+
+/// 解析得到的命名变量
+#[derive(Debug, Clone)]
+pub struct ParsedField {
+    pub name: String,
+    pub value: Variable,
+    pub unit: Option<String>,
+}
+
+/// 解析结果
+#[derive(Debug, Clone, Default)]
+pub struct ParseOutput {
+    /// 主值：写入步骤的 save_to，并作为检查规则的默认值
+    pub value: Option<Variable>,
+    /// 主值的单位
+    pub unit: Option<String>,
+    /// 多变量规则提取出的命名变量
+    pub fields: Vec<ParsedField>,
+    /// 流水线各步骤的中间值（调试用）
+    pub stages: Vec<String>,
+}
+
+impl ParseOutput {
+    /// 单值结果
+    pub fn single(value: Variable) -> Self {
+        Self { value: Some(value), unit: None, fields: Vec::new(), stages: Vec::new() }
+    }
+
+    /// 带单位的单值结果
+    pub fn with_unit(value: Variable, unit: Option<String>) -> Self {
+        Self { unit, ..Self::single(value) }
+    }
+
+    /// 多变量结果（主值取第一个字段）
+    pub fn fields(fields: Vec<ParsedField>) -> Self {
+        let first = fields.first();
+        Self {
+            value: first.map(|f| f.value.clone()),
+            unit: first.and_then(|f| f.unit.clone()),
+            fields,
+            stages: Vec::new(),
+        }
+    }
+}
 
 /// 解析响应数据
-pub fn parse_response(data: &[u8], rule: &ParseRule) -> Result<String> {
-    let text = String::from_utf8_lossy(data);
+///
+/// 文本类规则按 UTF-8（有损）解码后解析，二进制规则直接作用于原始字节；
+/// 正则与 JSON 路径优先取自 patterns 中的预编译结果
+pub fn parse_response(data: &[u8], rule: &ParseRule, patterns: &Patterns) -> Result<ParseOutput> {
+    let text = || String::from_utf8_lossy(data);
     
     match rule {
-        ParseRule::Number => number::extract_number(&text),
-        ParseRule::Regex { pattern, group } => regex_parser::extract_regex(&text, pattern, *group),
-        ParseRule::Json { path } => jsonpath::extract_json(&text, path),
+        ParseRule::Number => number::parse_number(&text(), None).map(|(v, u)| ParseOutput::with_unit(v, u)),
+        ParseRule::Quantity { unit } => number::parse_number(&text(), Some(unit)).map(|(v, u)| ParseOutput::with_unit(v, u)),
+        ParseRule::Regex { pattern, group, value_type } => {
+            let s = regex_parser::extract_regex(&text(), pattern, *group, patterns)?;
+            convert_value(&s, *value_type).map(ParseOutput::single)
+        }
+        ParseRule::Json { path, value_type } => {
+            let s = jsonpath::extract_json(&text(), path, patterns)?;
+            convert_value(&s, *value_type).map(ParseOutput::single)
+        }
+        ParseRule::RegexFields { pattern, fields } => regex_parser::extract_regex_fields(&text(), pattern, fields, patterns).map(ParseOutput::fields),
+        ParseRule::JsonFields { fields } => jsonpath::extract_json_fields(&text(), fields, patterns).map(ParseOutput::fields),
+        ParseRule::Binary { fields } => binary::extract_binary(data, fields).map(ParseOutput::fields),
+        ParseRule::ScpiList { index, value_type } => scpi::parse_list(&text(), *index, *value_type).map(ParseOutput::single),
+        ParseRule::ScpiString => scpi::parse_string(&text()).map(ParseOutput::single),
+        ParseRule::ScpiBlock { format, byte_order } => scpi::parse_block(data, *format, *byte_order).map(ParseOutput::single),
+        ParseRule::Pipeline { steps } => pipeline::run_pipeline(data, steps, patterns),
+        ParseRule::Custom { name, .. } => Err(EngineError::ParseError(format!("自定义解析器 '{}' 需通过引擎注册表执行", name))),
+        ParseRule::Modbus { transport, unit_id, fields } => {
+            let response = modbus::decode_response(data, *transport, *unit_id)?;
+            if fields.is_empty() {
+                Ok(ParseOutput::single(Variable::Bytes(response.data)))
+            } else {
+                binary::extract_binary(&response.data, fields).map(ParseOutput::fields)
+            }
+        }
     }
 }
+
+/// 按指定类型转换提取出的文本
+pub(crate) fn convert_value(text: &str, value_type: ValueType) -> Result<Variable> {
+    Variable::parse_as(text, value_type).ok_or_else(|| {
+        EngineError::ParseError(format!("值 '{}' 无法转换为 {:?}", text, value_type))
+    })
+}
+
+/// 按字段映射转换提取出的文本
+pub(crate) fn convert_field(mapping: &FieldMapping, text: &str) -> Result<ParsedField> {
+    let value = Variable::parse_as(text, mapping.value_type).ok_or_else(|| {
+        EngineError::ParseError(format!(
+            "字段 {} 的值 '{}' 无法转换为 {:?}", mapping.save_to, text, mapping.value_type
+        ))
+    })?;
+
+    Ok(ParsedField {
+        name: mapping.save_to.clone(),
+        value,
+        unit: mapping.unit.clone(),
+    })
+}
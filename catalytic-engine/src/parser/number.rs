@@ -1,21 +1,62 @@
 //! 数字提取器
 
 use crate::error::{EngineError, Result};
+use crate::model::{convert_unit, Unit, Variable};
 use regex::Regex;
+use std::sync::LazyLock;
+
+/// 数字模式：整数、浮点数、科学计数法（包括负数）
+static NUMBER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"-?\d+\.?\d*(?:[eE][-+]?\d+)?").expect("数字正则无效")
+});
 
 /// 从文本中提取第一个数字（支持科学计数法）
 pub fn extract_number(text: &str) -> Result<String> {
-    // 匹配整数、浮点数、科学计数法（包括负数）
     // 例如: 3.3, -12.5, 2.4E+09, 1.25E-03, 2400000000
-    let re = Regex::new(r"-?\d+\.?\d*(?:[eE][-+]?\d+)?")
-        .map_err(|e| EngineError::ParseError(format!("正则编译失败: {}", e)))?;
-    
-    match re.find(text.trim()) {
+    match NUMBER_RE.find(text.trim()) {
         Some(m) => Ok(m.as_str().to_string()),
         None => Err(EngineError::ParseError(format!("未找到数字: {}", text))),
     }
 }
 
+/// 从文本中提取第一个数字及紧随其后的单位（可带 SI 词头，如 "3.3mV"、"10 kΩ"）
+///
+/// 数字后的单词无法识别为单位时视为无单位
+pub fn extract_quantity(text: &str) -> Result<(String, Option<Unit>)> {
+    let text = text.trim();
+    let m = NUMBER_RE.find(text).ok_or_else(|| EngineError::ParseError(format!("未找到数字: {}", text)))?;
+    let token: String = text[m.end()..]
+        .trim_start()
+        .chars()
+        .take_while(|c| c.is_alphabetic() || matches!(c, '%' | '°'))
+        .collect();
+
+    Ok((m.as_str().to_string(), Unit::parse(&token)))
+}
+
+/// 解析带单位的数字
+///
+/// 指定 target 时换算到目标单位（数字无单位时视为已是目标单位，量纲不同报错）；
+/// 否则保持原值并返回识别出的单位
+pub fn parse_number(text: &str, target: Option<&str>) -> Result<(Variable, Option<String>)> {
+    let (number, unit) = extract_quantity(text)?;
+
+    let Some(target) = target else {
+        return Ok((Variable::from_string(&number), unit.map(|u| u.symbol)));
+    };
+
+    let value: f64 = number
+        .parse()
+        .map_err(|_| EngineError::ParseError(format!("无效数字: {}", number)))?;
+    let value = match unit {
+        Some(unit) => convert_unit(value, &unit.symbol, target).ok_or_else(|| {
+            EngineError::ParseError(format!("单位不兼容: {} 无法换算为 {}", unit.symbol, target))
+        })?,
+        None => value,
+    };
+    Ok((Variable::Float(value), Some(target.to_string())))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -38,4 +79,27 @@ mod tests {
     fn test_extract_number_no_match() {
         assert!(extract_number("no numbers here").is_err());
     }
+
+    #[test]
+    fn test_extract_quantity() {
+        let (n, u) = extract_quantity("3.3mV").unwrap();
+        assert_eq!((n.as_str(), u.map(|u| u.symbol)), ("3.3", Some("mV".to_string())));
+        assert_eq!(extract_quantity("10 kΩ\n").unwrap().1.map(|u| u.base), Some("Ω"));
+        assert!(extract_quantity("-12.5 degrees").unwrap().1.is_none());
+    }
+
+    #[test]
+    fn test_parse_number_with_target_unit() {
+        let (v, unit) = parse_number("3.3mV", Some("V")).unwrap();
+        assert!((v.as_f64().unwrap() - 0.0033).abs() < 1e-12);
+        assert_eq!(unit.as_deref(), Some("V"));
+
+        let (v, _) = parse_number("1.5", Some("mA")).unwrap();
+        assert_eq!(v.as_f64(), Some(1.5));
+
+        let (v, unit) = parse_number("2.4 GHz", None).unwrap();
+        assert_eq!((v.as_f64(), unit.as_deref()), (Some(2.4), Some("GHz")));
+
+        assert!(parse_number("20 mA", Some("V")).is_err());
+    }
 }
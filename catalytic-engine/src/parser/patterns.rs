@@ -0,0 +1,122 @@
+//! 预编译模式
+//!
+//! 方案加载时把解析规则中的正则与 JSON 路径（以及检查规则中的正则）编译一次，执行期间按原始字符串查找。
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use jsonpath_rust::JsonPathInst;
+use regex::Regex;
+
+use crate::error::{EngineError, Result};
+use crate::model::{ParseRule, Transform};
+
+/// 已编译的正则与 JSON 路径
+///
+/// 查找未预编译的模式时即时编译（不缓存），未经编译阶段的调用同样可用
+#[derive(Debug, Clone, Default)]
+pub struct Patterns {
+    regexes: HashMap<String, Regex>,
+    json_paths: HashMap<String, JsonPathInst>,
+}
+
+impl Patterns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 编译解析规则引用的所有模式，无效模式立即报错
+    pub fn add_rule(&mut self, rule: &ParseRule) -> Result<()> {
+        match rule {
+            ParseRule::Regex { pattern, .. } | ParseRule::RegexFields { pattern, .. } => self.add_regex(pattern),
+            ParseRule::Json { path, .. } => self.add_json_path(path),
+            ParseRule::JsonFields { fields } => fields.iter().try_for_each(|f| self.add_json_path(&f.source)),
+            ParseRule::Pipeline { steps } => steps.iter().try_for_each(|t| match t {
+                Transform::Regex { pattern, .. } => self.add_regex(pattern),
+                Transform::Json { path } => self.add_json_path(path),
+                _ => Ok(()),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// 编译单个正则（检查规则等非解析场景使用）
+    pub fn add_regex(&mut self, pattern: &str) -> Result<()> {
+        if !self.regexes.contains_key(pattern) {
+            self.regexes.insert(pattern.to_string(), compile_regex(pattern)?);
+        }
+        Ok(())
+    }
+
+    fn add_json_path(&mut self, path: &str) -> Result<()> {
+        if !self.json_paths.contains_key(path) {
+            self.json_paths.insert(path.to_string(), compile_json_path(path)?);
+        }
+        Ok(())
+    }
+
+    /// 获取正则
+    pub fn regex(&self, pattern: &str) -> Result<Cow<'_, Regex>> {
+        match self.regexes.get(pattern) {
+            Some(re) => Ok(Cow::Borrowed(re)),
+            None => compile_regex(pattern).map(Cow::Owned),
+        }
+    }
+
+    /// 获取 JSON 路径
+    pub fn json_path(&self, path: &str) -> Result<Cow<'_, JsonPathInst>> {
+        match self.json_paths.get(path) {
+            Some(inst) => Ok(Cow::Borrowed(inst)),
+            None => compile_json_path(path).map(Cow::Owned),
+        }
+    }
+
+    /// 已编译的模式数量
+    pub fn len(&self) -> usize {
+        self.regexes.len() + self.json_paths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn compile_regex(pattern: &str) -> Result<Regex> {
+    Regex::new(pattern).map_err(|e| EngineError::ParseError(format!("正则编译失败: {}", e)))
+}
+
+fn compile_json_path(path: &str) -> Result<JsonPathInst> {
+    JsonPathInst::from_str(path).map_err(|e| EngineError::ParseError(format!("JSON 路径无效 '{}': {}", path, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_rule_compiles_once() {
+        let mut patterns = Patterns::new();
+        let rule = ParseRule::Pipeline {
+            steps: vec![
+                Transform::Json { path: "$.v".to_string() },
+                Transform::Regex { pattern: r"(\d+)".to_string(), group: 1 },
+            ],
+        };
+        patterns.add_rule(&rule).unwrap();
+        patterns.add_rule(&rule).unwrap();
+        assert_eq!(patterns.len(), 2);
+        assert!(matches!(patterns.regex(r"(\d+)").unwrap(), Cow::Borrowed(_)));
+        assert!(matches!(patterns.regex(r"\w+").unwrap(), Cow::Owned(_)));
+    }
+
+    #[test]
+    fn test_invalid_patterns_rejected() {
+        let mut patterns = Patterns::new();
+        let bad_regex = ParseRule::Regex { pattern: "(".to_string(), group: 0, value_type: Default::default() };
+        assert!(patterns.add_rule(&bad_regex).is_err());
+
+        let bad_path = ParseRule::Json { path: "$[".to_string(), value_type: Default::default() };
+        assert!(patterns.add_rule(&bad_path).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+//! 变换流水线
+//!
+//! 从原始响应开始依次执行各变换，每一步的中间值记录为调试信息。
+
+use crate::error::{EngineError, Result};
+use crate::model::{Transform, Variable};
+use crate::parser::{hex, jsonpath, number, regex_parser, ParseOutput, Patterns};
+
+/// 流水线中间值
+#[derive(Debug, Clone)]
+enum Stage {
+    Text(String),
+    Bytes(Vec<u8>),
+    Int(i64),
+    Float(f64),
+}
+
+impl Stage {
+    /// 文本形式（字节按 UTF-8 有损解码）
+    fn text(&self) -> String {
+        match self {
+            Stage::Text(s) => s.clone(),
+            Stage::Bytes(b) => String::from_utf8_lossy(b).into_owned(),
+            Stage::Int(v) => v.to_string(),
+            Stage::Float(v) => v.to_string(),
+        }
+    }
+
+    /// 数值形式
+    fn number(&self) -> Result<f64> {
+        match self {
+            Stage::Int(v) => Ok(*v as f64),
+            Stage::Float(v) => Ok(*v),
+            other => {
+                let text = other.text();
+                text.trim().parse::<f64>().map_err(|_| EngineError::ParseError(format!("'{}' 不是数值", text)))
+            }
+        }
+    }
+
+    /// 调试显示
+    fn describe(&self) -> String {
+        match self {
+            Stage::Text(s) => format!("{:?}", s),
+            Stage::Bytes(b) => format!("0x{}", hex::encode(b)),
+            Stage::Int(v) => v.to_string(),
+            Stage::Float(v) => v.to_string(),
+        }
+    }
+
+    fn into_variable(self) -> Variable {
+        match self {
+            Stage::Text(s) => Variable::from_string(s.trim()),
+            Stage::Bytes(b) => Variable::Bytes(b),
+            Stage::Int(v) => Variable::Int(v),
+            Stage::Float(v) => Variable::Float(v),
+        }
+    }
+}
+
+/// 执行流水线，主值为最后一步的结果
+pub fn run_pipeline(data: &[u8], steps: &[Transform], patterns: &Patterns) -> Result<ParseOutput> {
+    let mut stage = Stage::Bytes(data.to_vec());
+    let mut stages = Vec::with_capacity(steps.len());
+
+    for (i, transform) in steps.iter().enumerate() {
+        stage = apply(transform, &stage, patterns).map_err(|e| {
+            let msg = match e {
+                EngineError::ParseError(msg) => msg,
+                other => other.to_string(),
+            };
+            EngineError::ParseError(format!(
+                "流水线第 {} 步 {} 失败（输入 {}）: {}", i + 1, op_name(transform), stage.describe(), msg
+            ))
+        })?;
+        stages.push(format!("{}: {}", op_name(transform), stage.describe()));
+    }
+
+    let mut output = ParseOutput::single(stage.into_variable());
+    output.stages = stages;
+    Ok(output)
+}
+
+/// 执行单个变换
+fn apply(transform: &Transform, input: &Stage, patterns: &Patterns) -> Result<Stage> {
+    match transform {
+        Transform::Trim => Ok(Stage::Text(input.text().trim().to_string())),
+        Transform::Split { separator, index } => {
+            let text = input.text();
+            let parts: Vec<&str> = text.split(separator.as_str()).collect();
+            let i = if *index < 0 { parts.len() as i64 + index } else { *index };
+            usize::try_from(i)
+                .ok()
+                .and_then(|i| parts.get(i))
+                .map(|s| Stage::Text(s.to_string()))
+                .ok_or_else(|| EngineError::ParseError(format!(
+                    "索引 {} 超出范围（共 {} 项）", index, parts.len()
+                )))
+        }
+        Transform::Substring { start, len } => {
+            let text = input.text();
+            let count = text.chars().count();
+            if *start > count {
+                return Err(EngineError::ParseError(format!("起始位置 {} 超出长度 {}", start, count)));
+            }
+            let rest = text.chars().skip(*start);
+            Ok(Stage::Text(match len {
+                Some(len) => rest.take(*len).collect(),
+                None => rest.collect(),
+            }))
+        }
+        Transform::Regex { pattern, group } => {
+            regex_parser::extract_regex(&input.text(), pattern, *group, patterns).map(Stage::Text)
+        }
+        Transform::Json { path } => jsonpath::extract_json(&input.text(), path, patterns).map(Stage::Text),
+        Transform::Number => {
+            let text = number::extract_number(&input.text())?;
+            Stage::Text(text).number().map(Stage::Float)
+        }
+        Transform::HexDecode => hex::decode(&input.text()).map(Stage::Bytes),
+        Transform::ParseInt { radix } => parse_int(&input.text(), *radix).map(Stage::Int),
+        Transform::Scale { scale, bias } => Ok(Stage::Float(input.number()? * scale + bias)),
+        Transform::Round { digits } => {
+            let factor = 10f64.powi(*digits as i32);
+            Ok(Stage::Float((input.number()? * factor).round() / factor))
+        }
+        Transform::Lookup { table, default } => {
+            let text = input.text();
+            let key = text.trim();
+            table
+                .get(key)
+                .or(default.as_ref())
+                .map(|v| Stage::Text(v.clone()))
+                .ok_or_else(|| EngineError::ParseError(format!("查表未命中: '{}'", key)))
+        }
+    }
+}
+
+/// 按进制解析整数
+fn parse_int(text: &str, radix: u32) -> Result<i64> {
+    if !(2..=36).contains(&radix) {
+        return Err(EngineError::ParseError(format!("不支持的进制: {}", radix)));
+    }
+
+    let trimmed = text.trim();
+    let (negative, digits) = match trimmed.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+    let prefix = match radix {
+        16 => Some("0x"),
+        8 => Some("0o"),
+        2 => Some("0b"),
+        _ => None,
+    };
+    let digits = match prefix {
+        Some(p) if digits.get(..2).is_some_and(|head| head.eq_ignore_ascii_case(p)) => &digits[2..],
+        _ => digits,
+    };
+
+    let value = i64::from_str_radix(digits, radix)
+        .map_err(|_| EngineError::ParseError(format!("'{}' 不是 {} 进制整数", trimmed, radix)))?;
+    Ok(if negative { -value } else { value })
+}
+
+/// 变换名称（用于调试信息）
+fn op_name(transform: &Transform) -> &'static str {
+    match transform {
+        Transform::Trim => "trim",
+        Transform::Split { .. } => "split",
+        Transform::Substring { .. } => "substring",
+        Transform::Regex { .. } => "regex",
+        Transform::Json { .. } => "json",
+        Transform::Number => "number",
+        Transform::HexDecode => "hex_decode",
+        Transform::ParseInt { .. } => "parse_int",
+        Transform::Scale { .. } => "scale",
+        Transform::Round { .. } => "round",
+        Transform::Lookup { .. } => "lookup",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_json_regex_scale_round() {
+        let steps = vec![
+            Transform::Json { path: "$.reading".to_string() },
+            Transform::Regex { pattern: r"V=(\S+)".to_string(), group: 1 },
+            Transform::Number,
+            Transform::Scale { scale: 1000.0, bias: 0.0 },
+            Transform::Round { digits: 1 },
+        ];
+
+        let output = run_pipeline(br#"{"reading": "V=3.30126 OK"}"#, &steps, &Patterns::new()).unwrap();
+        assert_eq!(output.value.unwrap().as_f64(), Some(3301.3));
+        assert_eq!(output.stages.len(), 5);
+        assert_eq!(output.stages[1], r#"regex: "3.30126""#);
+    }
+
+    #[test]
+    fn test_split_substring_parse_int() {
+        let steps = vec![
+            Transform::Trim,
+            Transform::Split { separator: ",".to_string(), index: -1 },
+            Transform::Substring { start: 2, len: Some(4) },
+            Transform::ParseInt { radix: 16 },
+        ];
+
+        let output = run_pipeline(b" STAT,OK,0x01FF\r\n", &steps, &Patterns::new()).unwrap();
+        assert!(matches!(output.value, Some(Variable::Int(0x01ff))));
+        assert_eq!(parse_int("-0b101", 2).unwrap(), -5);
+        assert!(parse_int("12", 1).is_err());
+    }
+
+    #[test]
+    fn test_hex_decode_and_lookup() {
+        let hex_steps = vec![Transform::Trim, Transform::HexDecode];
+        let output = run_pipeline(b"0A 0B\n", &hex_steps, &Patterns::new()).unwrap();
+        assert!(matches!(output.value, Some(Variable::Bytes(ref b)) if b == &vec![0x0a, 0x0b]));
+        assert_eq!(output.stages[1], "hex_decode: 0x0a0b");
+
+        let table = HashMap::from([("PASS".to_string(), "1".to_string())]);
+        let lookup = vec![Transform::Lookup { table: table.clone(), default: None }];
+        assert_eq!(run_pipeline(b"PASS\n", &lookup, &Patterns::new()).unwrap().value.unwrap().as_f64(), Some(1.0));
+        assert!(run_pipeline(b"FAIL", &lookup, &Patterns::new()).is_err());
+
+        let with_default = vec![Transform::Lookup { table, default: Some("0".to_string()) }];
+        assert_eq!(run_pipeline(b"FAIL", &with_default, &Patterns::new()).unwrap().value.unwrap().as_f64(), Some(0.0));
+    }
+
+    #[test]
+    fn test_error_reports_step() {
+        let steps = vec![
+            Transform::Split { separator: ",".to_string(), index: 0 },
+            Transform::Scale { scale: 2.0, bias: 0.0 },
+        ];
+        let err = run_pipeline(b"abc,1", &steps, &Patterns::new()).unwrap_err().to_string();
+        assert!(err.contains("第 2 步 scale"), "{}", err);
+    }
+}
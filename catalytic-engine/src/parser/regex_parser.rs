@@ -1,12 +1,12 @@
 //! 正则解析器
 
 use crate::error::{EngineError, Result};
-use regex::Regex;
+use crate::model::FieldMapping;
+use crate::parser::{convert_field, ParsedField, Patterns};
 
 /// 使用正则表达式提取文本
-pub fn extract_regex(text: &str, pattern: &str, group: usize) -> Result<String> {
-    let re = Regex::new(pattern)
-        .map_err(|e| EngineError::ParseError(format!("正则编译失败: {}", e)))?;
+pub fn extract_regex(text: &str, pattern: &str, group: usize, patterns: &Patterns) -> Result<String> {
+    let re = patterns.regex(pattern)?;
     
     match re.captures(text) {
         Some(caps) => {
@@ -29,25 +29,76 @@ pub fn extract_regex(text: &str, pattern: &str, group: usize) -> Result<String>
     }
 }
 
+/// 使用正则表达式一次提取多个字段
+///
+/// 字段来源为命名捕获组名，纯数字时按组序号取值
+pub fn extract_regex_fields(text: &str, pattern: &str, fields: &[FieldMapping], patterns: &Patterns) -> Result<Vec<ParsedField>> {
+    let re = patterns.regex(pattern)?;
+
+    let caps = re.captures(text).ok_or_else(|| EngineError::ParseError(format!(
+        "正则 '{}' 未匹配: {}", pattern, text
+    )))?;
+
+    fields
+        .iter()
+        .map(|field| {
+            let matched = match field.source.parse::<usize>() {
+                Ok(index) => caps.get(index),
+                Err(_) => caps.name(&field.source),
+            };
+            let matched = matched.ok_or_else(|| EngineError::ParseError(format!(
+                "捕获组 {} 不存在", field.source
+            )))?;
+            convert_field(field, matched.as_str())
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_extract_regex() {
-        let result = extract_regex("VOLT: 3.31 V", r"VOLT:\s*([0-9.]+)", 1).unwrap();
+        let result = extract_regex("VOLT: 3.31 V", r"VOLT:\s*([0-9.]+)", 1, &Patterns::new()).unwrap();
         assert_eq!(result, "3.31");
     }
 
     #[test]
     fn test_extract_regex_group_0() {
-        let result = extract_regex("Temperature: 25.5C", r"Temperature: [0-9.]+C", 0).unwrap();
+        let result = extract_regex("Temperature: 25.5C", r"Temperature: [0-9.]+C", 0, &Patterns::new()).unwrap();
         assert_eq!(result, "Temperature: 25.5C");
     }
 
+    #[test]
+    fn test_extract_regex_fields() {
+        use crate::model::{ValueType, Variable};
+
+        let fields = vec![
+            FieldMapping { source: "v".into(), save_to: "vout".into(), value_type: ValueType::Float, unit: Some("V".into()) },
+            FieldMapping { source: "2".into(), save_to: "iout".into(), value_type: ValueType::Auto, unit: None },
+            FieldMapping { source: "t".into(), save_to: "temp".into(), value_type: ValueType::Int, unit: Some("C".into()) },
+        ];
+        let result = extract_regex_fields(
+            "V=3.31 I=0.52 T=25",
+            r"V=(?P<v>[0-9.]+) I=([0-9.]+) T=(?P<t>\d+)",
+            &fields,
+            &Patterns::new(),
+        ).unwrap();
+
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].value.as_f64(), Some(3.31));
+        assert_eq!(result[0].unit.as_deref(), Some("V"));
+        assert_eq!(result[1].value.as_f64(), Some(0.52));
+        assert!(matches!(result[2].value, Variable::Int(25)));
+
+        let missing = vec![FieldMapping { source: "x".into(), save_to: "x".into(), value_type: ValueType::Auto, unit: None }];
+        assert!(extract_regex_fields("V=3.31 I=0.52 T=25", r"V=(?P<v>[0-9.]+)", &missing, &Patterns::new()).is_err());
+    }
+
     #[test]
     fn test_extract_regex_no_match() {
-        let result = extract_regex("no match", r"VOLT:\s*([0-9.]+)", 1);
+        let result = extract_regex("no match", r"VOLT:\s*([0-9.]+)", 1, &Patterns::new());
         assert!(result.is_err());
     }
 }
@@ -0,0 +1,196 @@
+//! SCPI / IEEE 488.2 响应解析器
+//!
+//! 支持逗号分隔列表、引号字符串与定长二进制块（#<n><长度><数据>）。
+
+use crate::error::{EngineError, Result};
+use crate::model::{BinaryType, Endian, ValueType, Variable};
+use crate::parser::binary::decode_number;
+use crate::parser::convert_value;
+
+/// 解析逗号分隔列表
+///
+/// 指定 index 时按 value_type 返回该元素，否则整个列表转为浮点数组
+pub fn parse_list(text: &str, index: Option<usize>, value_type: ValueType) -> Result<Variable> {
+    let items = split_list(text.trim());
+
+    match index {
+        Some(i) => {
+            let item = items.get(i).ok_or_else(|| EngineError::ParseError(format!(
+                "列表索引 {} 超出范围（共 {} 项）", i, items.len()
+            )))?;
+            convert_value(&unquote(item), value_type)
+        }
+        None => items
+            .iter()
+            .map(|item| {
+                item.parse::<f64>().map_err(|_| EngineError::ParseError(format!(
+                    "列表元素 '{}' 不是数值", item
+                )))
+            })
+            .collect::<Result<Vec<f64>>>()
+            .map(Variable::FloatArray),
+    }
+}
+
+/// 解析引号字符串（"..." 或 '...'，内部重复的引号视为转义）
+pub fn parse_string(text: &str) -> Result<Variable> {
+    Ok(Variable::String(unquote(text.trim())))
+}
+
+/// 解析 IEEE 488.2 二进制块为浮点数组
+///
+/// 定长块 `#<n><长度><数据>`；`#0` 为不定长块，数据取到末尾（去除结尾的一个 \n 终止符）
+pub fn parse_block(data: &[u8], format: BinaryType, endian: Endian) -> Result<Variable> {
+    let payload = block_payload(data)?;
+
+    let size = format.size();
+    if size == 0 {
+        return Err(EngineError::ParseError("二进制块格式不能为 bytes".to_string()));
+    }
+    if payload.len() % size != 0 {
+        return Err(EngineError::ParseError(format!(
+            "二进制块长度 {} 不是元素大小 {} 的整数倍", payload.len(), size
+        )));
+    }
+
+    let values = payload
+        .chunks(size)
+        .filter_map(|chunk| decode_number(chunk, format, endian))
+        .collect();
+    Ok(Variable::FloatArray(values))
+}
+
+/// 提取二进制块的数据部分
+fn block_payload(data: &[u8]) -> Result<&[u8]> {
+    let start = data
+        .iter()
+        .position(|b| *b == b'#')
+        .ok_or_else(|| EngineError::ParseError("未找到二进制块头 '#'".to_string()))?;
+
+    let digits = data
+        .get(start + 1)
+        .filter(|b| b.is_ascii_digit())
+        .map(|b| (b - b'0') as usize)
+        .ok_or_else(|| EngineError::ParseError("二进制块头格式错误".to_string()))?;
+
+    if digits == 0 {
+        // 不定长块以单个 \n 结束，只去除这一个终止符，数据本身可能以 0x0A/0x0D 结尾
+        let payload = &data[start + 2..];
+        return Ok(payload.strip_suffix(b"\n").unwrap_or(payload));
+    }
+
+    let len_field = data
+        .get(start + 2..start + 2 + digits)
+        .ok_or_else(|| EngineError::ParseError("二进制块头长度不足".to_string()))?;
+    let len: usize = std::str::from_utf8(len_field)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| EngineError::ParseError("二进制块长度字段无效".to_string()))?;
+
+    let begin = start + 2 + digits;
+    data.get(begin..begin + len).ok_or_else(|| EngineError::ParseError(format!(
+        "二进制块数据不足: 期望 {} 字节，实际 {} 字节", len, data.len().saturating_sub(begin)
+    )))
+}
+
+/// 按逗号拆分列表（忽略引号内的逗号）
+fn split_list(text: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+
+    for c in text.chars() {
+        match (c, quote) {
+            ('"' | '\'', None) => {
+                quote = Some(c);
+                current.push(c);
+            }
+            (c, Some(q)) if c == q => {
+                quote = None;
+                current.push(c);
+            }
+            (',', None) => items.push(std::mem::take(&mut current).trim().to_string()),
+            _ => current.push(c),
+        }
+    }
+    items.push(current.trim().to_string());
+    items
+}
+
+/// 去除首尾引号并还原重复引号
+fn unquote(text: &str) -> String {
+    for q in ['"', '\''] {
+        if text.len() >= 2 && text.starts_with(q) && text.ends_with(q) {
+            let doubled = format!("{}{}", q, q);
+            return text[1..text.len() - 1].replace(&doubled, &q.to_string());
+        }
+    }
+    text.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_list() {
+        let all = parse_list("+1.5E+00,-2.0,3\n", None, ValueType::Auto).unwrap();
+        assert!(matches!(all, Variable::FloatArray(ref v) if v == &vec![1.5, -2.0, 3.0]));
+
+        let item = parse_list("1.5,\"OK, done\",3", Some(2), ValueType::Auto).unwrap();
+        assert_eq!(item.as_f64(), Some(3.0));
+
+        let quoted = parse_list("1.5,\"OK, done\",3", Some(1), ValueType::Auto).unwrap();
+        assert!(matches!(quoted, Variable::String(ref s) if s == "OK, done"));
+
+        let flag = parse_list("1.5,OFF", Some(1), ValueType::Bool).unwrap();
+        assert!(matches!(flag, Variable::Bool(false)));
+
+        assert!(parse_list("1,2", Some(5), ValueType::Auto).is_err());
+        assert!(parse_list("1,ABC", None, ValueType::Auto).is_err());
+        assert!(parse_list("1,ABC", Some(1), ValueType::Int).is_err());
+    }
+
+    #[test]
+    fn test_parse_string() {
+        let v = parse_string("\"Keysight \"\"34465A\"\"\"\n").unwrap();
+        assert!(matches!(v, Variable::String(ref s) if s == r#"Keysight "34465A""#));
+    }
+
+    #[test]
+    fn test_parse_definite_block() {
+        let mut data = b"#18".to_vec();
+        data.extend_from_slice(&1.0f32.to_be_bytes());
+        data.extend_from_slice(&(-0.5f32).to_be_bytes());
+        data.push(b'\n');
+
+        let v = parse_block(&data, BinaryType::F32, Endian::Big).unwrap();
+        assert!(matches!(v, Variable::FloatArray(ref w) if w == &vec![1.0, -0.5]));
+    }
+
+    #[test]
+    fn test_parse_block_int16_little_endian() {
+        let mut data = b"#204".to_vec();
+        data.extend_from_slice(&(-2i16).to_le_bytes());
+        data.extend_from_slice(&300i16.to_le_bytes());
+
+        let v = parse_block(&data, BinaryType::I16, Endian::Little).unwrap();
+        assert!(matches!(v, Variable::FloatArray(ref w) if w == &vec![-2.0, 300.0]));
+    }
+
+    #[test]
+    fn test_parse_indefinite_and_invalid_block() {
+        let v = parse_block(b"#0\x01\x02\n", BinaryType::U8, Endian::Big).unwrap();
+        assert!(matches!(v, Variable::FloatArray(ref w) if w == &vec![1.0, 2.0]));
+
+        // 数据末字节为 0x0A / 0x0D 时只去除一个终止符
+        let v = parse_block(b"#0\x01\x0a\n", BinaryType::U8, Endian::Big).unwrap();
+        assert!(matches!(v, Variable::FloatArray(ref w) if w == &vec![1.0, 10.0]));
+        let v = parse_block(b"#0\x01\x0d\n", BinaryType::U8, Endian::Big).unwrap();
+        assert!(matches!(v, Variable::FloatArray(ref w) if w == &vec![1.0, 13.0]));
+
+        assert!(parse_block(b"#19\x00\x01", BinaryType::U8, Endian::Big).is_err());
+        assert!(parse_block(b"#13\x00\x01\x02", BinaryType::I16, Endian::Big).is_err());
+        assert!(parse_block(b"no header", BinaryType::U8, Endian::Big).is_err());
+    }
+}
@@ -0,0 +1,5 @@
+//! 设备协议模块
+//!
+//! 引擎侧的协议帧构建与响应解码，插件只负责透明收发。
+
+pub mod modbus;
@@ -0,0 +1,314 @@
+//! Modbus RTU/TCP 帧构建与响应解码
+
+use std::sync::atomic::{AtomicU16, Ordering};
+
+use crate::error::{EngineError, Result};
+use crate::model::{ModbusFunction, ModbusRequest, ModbusTransport};
+
+/// TCP 事务标识计数器
+static TRANSACTION_ID: AtomicU16 = AtomicU16::new(1);
+
+/// 生成下一个 TCP 事务标识
+pub fn next_transaction_id() -> u16 {
+    TRANSACTION_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// CRC-16/MODBUS（多项式 0xA001，初值 0xFFFF）
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for byte in data {
+        crc ^= *byte as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xA001 } else { crc >> 1 };
+        }
+    }
+    crc
+}
+
+/// 构建请求 PDU（功能码 + 数据）
+pub fn build_pdu(request: &ModbusRequest) -> Result<Vec<u8>> {
+    let mut pdu = vec![request.function.code()];
+    pdu.extend_from_slice(&request.address.to_be_bytes());
+
+    match request.function {
+        ModbusFunction::ReadCoils | ModbusFunction::ReadDiscreteInputs => {
+            check_count(request.count, 2000)?;
+            pdu.extend_from_slice(&request.count.to_be_bytes());
+        }
+        ModbusFunction::ReadHoldingRegisters | ModbusFunction::ReadInputRegisters => {
+            check_count(request.count, 125)?;
+            pdu.extend_from_slice(&request.count.to_be_bytes());
+        }
+        ModbusFunction::WriteSingleCoil => {
+            let on = first_value(request)? != 0;
+            pdu.extend_from_slice(if on { &[0xFF, 0x00] } else { &[0x00, 0x00] });
+        }
+        ModbusFunction::WriteSingleRegister => {
+            pdu.extend_from_slice(&first_value(request)?.to_be_bytes());
+        }
+        ModbusFunction::WriteMultipleCoils => {
+            let count = request.values.len() as u16;
+            check_count(count, 1968)?;
+            let mut packed = vec![0u8; request.values.len().div_ceil(8)];
+            for (i, v) in request.values.iter().enumerate() {
+                if *v != 0 {
+                    packed[i / 8] |= 1 << (i % 8);
+                }
+            }
+            pdu.extend_from_slice(&count.to_be_bytes());
+            pdu.push(packed.len() as u8);
+            pdu.extend_from_slice(&packed);
+        }
+        ModbusFunction::WriteMultipleRegisters => {
+            let count = request.values.len() as u16;
+            check_count(count, 123)?;
+            pdu.extend_from_slice(&count.to_be_bytes());
+            pdu.push((count * 2) as u8);
+            for v in &request.values {
+                pdu.extend_from_slice(&v.to_be_bytes());
+            }
+        }
+    }
+
+    Ok(pdu)
+}
+
+/// 构建完整请求帧
+///
+/// RTU: 地址 + PDU + CRC（低字节在前）；TCP: MBAP 头 + PDU
+pub fn build_frame(request: &ModbusRequest, transaction_id: u16) -> Result<Vec<u8>> {
+    let pdu = build_pdu(request)?;
+
+    let frame = match request.transport {
+        ModbusTransport::Rtu => {
+            let mut frame = vec![request.unit_id];
+            frame.extend_from_slice(&pdu);
+            let crc = crc16(&frame);
+            frame.extend_from_slice(&crc.to_le_bytes());
+            frame
+        }
+        ModbusTransport::Tcp => {
+            let mut frame = Vec::with_capacity(7 + pdu.len());
+            frame.extend_from_slice(&transaction_id.to_be_bytes());
+            frame.extend_from_slice(&[0x00, 0x00]);
+            frame.extend_from_slice(&((pdu.len() + 1) as u16).to_be_bytes());
+            frame.push(request.unit_id);
+            frame.extend_from_slice(&pdu);
+            frame
+        }
+    };
+
+    Ok(frame)
+}
+
+/// 解码后的响应
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModbusResponse {
+    pub unit_id: u8,
+    pub function: u8,
+    /// 数据区：读响应为字节计数之后的数据，写响应为回显的地址与值
+    pub data: Vec<u8>,
+}
+
+/// 解码响应帧：校验 CRC / MBAP 头、单元标识与异常码
+pub fn decode_response(frame: &[u8], transport: ModbusTransport, expected_unit: Option<u8>) -> Result<ModbusResponse> {
+    let (unit_id, pdu) = match transport {
+        ModbusTransport::Rtu => {
+            if frame.len() < 4 {
+                return Err(modbus_error(format!("RTU 帧过短: {} 字节", frame.len())));
+            }
+            let (body, crc_bytes) = frame.split_at(frame.len() - 2);
+            let expected = crc16(body);
+            let actual = u16::from_le_bytes([crc_bytes[0], crc_bytes[1]]);
+            if expected != actual {
+                return Err(modbus_error(format!("CRC 校验失败: 期望 {:04X}, 实际 {:04X}", expected, actual)));
+            }
+            (body[0], &body[1..])
+        }
+        ModbusTransport::Tcp => {
+            if frame.len() < 8 {
+                return Err(modbus_error(format!("TCP 帧过短: {} 字节", frame.len())));
+            }
+            if frame[2..4] != [0x00, 0x00] {
+                return Err(modbus_error("MBAP 协议标识不为 0".to_string()));
+            }
+            let length = u16::from_be_bytes([frame[4], frame[5]]) as usize;
+            if length != frame.len() - 6 {
+                return Err(modbus_error(format!("MBAP 长度不符: 头部 {}, 实际 {}", length, frame.len() - 6)));
+            }
+            (frame[6], &frame[7..])
+        }
+    };
+
+    if let Some(expected) = expected_unit {
+        if unit_id != expected {
+            return Err(modbus_error(format!("从站地址不符: 期望 {}, 实际 {}", expected, unit_id)));
+        }
+    }
+
+    let function = pdu[0];
+    if function & 0x80 != 0 {
+        let code = pdu.get(1).copied().unwrap_or(0);
+        return Err(modbus_error(format!(
+            "异常响应: 功能码 0x{:02X}, 异常码 0x{:02X} ({})", function & 0x7F, code, exception_name(code)
+        )));
+    }
+
+    let data = match function {
+        0x01..=0x04 => {
+            let count = *pdu.get(1).ok_or_else(|| modbus_error("缺少字节计数".to_string()))? as usize;
+            pdu.get(2..2 + count)
+                .ok_or_else(|| modbus_error(format!("数据不足: 字节计数 {}, 实际 {}", count, pdu.len().saturating_sub(2))))?
+                .to_vec()
+        }
+        _ => pdu[1..].to_vec(),
+    };
+
+    Ok(ModbusResponse { unit_id, function, data })
+}
+
+/// 校验 TCP 响应的事务标识与请求帧一致，拒绝迟到的旧响应
+pub fn check_transaction(request: &[u8], response: &[u8]) -> Result<()> {
+    let (Some(expected), Some(actual)) = (request.get(..2), response.get(..2)) else {
+        return Err(modbus_error(format!("TCP 帧过短: {} 字节", response.len())));
+    };
+    if expected != actual {
+        return Err(modbus_error(format!(
+            "事务标识不符: 期望 {}, 实际 {}",
+            u16::from_be_bytes([expected[0], expected[1]]),
+            u16::from_be_bytes([actual[0], actual[1]])
+        )));
+    }
+    Ok(())
+}
+
+/// 异常码说明
+fn exception_name(code: u8) -> &'static str {
+    match code {
+        0x01 => "非法功能",
+        0x02 => "非法数据地址",
+        0x03 => "非法数据值",
+        0x04 => "从站设备故障",
+        0x05 => "确认",
+        0x06 => "从站设备忙",
+        0x08 => "存储奇偶性差错",
+        0x0A => "网关路径不可用",
+        0x0B => "网关目标设备响应失败",
+        _ => "未知异常",
+    }
+}
+
+fn check_count(count: u16, max: u16) -> Result<()> {
+    if count == 0 || count > max {
+        return Err(request_error(format!("数量 {} 超出范围 1..={}", count, max)));
+    }
+    Ok(())
+}
+
+fn first_value(request: &ModbusRequest) -> Result<u16> {
+    request.values.first().copied().ok_or_else(|| request_error("写请求缺少 values".to_string()))
+}
+
+/// 请求构建错误
+fn request_error(msg: String) -> EngineError {
+    EngineError::ExecutionError(format!("Modbus 请求无效: {}", msg))
+}
+
+/// 响应解码错误
+fn modbus_error(msg: String) -> EngineError {
+    EngineError::ParseError(format!("Modbus {}", msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(function: ModbusFunction, transport: ModbusTransport) -> ModbusRequest {
+        ModbusRequest { transport, unit_id: 1, function, address: 0, count: 2, values: vec![] }
+    }
+
+    #[test]
+    fn test_crc16() {
+        // 01 03 00 00 00 02 -> CRC C40B（低字节在前：C4 0B）
+        assert_eq!(crc16(&[0x01, 0x03, 0x00, 0x00, 0x00, 0x02]), 0x0BC4);
+    }
+
+    #[test]
+    fn test_build_rtu_and_tcp_frames() {
+        let rtu = build_frame(&request(ModbusFunction::ReadHoldingRegisters, ModbusTransport::Rtu), 0).unwrap();
+        assert_eq!(rtu, vec![0x01, 0x03, 0x00, 0x00, 0x00, 0x02, 0xC4, 0x0B]);
+
+        let tcp = build_frame(&request(ModbusFunction::ReadHoldingRegisters, ModbusTransport::Tcp), 0x0102).unwrap();
+        assert_eq!(tcp, vec![0x01, 0x02, 0x00, 0x00, 0x00, 0x06, 0x01, 0x03, 0x00, 0x00, 0x00, 0x02]);
+    }
+
+    #[test]
+    fn test_build_write_requests() {
+        let mut req = request(ModbusFunction::WriteMultipleRegisters, ModbusTransport::Rtu);
+        req.values = vec![0x000A, 0x0102];
+        let pdu = build_pdu(&req).unwrap();
+        assert_eq!(pdu, vec![0x10, 0x00, 0x00, 0x00, 0x02, 0x04, 0x00, 0x0A, 0x01, 0x02]);
+
+        req.function = ModbusFunction::WriteMultipleCoils;
+        req.values = vec![1, 0, 1, 1, 0, 0, 0, 0, 1];
+        let pdu = build_pdu(&req).unwrap();
+        assert_eq!(pdu, vec![0x0F, 0x00, 0x00, 0x00, 0x09, 0x02, 0x0D, 0x01]);
+
+        req.function = ModbusFunction::WriteSingleCoil;
+        req.values = vec![1];
+        assert_eq!(build_pdu(&req).unwrap(), vec![0x05, 0x00, 0x00, 0xFF, 0x00]);
+
+        req.values.clear();
+        assert!(build_pdu(&req).is_err());
+
+        let mut read = request(ModbusFunction::ReadHoldingRegisters, ModbusTransport::Rtu);
+        read.count = 0;
+        assert!(build_pdu(&read).is_err());
+    }
+
+    #[test]
+    fn test_decode_rtu_response() {
+        let mut frame = vec![0x01, 0x03, 0x04, 0x00, 0x0A, 0x01, 0x02];
+        let crc = crc16(&frame);
+        frame.extend_from_slice(&crc.to_le_bytes());
+
+        let resp = decode_response(&frame, ModbusTransport::Rtu, Some(1)).unwrap();
+        assert_eq!(resp.function, 0x03);
+        assert_eq!(resp.data, vec![0x00, 0x0A, 0x01, 0x02]);
+
+        assert!(decode_response(&frame, ModbusTransport::Rtu, Some(2)).is_err());
+
+        let last = frame.len() - 1;
+        frame[last] ^= 0xFF;
+        let err = decode_response(&frame, ModbusTransport::Rtu, None).unwrap_err();
+        assert!(err.to_string().contains("CRC"));
+    }
+
+    #[test]
+    fn test_decode_exception_and_tcp() {
+        let mut frame = vec![0x01, 0x83, 0x02];
+        let crc = crc16(&frame);
+        frame.extend_from_slice(&crc.to_le_bytes());
+        let err = decode_response(&frame, ModbusTransport::Rtu, None).unwrap_err();
+        assert!(err.to_string().contains("非法数据地址"));
+
+        let tcp = vec![0x00, 0x01, 0x00, 0x00, 0x00, 0x05, 0x01, 0x03, 0x02, 0x12, 0x34];
+        let resp = decode_response(&tcp, ModbusTransport::Tcp, None).unwrap();
+        assert_eq!(resp.data, vec![0x12, 0x34]);
+
+        let bad_len = vec![0x00, 0x01, 0x00, 0x00, 0x00, 0x09, 0x01, 0x03, 0x02, 0x12, 0x34];
+        assert!(decode_response(&bad_len, ModbusTransport::Tcp, None).is_err());
+    }
+
+    #[test]
+    fn test_check_transaction() {
+        let request = build_frame(&request(ModbusFunction::ReadHoldingRegisters, ModbusTransport::Tcp), 0x0102).unwrap();
+        let fresh = vec![0x01, 0x02, 0x00, 0x00, 0x00, 0x05, 0x01, 0x03, 0x02, 0x12, 0x34];
+        assert!(check_transaction(&request, &fresh).is_ok());
+
+        let stale = vec![0x01, 0x01, 0x00, 0x00, 0x00, 0x05, 0x01, 0x03, 0x02, 0x12, 0x34];
+        let err = check_transaction(&request, &stale).unwrap_err();
+        assert!(err.to_string().contains("事务标识不符: 期望 258, 实际 257"), "{}", err);
+        assert!(check_transaction(&request, &[0x01]).is_err());
+    }
+}
@@ -0,0 +1,224 @@
+//! 配置 schema 版本与迁移
+//!
+//! 持久化配置与方案包都带有 `schema_version` 字段。加载旧版本数据时，
+//! 按版本顺序执行迁移函数，并记录每一处改动，供 Host 展示给用户确认。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::error::{EngineError, Result};
+
+/// 当前 schema 版本
+///
+/// - 1: 初始格式（无 schema_version 字段）
+/// - 2: 增加命名方案（test_plans / plan_assignment）
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+/// 迁移报告
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MigrationReport {
+    /// 原始版本
+    pub from_version: u32,
+    /// 迁移后版本
+    pub to_version: u32,
+    /// 改动说明
+    pub changes: Vec<String>,
+}
+
+impl MigrationReport {
+    /// 是否发生了迁移
+    pub fn migrated(&self) -> bool {
+        self.from_version != self.to_version || !self.changes.is_empty()
+    }
+}
+
+/// 单个迁移：将文档从 `from` 版本升级到 `from + 1`
+struct Migration {
+    from: u32,
+    apply: fn(&mut Map<String, Value>, &mut Vec<String>),
+}
+
+/// 迁移列表（按版本升序）
+const MIGRATIONS: &[Migration] = &[
+    Migration { from: 1, apply: migrate_v1_to_v2 },
+];
+
+/// 迁移配置或方案包文档到当前版本
+pub fn migrate(doc: &mut Value) -> Result<MigrationReport> {
+    let obj = doc
+        .as_object_mut()
+        .ok_or_else(|| EngineError::SchemaError("文档根节点必须是对象".to_string()))?;
+
+    let from_version = match obj.get("schema_version") {
+        None => 1,
+        Some(v) => v
+            .as_u64()
+            .map(|v| v as u32)
+            .ok_or_else(|| EngineError::SchemaError(format!("无效的 schema_version: {}", v)))?,
+    };
+
+    if from_version > CURRENT_SCHEMA_VERSION {
+        return Err(EngineError::SchemaError(format!(
+            "schema_version {} 高于引擎支持的版本 {}",
+            from_version, CURRENT_SCHEMA_VERSION
+        )));
+    }
+
+    let mut changes = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.from >= from_version) {
+        (migration.apply)(obj, &mut changes);
+    }
+    obj.insert("schema_version".to_string(), Value::from(CURRENT_SCHEMA_VERSION));
+
+    Ok(MigrationReport {
+        from_version,
+        to_version: CURRENT_SCHEMA_VERSION,
+        changes,
+    })
+}
+
+/// 遍历文档中的所有步骤（配置的默认步骤、命名方案、方案包中的方案）
+fn for_each_step(obj: &mut Map<String, Value>, mut f: impl FnMut(&str, &mut Map<String, Value>)) {
+    let mut visit = |location: &str, steps: Option<&mut Value>| {
+        if let Some(Value::Array(steps)) = steps {
+            for step in steps.iter_mut().filter_map(Value::as_object_mut) {
+                f(location, step);
+            }
+        }
+    };
+
+    visit("test_steps", obj.get_mut("test_steps"));
+
+    if let Some(Value::Object(plans)) = obj.get_mut("test_plans") {
+        for (name, plan) in plans.iter_mut() {
+            visit(&format!("test_plans.{}", name), plan.get_mut("steps"));
+        }
+    }
+
+    if let Some(plan) = obj.get_mut("plan") {
+        visit("plan", plan.get_mut("steps"));
+    }
+}
+
+/// v1 -> v2
+/// - 旧字段 device_types.*.devices 重命名为 instances
+/// - 有 check_rule 但缺少 check_type 的步骤补齐为 builtin（v1 中这类检查会被静默忽略）
+/// - 配置文档补齐 test_plans / plan_assignment
+fn migrate_v1_to_v2(obj: &mut Map<String, Value>, changes: &mut Vec<String>) {
+    // device_types 可能是 {类型名: 设备类型} 对象，也可能是带 id 的数组（配置导出格式）
+    let types: Vec<(String, &mut Value)> = match obj.get_mut("device_types") {
+        Some(Value::Object(types)) => types.iter_mut().map(|(name, dt)| (name.clone(), dt)).collect(),
+        Some(Value::Array(types)) => types
+            .iter_mut()
+            .enumerate()
+            .map(|(i, dt)| {
+                let name = ["id", "type_name"]
+                    .iter()
+                    .find_map(|key| dt.get(key).and_then(Value::as_str))
+                    .map(str::to_string)
+                    .unwrap_or_else(|| i.to_string());
+                (name, dt)
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    for (name, device_type) in types {
+        if let Some(dt) = device_type.as_object_mut() {
+            if !dt.contains_key("instances") {
+                if let Some(devices) = dt.remove("devices") {
+                    dt.insert("instances".to_string(), devices);
+                    changes.push(format!("device_types.{}: 字段 devices 重命名为 instances", name));
+                }
+            }
+        }
+    }
+
+    for_each_step(obj, |location, step| {
+        // v1 未声明 check_type 的步骤从不执行检查，保持 none，避免迁移改变判定结果
+        if step.contains_key("check_rule") && !step.contains_key("check_type") {
+            step.insert("check_type".to_string(), Value::from("none"));
+            changes.push(format!(
+                "{}: 步骤 {} 有 check_rule 但未声明 check_type，保持不检查；如需启用请设为 builtin",
+                location,
+                step.get("step_id").map(|v| v.to_string()).unwrap_or_default()
+            ));
+        }
+    });
+
+    // 仅配置文档（非方案包）需要方案字段
+    if !obj.contains_key("plan") {
+        if !obj.contains_key("test_plans") {
+            obj.insert("test_plans".to_string(), Value::Object(Map::new()));
+            changes.push("新增空的 test_plans".to_string());
+        }
+        if !obj.contains_key("plan_assignment") {
+            obj.insert("plan_assignment".to_string(), serde_json::json!({}));
+            changes.push("新增空的 plan_assignment".to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_v1_config() {
+        let mut doc = serde_json::json!({
+            "slot_count": 2,
+            "device_types": {
+                "dut": {"name": "DUT", "devices": [{"id": "d1", "name": "A", "address": "COM3"}]}
+            },
+            "test_steps": [
+                {"step_id": 1, "step_name": "v", "execution_mode": "engine_controlled",
+                 "check_rule": {"template": "range_check", "min": 1.0, "max": 2.0}}
+            ]
+        });
+
+        let report = migrate(&mut doc).unwrap();
+        assert_eq!(report.from_version, 1);
+        assert_eq!(report.to_version, CURRENT_SCHEMA_VERSION);
+        assert!(report.migrated());
+        assert_eq!(doc["schema_version"], CURRENT_SCHEMA_VERSION);
+        assert!(doc["device_types"]["dut"].get("devices").is_none());
+        assert_eq!(doc["device_types"]["dut"]["instances"][0]["id"], "d1");
+        assert_eq!(doc["test_steps"][0]["check_type"], "none");
+        assert!(report.changes.iter().any(|c| c.contains("保持不检查")), "{:?}", report.changes);
+        assert!(doc["test_plans"].is_object());
+    }
+
+    #[test]
+    fn test_migrate_v1_device_type_array() {
+        let mut doc = serde_json::json!({
+            "device_types": [
+                {"id": "dut", "name": "DUT", "devices": [{"id": "d1", "name": "A", "address": "COM3"}]},
+                {"name": "PSU", "devices": []}
+            ]
+        });
+
+        let report = migrate(&mut doc).unwrap();
+        assert!(doc["device_types"][0].get("devices").is_none());
+        assert_eq!(doc["device_types"][0]["instances"][0]["id"], "d1");
+        assert!(doc["device_types"][1]["instances"].is_array());
+        assert!(report.changes.iter().any(|c| c.starts_with("device_types.dut:")));
+        assert!(report.changes.iter().any(|c| c.starts_with("device_types.1:")));
+    }
+
+    #[test]
+    fn test_migrate_current_is_noop() {
+        let mut doc = serde_json::json!({
+            "schema_version": CURRENT_SCHEMA_VERSION,
+            "test_steps": [],
+            "test_plans": {},
+            "plan_assignment": {}
+        });
+
+        let report = migrate(&mut doc).unwrap();
+        assert!(!report.migrated());
+    }
+
+    #[test]
+    fn test_migrate_newer_version_rejected() {
+        let mut doc = serde_json::json!({"schema_version": CURRENT_SCHEMA_VERSION + 1});
+        assert!(migrate(&mut doc).is_err());
+    }
+}
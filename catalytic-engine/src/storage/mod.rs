@@ -1,5 +1,6 @@
 //! 持久化存储模块
 
 pub mod redb_store;
+pub mod migration;
 
-pub use redb_store::Storage;
+pub use redb_store::{RunEntry, SpcKey, Storage};
@@ -1,10 +1,24 @@
 //! redb 数据库存储
 
-use redb::{Database, TableDefinition};
+use redb::{Database, Durability, ReadableTableMetadata, TableDefinition};
 use crate::error::{EngineError, Result};
 
 // 定义表
 const CONFIG_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("config");
+/// I/O 追踪表: (run_id, task_id) -> TraceRecord JSON
+const TRACE_TABLE: TableDefinition<(&str, u64), &[u8]> = TableDefinition::new("trace");
+/// 步骤判定表: (run_id, 序号) -> StepVerdict JSON
+const VERDICT_TABLE: TableDefinition<(&str, u64), &[u8]> = TableDefinition::new("trace_verdict");
+/// 运行上下文表: (run_id, 0) -> RunContext JSON
+const RUN_TABLE: TableDefinition<(&str, u64), &[u8]> = TableDefinition::new("trace_run");
+/// SPC 滚动样本表: (slot_id, step_id) -> 样本序列 JSON
+const SPC_TABLE: TableDefinition<SpcKey, &[u8]> = TableDefinition::new("spc");
+
+/// SPC 样本序列键 (slot_id, step_id)
+pub type SpcKey = (u32, u32);
+
+/// 按运行 ID 分组的记录 (run_id, 序号, JSON)
+pub type RunEntry = (String, u64, Vec<u8>);
 
 /// 存储接口
 pub struct Storage {
@@ -23,6 +37,14 @@ impl Storage {
         {
             let _ = write_txn.open_table(CONFIG_TABLE)
                 .map_err(|e| EngineError::StorageError(format!("创建表失败: {}", e)))?;
+            let _ = write_txn.open_table(TRACE_TABLE)
+                .map_err(|e| EngineError::StorageError(format!("创建表失败: {}", e)))?;
+            let _ = write_txn.open_table(VERDICT_TABLE)
+                .map_err(|e| EngineError::StorageError(format!("创建表失败: {}", e)))?;
+            let _ = write_txn.open_table(RUN_TABLE)
+                .map_err(|e| EngineError::StorageError(format!("创建表失败: {}", e)))?;
+            let _ = write_txn.open_table(SPC_TABLE)
+                .map_err(|e| EngineError::StorageError(format!("创建表失败: {}", e)))?;
         }
         write_txn.commit()
             .map_err(|e| EngineError::StorageError(format!("提交事务失败: {}", e)))?;
@@ -58,4 +80,137 @@ impl Storage {
             Err(e) => Err(EngineError::StorageError(format!("读取数据失败: {}", e))),
         }
     }
+
+    /// 批量保存追踪记录、步骤判定与运行上下文，并将各表分别裁剪到 retention 行以内
+    ///
+    /// run_id 以毫秒时间戳开头，键序即时间序，裁剪时从最早的运行开始删除
+    pub fn save_trace_batch(
+        &self,
+        traces: &[RunEntry],
+        verdicts: &[RunEntry],
+        runs: &[RunEntry],
+        retention: usize,
+    ) -> Result<()> {
+        let mut write_txn = self.db.begin_write()
+            .map_err(|e| EngineError::StorageError(format!("开始写事务失败: {}", e)))?;
+        // 追踪写入频繁，使用 Eventual 持久化级别以避免每批记录都同步刷盘
+        write_txn.set_durability(Durability::Eventual);
+        for (table_def, entries) in [(TRACE_TABLE, traces), (VERDICT_TABLE, verdicts), (RUN_TABLE, runs)] {
+            let mut table = write_txn.open_table(table_def)
+                .map_err(|e| EngineError::StorageError(format!("打开表失败: {}", e)))?;
+            for (run_id, seq, value) in entries {
+                table.insert((run_id.as_str(), *seq), value.as_slice())
+                    .map_err(|e| EngineError::StorageError(format!("插入数据失败: {}", e)))?;
+            }
+            let len = table.len()
+                .map_err(|e| EngineError::StorageError(format!("读取数据失败: {}", e)))?;
+            for _ in retention as u64..len {
+                table.pop_first()
+                    .map_err(|e| EngineError::StorageError(format!("删除数据失败: {}", e)))?;
+            }
+        }
+        write_txn.commit()
+            .map_err(|e| EngineError::StorageError(format!("提交事务失败: {}", e)))?;
+        Ok(())
+    }
+
+    /// 加载某次运行的所有追踪记录（按 task_id 升序）
+    pub fn load_traces(&self, run_id: &str) -> Result<Vec<Vec<u8>>> {
+        self.load_run_entries(TRACE_TABLE, run_id)
+    }
+
+    /// 加载某次运行的所有步骤判定（按执行顺序）
+    pub fn load_verdicts(&self, run_id: &str) -> Result<Vec<Vec<u8>>> {
+        self.load_run_entries(VERDICT_TABLE, run_id)
+    }
+
+    /// 加载某次运行的上下文
+    pub fn load_run_context(&self, run_id: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.load_run_entries(RUN_TABLE, run_id)?.into_iter().next())
+    }
+
+    /// 批量保存 SPC 样本序列
+    ///
+    /// 每次测量都会更新样本，使用 Eventual 持久化级别以避免每次写入都同步刷盘
+    pub fn save_spc_batch(&self, rows: &[(SpcKey, Vec<u8>)]) -> Result<()> {
+        let mut write_txn = self.db.begin_write()
+            .map_err(|e| EngineError::StorageError(format!("开始写事务失败: {}", e)))?;
+        write_txn.set_durability(Durability::Eventual);
+        {
+            let mut table = write_txn.open_table(SPC_TABLE)
+                .map_err(|e| EngineError::StorageError(format!("打开表失败: {}", e)))?;
+            for (key, value) in rows {
+                table.insert(*key, value.as_slice())
+                    .map_err(|e| EngineError::StorageError(format!("插入数据失败: {}", e)))?;
+            }
+        }
+        write_txn.commit()
+            .map_err(|e| EngineError::StorageError(format!("提交事务失败: {}", e)))?;
+        Ok(())
+    }
+
+    /// 加载全部 SPC 样本序列
+    pub fn load_spc(&self) -> Result<Vec<(SpcKey, Vec<u8>)>> {
+        let read_txn = self.db.begin_read()
+            .map_err(|e| EngineError::StorageError(format!("开始读事务失败: {}", e)))?;
+        let table = read_txn.open_table(SPC_TABLE)
+            .map_err(|e| EngineError::StorageError(format!("打开表失败: {}", e)))?;
+
+        let range = table.range::<SpcKey>(..)
+            .map_err(|e| EngineError::StorageError(format!("读取数据失败: {}", e)))?;
+
+        range
+            .map(|entry| {
+                entry
+                    .map(|(key, value)| (key.value(), value.value().to_vec()))
+                    .map_err(|e| EngineError::StorageError(format!("读取数据失败: {}", e)))
+            })
+            .collect()
+    }
+
+    /// 删除 SPC 样本序列（key 为 None 时清空全部）
+    pub fn remove_spc(&self, key: Option<SpcKey>) -> Result<()> {
+        let write_txn = self.db.begin_write()
+            .map_err(|e| EngineError::StorageError(format!("开始写事务失败: {}", e)))?;
+        {
+            let mut table = write_txn.open_table(SPC_TABLE)
+                .map_err(|e| EngineError::StorageError(format!("打开表失败: {}", e)))?;
+            match key {
+                Some(key) => {
+                    table.remove(key)
+                        .map_err(|e| EngineError::StorageError(format!("删除数据失败: {}", e)))?;
+                }
+                None => {
+                    table.retain(|_, _| false)
+                        .map_err(|e| EngineError::StorageError(format!("删除数据失败: {}", e)))?;
+                }
+            }
+        }
+        write_txn.commit()
+            .map_err(|e| EngineError::StorageError(format!("提交事务失败: {}", e)))?;
+        Ok(())
+    }
+
+    /// 读取某次运行的所有记录（按序号升序）
+    fn load_run_entries(
+        &self,
+        table_def: TableDefinition<(&str, u64), &[u8]>,
+        run_id: &str,
+    ) -> Result<Vec<Vec<u8>>> {
+        let read_txn = self.db.begin_read()
+            .map_err(|e| EngineError::StorageError(format!("开始读事务失败: {}", e)))?;
+        let table = read_txn.open_table(table_def)
+            .map_err(|e| EngineError::StorageError(format!("打开表失败: {}", e)))?;
+
+        let range = table.range((run_id, 0u64)..=(run_id, u64::MAX))
+            .map_err(|e| EngineError::StorageError(format!("读取数据失败: {}", e)))?;
+
+        range
+            .map(|entry| {
+                entry
+                    .map(|(_, value)| value.value().to_vec())
+                    .map_err(|e| EngineError::StorageError(format!("读取数据失败: {}", e)))
+            })
+            .collect()
+    }
 }
@@ -15,6 +15,8 @@ pub struct TestReport {
     pub overall_status: String,
     pub total_steps: u32,
     pub passed: u32,
+    /// 临界通过的步骤数（同时计入 passed）
+    pub marginal: u32,
     pub failed: u32,
     pub skipped: u32,
     pub elapsed_ms: u64,
@@ -34,6 +36,7 @@ impl TestReport {
     ) -> Self {
         let total_steps = steps.len() as u32;
         let passed = steps.iter().filter(|s| s.status == crate::model::StepStatus::Passed).count() as u32;
+        let marginal = steps.iter().filter(|s| s.marginal).count() as u32;
         let failed = steps.iter().filter(|s| s.status == crate::model::StepStatus::Failed).count() as u32;
         let skipped = steps.iter().filter(|s| s.status == crate::model::StepStatus::Skipped).count() as u32;
 
@@ -51,6 +54,7 @@ impl TestReport {
             overall_status,
             total_steps,
             passed,
+            marginal,
             failed,
             skipped,
             elapsed_ms: end_time.saturating_sub(start_time),
@@ -38,6 +38,12 @@ pub struct SlotSnapshot {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub current_step: Option<CurrentStepInfo>,
     pub variables: HashMap<String, VariableDisplay>,
+    /// 临界通过的步骤 ID（UI 高亮）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub marginal_steps: Vec<u32>,
+    /// 触发 SPC 判异的步骤 ID（过程失控预警）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub spc_alert_steps: Vec<u32>,
 }
 
 /// 进度信息
@@ -155,10 +155,11 @@ fn test_e2e_full_test_execution() {
         check_type: CheckType::Builtin,
         check_rule: Some(CheckRule::RangeCheck {
             variable: Some("voltage".into()),
-            min: 3.0,
-            max: 3.5,
+            min: 3.0.into(),
+            max: 3.5.into(),
             include_min: true,
             include_max: true,
+            unit: None,
         }),
         ..Default::default()
     };
@@ -0,0 +1,209 @@
+//! 配置事务相关集成测试
+//! 包括：事务提交/放弃、提交时校验与回滚、load_config 原子加载
+
+use std::ffi::CString;
+
+use catalytic::core::engine::CatEngine;
+use catalytic::error::{SUCCESS, ERR_INVALID_PARAM, ERR_INVALID_STATE};
+use catalytic::ffi::{cat_engine_load_config, cat_engine_begin_transaction, cat_engine_commit_transaction};
+use catalytic::model::{TestStep, ExecutionMode, EngineTask, ActionType, DeviceType, TestPlan};
+
+// 辅助函数：创建指向指定设备的步骤
+fn device_step(step_id: u32, device: &str) -> TestStep {
+    TestStep {
+        step_id,
+        step_name: format!("Step_{}", step_id),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: device.into(),
+            action_type: ActionType::Query,
+            payload: b"CMD".to_vec(),
+            timeout_ms: 1000,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn mock_device_type() -> DeviceType {
+    DeviceType {
+        type_name: "MockDevice".into(),
+        name: "Mock Device".into(),
+        plugin_id: "mock.plugin".into(),
+        instances: vec![],
+        commands: vec![],
+    }
+}
+
+// ========== 测试：事务内修改只在提交时写入存储 ==========
+#[test]
+fn test_transaction_defers_storage_write() {
+    let dir = std::env::temp_dir().join(format!("catalytic_txn_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let path = dir.to_str().unwrap().to_string();
+
+    // 未提交的事务不落盘
+    {
+        let mut engine = CatEngine::new(1).unwrap();
+        engine.set_data_path(&path).unwrap();
+        engine.begin_transaction().unwrap();
+        engine.add_device_type("MockDevice".into(), mock_device_type()).unwrap();
+        engine.add_test_step(device_step(1, "MockDevice")).unwrap();
+        assert_eq!(engine.get_test_steps().len(), 1);
+    }
+    {
+        let mut engine = CatEngine::new(1).unwrap();
+        engine.set_data_path(&path).unwrap();
+        assert!(engine.get_test_steps().is_empty());
+
+        // 放弃事务恢复原配置
+        engine.begin_transaction().unwrap();
+        assert!(engine.begin_transaction().is_err());
+        engine.add_test_step(device_step(1, "MockDevice")).unwrap();
+        engine.abort_transaction().unwrap();
+        assert!(engine.get_test_steps().is_empty());
+        assert!(!engine.in_transaction());
+
+        engine.begin_transaction().unwrap();
+        engine.add_device_type("MockDevice".into(), mock_device_type()).unwrap();
+        engine.add_test_step(device_step(1, "MockDevice")).unwrap();
+        engine.add_test_step(device_step(2, "MockDevice")).unwrap();
+        engine.commit_transaction().unwrap();
+    }
+    {
+        let mut engine = CatEngine::new(1).unwrap();
+        engine.set_data_path(&path).unwrap();
+        assert_eq!(engine.get_test_steps().len(), 2);
+    }
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+// ========== 测试：提交校验失败自动回滚 ==========
+#[test]
+fn test_commit_validation_rolls_back() {
+    let mut engine = CatEngine::new(2).unwrap();
+    engine.add_device_type("MockDevice".into(), mock_device_type()).unwrap();
+    engine.add_test_step(device_step(1, "MockDevice")).unwrap();
+
+    engine.begin_transaction().unwrap();
+    engine.set_slot_count(4).unwrap();
+    engine.add_test_step(device_step(1, "MockDevice")).unwrap();
+    let mut jumping = device_step(2, "MissingDevice");
+    jumping.next_on_fail = Some(99);
+    engine.add_test_step(jumping).unwrap();
+
+    let err = engine.commit_transaction().unwrap_err().to_string();
+    assert!(err.contains("重复"), "{}", err);
+    assert!(err.contains("MissingDevice"), "{}", err);
+    assert!(err.contains("99"), "{}", err);
+
+    assert!(!engine.in_transaction());
+    assert_eq!(engine.get_test_steps().len(), 1);
+    assert_eq!(engine.slot_count(), 2);
+
+    // 回滚恢复原有槽位对象，而不是重建空槽位
+    engine.get_slot(1).unwrap().write().set_sn("SN-0001".into());
+    let original = engine.get_slot(1).unwrap();
+    engine.begin_transaction().unwrap();
+    engine.set_slot_count(1).unwrap();
+    engine.abort_transaction().unwrap();
+    let restored = engine.get_slot(1).unwrap();
+    assert!(std::sync::Arc::ptr_eq(&original, &restored));
+    assert_eq!(restored.read().sn.as_deref(), Some("SN-0001"));
+}
+
+// ========== 测试：load_config 原子加载 ==========
+#[test]
+fn test_load_config_is_atomic() {
+    let mut engine = CatEngine::new(1).unwrap();
+    let engine_ptr = &mut engine as *mut CatEngine;
+
+    // 方案分配引用不存在的方案：应用失败，整体回滚，配置不变
+    let invalid = CString::new(serde_json::json!({
+        "device_types": {"MockDevice": mock_device_type()},
+        "test_steps": [device_step(1, "MockDevice")],
+        "plan_assignment": {"slots": {"0": "missing"}},
+    }).to_string()).unwrap();
+    assert_eq!(unsafe { cat_engine_load_config(engine_ptr, invalid.as_ptr()) }, ERR_INVALID_PARAM);
+    assert!(engine.get_test_steps().is_empty());
+    assert!(engine.get_device_type("MockDevice").is_none());
+
+    // 一致性问题（跳转目标、设备类型不存在）不阻止旧接口加载，记为警告
+    let lenient = CString::new(serde_json::json!({
+        "test_steps": [device_step(1, "MissingDevice"), {
+            "step_id": 2, "step_name": "bad", "execution_mode": "engine_controlled", "next_on_pass": 7
+        }],
+    }).to_string()).unwrap();
+    assert_eq!(unsafe { cat_engine_load_config(engine_ptr, lenient.as_ptr()) }, SUCCESS);
+    assert_eq!(engine.get_test_steps().len(), 2);
+    let warnings = engine.config_warnings().join("; ");
+    assert!(warnings.contains("MissingDevice") && warnings.contains("跳转目标 7"), "{}", warnings);
+    for id in [1, 2] {
+        engine.remove_test_step(id).unwrap();
+    }
+
+    // 调用方已开启事务时并入该事务
+    let valid = CString::new(serde_json::json!({
+        "device_types": {"MockDevice": mock_device_type()},
+        "test_steps": [device_step(1, "MockDevice"), device_step(2, "MockDevice")],
+    }).to_string()).unwrap();
+    unsafe {
+        assert_eq!(cat_engine_begin_transaction(engine_ptr), SUCCESS);
+        assert_eq!(cat_engine_begin_transaction(engine_ptr), ERR_INVALID_STATE);
+        assert_eq!(cat_engine_load_config(engine_ptr, valid.as_ptr()), SUCCESS);
+        assert!((*engine_ptr).in_transaction());
+        assert_eq!(cat_engine_commit_transaction(engine_ptr), SUCCESS);
+    }
+    assert_eq!(engine.get_test_steps().len(), 2);
+}
+
+// ========== 测试：预编译方案的校验与缓存 ==========
+#[test]
+fn test_compiled_plan_validation_and_cache() {
+    use catalytic::model::{CheckRule, ParseRule};
+
+    let mut engine = CatEngine::new(2).unwrap();
+    engine.add_device_type("MockDevice".into(), mock_device_type()).unwrap();
+
+    // 无效正则在添加步骤或方案时即被拒绝
+    let mut bad = device_step(1, "MockDevice");
+    bad.engine_task.as_mut().unwrap().parse_rule = Some(ParseRule::Regex {
+        pattern: "([0-9".into(), group: 1, value_type: Default::default(),
+    });
+    let err = engine.add_test_step(bad.clone()).unwrap_err().to_string();
+    assert!(err.contains("正则编译失败"), "{}", err);
+    assert!(engine.get_test_steps().is_empty());
+    let err = engine.add_test_plan("bad".into(), TestPlan { steps: vec![bad.clone()], ..Default::default() }).unwrap_err();
+    assert!(err.to_string().contains("方案 bad"), "{}", err);
+    assert!(engine.get_test_plan("bad").is_none());
+
+    // 旧版宽松加载接口同样拒绝无法编译的配置，整体回滚
+    let engine_ptr = &mut engine as *mut CatEngine;
+    let invalid = CString::new(serde_json::json!({
+        "device_types": {"MockDevice": mock_device_type()},
+        "test_steps": [bad],
+    }).to_string()).unwrap();
+    assert_eq!(unsafe { cat_engine_load_config(engine_ptr, invalid.as_ptr()) }, ERR_INVALID_PARAM);
+    assert!(engine.get_test_steps().is_empty());
+
+    // 同一方案的所有槽位共享一次编译结果
+    let mut good = device_step(1, "MockDevice");
+    good.engine_task.as_mut().unwrap().parse_rule = Some(ParseRule::Regex {
+        pattern: r"([0-9.]+)".into(), group: 1, value_type: Default::default(),
+    });
+    good.check_rule = Some(CheckRule::Expression { expr: "v > 1".into() });
+    engine.add_test_step(good).unwrap();
+
+    let plan0 = engine.compiled_slot_plan(0).unwrap();
+    let plan1 = engine.compiled_slot_plan(1).unwrap();
+    assert!(std::sync::Arc::ptr_eq(&plan0, &plan1));
+    assert_eq!(plan0.patterns.len(), 1);
+    assert_eq!(plan0.expressions.len(), 1);
+
+    // 配置变更后缓存失效
+    engine.add_test_step(device_step(2, "MockDevice")).unwrap();
+    let plan2 = engine.compiled_slot_plan(0).unwrap();
+    assert!(!std::sync::Arc::ptr_eq(&plan0, &plan2));
+    assert_eq!(plan2.steps.len(), 2);
+}
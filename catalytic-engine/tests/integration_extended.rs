@@ -276,7 +276,8 @@ fn test_check_failure_step_status() {
         check_rule: Some(CheckRule::Threshold {
             variable: "val".into(),
             operator: CompareOp::Gt,
-            value: 100.0, // "SUCCESS" 无法解析为数值，应失败
+            value: 100.0.into(), // "SUCCESS" 无法解析为数值，应失败
+            unit: None,
         }),
         ..Default::default()
     };
@@ -428,7 +429,7 @@ fn test_next_on_fail_jump() {
             action_type: ActionType::Query,
             payload: b"CMD".to_vec(),
             timeout_ms: 1000,
-            parse_rule: Some(ParseRule::Regex { pattern: "(.+)".into(), group: 1 }), // 捕获整个响应
+            parse_rule: Some(ParseRule::Regex { pattern: "(.+)".into(), group: 1, value_type: Default::default() }), // 捕获整个响应
             ..Default::default()
         }),
         save_to: Some("result".into()),
@@ -712,3 +713,1622 @@ fn test_stop_slot_control() {
     assert!(executed < 10, "Stop should prevent all 10 steps from executing, actual: {}", executed);
     assert!(executed >= 1, "At least 1 step should have executed before stop");
 }
+
+// ========== 测试：I/O 追踪记录 ==========
+#[test]
+fn test_trace_attached_to_failed_step() {
+    use std::sync::Arc;
+    use catalytic::model::TraceOutcome;
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+
+    engine.register_engine_task_callback(mock_engine_task_error, registry_ptr);
+    engine.trace_recorder().set_enabled(true, 0);
+
+    let step = TestStep {
+        step_id: 1,
+        step_name: "Traced_Step".into(),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: "MockDevice".into(),
+            action_type: ActionType::Query,
+            payload: b"MEAS?".to_vec(),
+            timeout_ms: 1000,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    engine.add_test_step(step).unwrap();
+
+    use catalytic::core::executor;
+    executor::spawn_slot(&engine, 0).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+
+    let slot = engine.get_slot(0).unwrap();
+    let guard = slot.read();
+    let run_id = guard.run_id.clone().expect("run_id should be set");
+    let result = &guard.step_results[0];
+    assert_ne!(result.status, StepStatus::Passed);
+
+    // 失败步骤附带追踪
+    let trace = result.trace.as_ref().expect("failed step should carry trace");
+    assert_eq!(trace.len(), 1);
+    assert_eq!(trace[0].payload, b"MEAS?".to_vec());
+    assert_eq!(trace[0].address, "mock://test");
+    assert_eq!(trace[0].outcome, TraceOutcome::Error);
+    assert_eq!(trace[0].message.as_deref(), Some("Device unreachable"));
+
+    // 按运行 ID 查询
+    assert_eq!(engine.trace_recorder().get_run(&run_id).len(), 1);
+}
+
+// --- EngineTask Mock 回调 (返回电压读数) ---
+extern "C" fn mock_engine_task_voltage(
+    slot_id: u32,
+    task_id: u64,
+    _device: *const std::ffi::c_char,
+    _addr: *const std::ffi::c_char,
+    _proto: *const std::ffi::c_char,
+    _action: *const std::ffi::c_char,
+    _payload: *const u8,
+    _len: u32,
+    _timeout: u32,
+    user_data: *mut std::ffi::c_void,
+) -> i32 {
+    unsafe {
+        let registry = &*(user_data as *const TaskRegistry);
+        registry.submit(task_id, slot_id, TaskResult::Ok(b"VOLT 3.30".to_vec()));
+    }
+    0
+}
+
+// ========== 测试：修改限值后离线回放 ==========
+#[test]
+fn test_replay_with_modified_limits() {
+    use std::sync::Arc;
+    use catalytic::core::replay::replay_run;
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+
+    engine.register_engine_task_callback(mock_engine_task_voltage, registry_ptr);
+    engine.trace_recorder().set_enabled(true, 0);
+
+    let mut step = TestStep {
+        step_id: 1,
+        step_name: "Voltage_Step".into(),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: "MockDevice".into(),
+            action_type: ActionType::Query,
+            payload: b"MEAS:VOLT?".to_vec(),
+            timeout_ms: 1000,
+            parse_rule: Some(ParseRule::Number),
+            ..Default::default()
+        }),
+        save_to: Some("vout".into()),
+        check_type: CheckType::Builtin,
+        check_rule: Some(CheckRule::RangeCheck {
+            variable: Some("vout".into()),
+            min: 3.0.into(),
+            max: 3.6.into(),
+            include_min: true,
+            include_max: true,
+            unit: None,
+        }),
+        ..Default::default()
+    };
+    engine.add_test_step(step.clone()).unwrap();
+
+    use catalytic::core::executor;
+    executor::spawn_slot(&engine, 0).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+
+    let run_id = {
+        let slot = engine.get_slot(0).unwrap();
+        let guard = slot.read();
+        assert_eq!(guard.step_results[0].status, StepStatus::Passed);
+        guard.run_id.clone().unwrap()
+    };
+
+    // 不再调用设备：注销回调后收紧上限重新判定
+    engine.register_engine_task_callback(mock_engine_task_error, std::ptr::null_mut());
+    step.check_rule = Some(CheckRule::RangeCheck {
+        variable: Some("vout".into()),
+        min: 3.0.into(),
+        max: 3.2.into(),
+        include_min: true,
+        include_max: true,
+        unit: None,
+    });
+
+    let report = replay_run(&engine, &run_id, Some(vec![step])).unwrap();
+    assert_eq!(report.steps.len(), 1);
+    assert_eq!(report.changed, 1);
+    assert_eq!(report.steps[0].original_status, Some(StepStatus::Passed));
+    assert_eq!(report.steps[0].replayed_status, Some(StepStatus::Failed));
+
+    // 原方案回放应无差异
+    let unchanged = replay_run(&engine, &run_id, None).unwrap();
+    assert_eq!(unchanged.changed, 0);
+
+    assert!(replay_run(&engine, "missing-run", None).is_err());
+}
+
+// ========== 测试：槽位切换方案与限值后回放仍使用原运行的上下文 ==========
+#[test]
+fn test_replay_uses_recorded_plan_and_limits() {
+    use std::sync::Arc;
+    use catalytic::core::replay::replay_run;
+    use catalytic::model::{LimitConfig, LimitValue, TestPlan};
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+    engine.register_engine_task_callback(mock_engine_task_voltage, registry_ptr);
+    engine.trace_recorder().set_enabled(true, 0);
+
+    let limits = |csv: &str| {
+        let mut config = LimitConfig::default();
+        config.import_csv(csv).unwrap();
+        config
+    };
+    let plan = |max: LimitValue| TestPlan {
+        steps: vec![TestStep {
+            step_id: 1,
+            step_name: "Voltage_Step".into(),
+            execution_mode: ExecutionMode::EngineControlled,
+            engine_task: Some(EngineTask {
+                target_device: "MockDevice".into(),
+                action_type: ActionType::Query,
+                payload: b"MEAS:VOLT?".to_vec(),
+                timeout_ms: 1000,
+                parse_rule: Some(ParseRule::Number),
+                ..Default::default()
+            }),
+            save_to: Some("vout".into()),
+            check_type: CheckType::Builtin,
+            check_rule: Some(CheckRule::RangeCheck {
+                variable: Some("vout".into()),
+                min: 3.0.into(),
+                max,
+                include_min: true,
+                include_max: true,
+                unit: None,
+            }),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    engine.set_limit_config(limits("name,value,variant,slot\nvmax,3.6,,\n")).unwrap();
+    engine.add_test_plan("sku_a".into(), plan(LimitValue::Ref("@vmax".into()))).unwrap();
+    engine.set_slot_plan(0, Some("sku_a".into())).unwrap();
+    engine.get_slot(0).unwrap().write().set_sn("SN-A".into());
+
+    use catalytic::core::executor;
+    executor::spawn_slot(&engine, 0).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+    let run_id = engine.get_slot(0).unwrap().read().run_id.clone().unwrap();
+
+    let context = engine.trace_recorder().get_run_context(&run_id).unwrap();
+    assert_eq!(context.plan_name.as_deref(), Some("sku_a"));
+    assert_eq!(context.sn.as_deref(), Some("SN-A"));
+    assert_eq!(context.limits["vmax"], 3.6);
+
+    // 槽位切换到另一方案、限值收紧：按原运行的方案与限值回放，判定不变
+    engine.set_limit_config(limits("name,value,variant,slot\nvmax,3.2,,\n")).unwrap();
+    engine.add_test_plan("sku_b".into(), plan(1.0.into())).unwrap();
+    engine.set_slot_plan(0, Some("sku_b".into())).unwrap();
+    engine.get_slot(0).unwrap().write().set_sn("SN-B".into());
+
+    let report = replay_run(&engine, &run_id, None).unwrap();
+    assert_eq!(report.changed, 0, "{:?}", report.steps);
+    assert_eq!(report.steps[0].replayed_status, Some(StepStatus::Passed));
+}
+
+// ========== 测试：标称值百分比容差检查导出限值 ==========
+#[test]
+fn test_tolerance_percent_exports_limits() {
+    use std::sync::Arc;
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+    engine.register_engine_task_callback(mock_engine_task_voltage, registry_ptr);
+
+    // 3.30 V 落在 3.3 V ± 1% 内；在 3.4 V ± 2% 外
+    let step = |step_id: u32, nominal: f64, percent: f64| TestStep {
+        step_id,
+        step_name: format!("Vout_{}", step_id),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: "MockDevice".into(),
+            action_type: ActionType::Query,
+            payload: b"MEAS:VOLT?".to_vec(),
+            timeout_ms: 1000,
+            parse_rule: Some(ParseRule::Quantity { unit: "V".into() }),
+            ..Default::default()
+        }),
+        check_type: CheckType::Builtin,
+        check_rule: Some(CheckRule::TolerancePercent { variable: None, nominal: nominal.into(), percent: percent.into(), unit: Some("V".into()) }),
+        next_on_fail: Some(step_id + 1),
+        ..Default::default()
+    };
+    engine.add_test_step(step(1, 3.3, 1.0)).unwrap();
+    engine.add_test_step(step(2, 3.4, 2.0)).unwrap();
+
+    use catalytic::core::executor;
+    executor::spawn_slot(&engine, 0).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+
+    let slot = engine.get_slot(0).unwrap();
+    let guard = slot.read();
+    assert_eq!(guard.step_results[0].status, StepStatus::Passed);
+    assert_eq!(guard.step_results[1].status, StepStatus::Failed);
+
+    let params = &guard.step_results[1].check_result.as_ref().unwrap().params;
+    assert!((params["min"].as_f64().unwrap() - 3.332).abs() < 1e-9);
+    assert!((params["max"].as_f64().unwrap() - 3.468).abs() < 1e-9);
+}
+
+// ========== 测试：保护带内的读数判为临界通过 ==========
+#[test]
+fn test_guard_band_marks_marginal_pass() {
+    use std::sync::Arc;
+    use catalytic::model::GuardBand;
+    use catalytic::ui::report::TestReport;
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+    engine.register_engine_task_callback(mock_engine_task_voltage, registry_ptr);
+
+    // 10% 保护带：[3.0, 3.35] 带宽 0.035，3.30 距上限 0.05 → 正常；[3.0, 3.32] 带宽 0.032 → 临界
+    let step = |step_id: u32, max: f64| TestStep {
+        step_id,
+        step_name: format!("Vout_{}", step_id),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: "MockDevice".into(),
+            action_type: ActionType::Query,
+            payload: b"MEAS:VOLT?".to_vec(),
+            timeout_ms: 1000,
+            parse_rule: Some(ParseRule::Number),
+            ..Default::default()
+        }),
+        check_type: CheckType::Builtin,
+        check_rule: Some(CheckRule::RangeCheck {
+            variable: None,
+            min: 3.0.into(),
+            max: max.into(),
+            include_min: true,
+            include_max: true,
+            unit: None,
+        }),
+        guard_band: Some(GuardBand { percent: 10.0, absolute: 0.0 }),
+        ..Default::default()
+    };
+    engine.add_test_step(step(1, 3.35)).unwrap();
+    engine.add_test_step(step(2, 3.32)).unwrap();
+
+    use catalytic::core::executor;
+    executor::spawn_slot(&engine, 0).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+
+    let slot = engine.get_slot(0).unwrap();
+    let guard = slot.read();
+    let results = &guard.step_results;
+    assert_eq!((results[0].status, results[0].marginal), (StepStatus::Passed, false));
+    assert_eq!((results[1].status, results[1].marginal), (StepStatus::Passed, true));
+
+    let report = TestReport::from_results(0, None, HashMap::new(), results.clone(), 0, 0);
+    assert_eq!((report.passed, report.marginal, report.failed), (2, 1, 0));
+    assert_eq!(report.overall_status, "passed");
+}
+
+// ========== 测试：SPC 统计跨运行累计并在失控时告警 ==========
+#[test]
+fn test_spc_alerts_on_drift_within_limits() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use catalytic::model::SpcRule;
+
+    static READING: AtomicUsize = AtomicUsize::new(0);
+
+    // 12 次稳定读数（3.30 ± 0.01）后出现一次 3.45：仍在 [3.0, 3.6] 内，但超出 3σ
+    extern "C" fn mock_drifting(
+        slot_id: u32,
+        task_id: u64,
+        _device: *const std::ffi::c_char,
+        _addr: *const std::ffi::c_char,
+        _proto: *const std::ffi::c_char,
+        _action: *const std::ffi::c_char,
+        _payload: *const u8,
+        _len: u32,
+        _timeout: u32,
+        user_data: *mut std::ffi::c_void,
+    ) -> i32 {
+        let reading = match READING.fetch_add(1, Ordering::SeqCst) {
+            12 => "3.45",
+            n if n % 2 == 0 => "3.29",
+            _ => "3.31",
+        };
+        unsafe {
+            let registry = &*(user_data as *const TaskRegistry);
+            registry.submit(task_id, slot_id, TaskResult::Ok(reading.as_bytes().to_vec()));
+        }
+        0
+    }
+
+    let dir = std::env::temp_dir().join(format!("catalytic_spc_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let path = dir.to_str().unwrap().to_string();
+
+    {
+        let mut engine = CatEngine::new(1).unwrap();
+        engine.set_data_path(&path).unwrap();
+        engine.add_device_type("MockDevice".into(), DeviceType {
+            type_name: "MockDevice".into(),
+            name: "Mock Device".into(),
+            plugin_id: "mock.plugin".into(),
+            instances: vec![],
+            commands: vec![],
+        }).unwrap();
+        engine.add_device_instance("MockDevice", DeviceInstance {
+            id: "mock_inst".into(),
+            name: "MockInst".into(),
+            address: "mock://test".into(),
+            ..Default::default()
+        }).unwrap();
+        engine.set_slot_binding(0, HashMap::from([("MockDevice".into(), vec!["mock_inst".into()])])).unwrap();
+
+        let registry = engine.task_registry();
+        engine.register_engine_task_callback(mock_drifting, Arc::as_ptr(&registry) as *mut std::ffi::c_void);
+        engine.add_test_step(TestStep {
+            step_id: 1,
+            step_name: "Vout".into(),
+            execution_mode: ExecutionMode::EngineControlled,
+            engine_task: Some(EngineTask {
+                target_device: "MockDevice".into(),
+                action_type: ActionType::Query,
+                payload: b"MEAS:VOLT?".to_vec(),
+                timeout_ms: 1000,
+                parse_rule: Some(ParseRule::Number),
+                ..Default::default()
+            }),
+            check_type: CheckType::Builtin,
+            check_rule: Some(CheckRule::RangeCheck {
+                variable: None,
+                min: 3.0.into(),
+                max: 3.6.into(),
+                include_min: true,
+                include_max: true,
+                unit: None,
+            }),
+            ..Default::default()
+        }).unwrap();
+
+        use catalytic::core::executor;
+        READING.store(0, Ordering::SeqCst);
+        for _ in 0..12 {
+            executor::run_slot(&engine, 0).unwrap();
+            assert!(engine.get_slot(0).unwrap().read().step_results.last().unwrap().spc_violations.is_empty());
+        }
+
+        let stats = engine.spc_monitor().stats(0, 1).unwrap();
+        assert_eq!(stats.count, 12);
+        assert!((stats.mean - 3.30).abs() < 1e-9);
+        assert_eq!((stats.lsl, stats.usl), (Some(3.0), Some(3.6)));
+        assert!(stats.cpk.unwrap() > 9.0, "cpk = {:?}", stats.cpk);
+
+        executor::run_slot(&engine, 0).unwrap();
+        let slot = engine.get_slot(0).unwrap();
+        let result = slot.read().step_results.last().unwrap().clone();
+        assert_eq!(result.status, StepStatus::Passed);
+        assert_eq!(result.spc_violations, vec![SpcRule::Beyond3Sigma]);
+    }
+
+    // 重新打开数据目录后统计继续累计
+    {
+        let mut engine = CatEngine::new(1).unwrap();
+        engine.set_data_path(&path).unwrap();
+        let stats = engine.spc_monitor().stats(0, 1).unwrap();
+        assert_eq!((stats.count, stats.samples), (13, 13));
+        assert_eq!(stats.violations, vec![SpcRule::Beyond3Sigma]);
+
+        engine.spc_monitor().set_window(5);
+    }
+    // 窗口大小与裁剪后的序列同样持久化
+    {
+        let mut engine = CatEngine::new(1).unwrap();
+        engine.set_data_path(&path).unwrap();
+        assert_eq!(engine.spc_monitor().window(), 5);
+        let stats = engine.spc_monitor().stats(0, 1).unwrap();
+        assert_eq!((stats.count, stats.samples), (13, 5));
+
+        engine.spc_monitor().reset(Some((0, 1)));
+    }
+    {
+        let mut engine = CatEngine::new(1).unwrap();
+        engine.set_data_path(&path).unwrap();
+        assert!(engine.spc_monitor().all_stats().is_empty());
+    }
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+// ========== 测试：计算步骤由表达式派生变量 ==========
+#[test]
+fn test_calculation_step_derives_variables() {
+    use std::sync::Arc;
+    use catalytic::model::{Calculation, Variable};
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+    engine.register_engine_task_callback(mock_engine_task_voltage, registry_ptr);
+
+    engine.add_test_step(TestStep {
+        step_id: 1,
+        step_name: "Vout".into(),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: "MockDevice".into(),
+            action_type: ActionType::Query,
+            payload: b"MEAS:VOLT?".to_vec(),
+            timeout_ms: 1000,
+            parse_rule: Some(ParseRule::Number),
+            ..Default::default()
+        }),
+        save_to: Some("vout".into()),
+        ..Default::default()
+    }).unwrap();
+
+    let calc = |target: &str, expr: &str, unit: Option<&str>| Calculation {
+        target: target.into(),
+        expr: expr.into(),
+        unit: unit.map(Into::into),
+    };
+    // 第二项引用第一项的结果
+    engine.add_test_step(TestStep {
+        step_id: 2,
+        step_name: "Power".into(),
+        execution_mode: ExecutionMode::Calculation,
+        calculations: vec![
+            calc("vcorr", "vout - 0.05", Some("V")),
+            calc("power", "vcorr * 0.5", Some("W")),
+        ],
+        check_type: CheckType::Builtin,
+        check_rule: Some(CheckRule::RangeCheck {
+            variable: None,
+            min: 1.6.into(),
+            max: 1.7.into(),
+            include_min: true,
+            include_max: true,
+            unit: Some("W".into()),
+        }),
+        ..Default::default()
+    }).unwrap();
+
+    engine.add_test_step(TestStep {
+        step_id: 3,
+        step_name: "Broken".into(),
+        execution_mode: ExecutionMode::Calculation,
+        calculations: vec![calc("x", "missing * 2", None)],
+        ..Default::default()
+    }).unwrap();
+
+    use catalytic::core::executor;
+    executor::spawn_slot(&engine, 0).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+
+    let slot = engine.get_slot(0).unwrap();
+    let guard = slot.read();
+    let results = &guard.step_results;
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[1].status, StepStatus::Passed, "{}", results[1].result_summary);
+    assert_eq!(results[1].unit.as_deref(), Some("W"));
+    assert!(results[1].variables.contains_key("vcorr"));
+    assert!(matches!(guard.variables.get("power"), Some(Variable::Float(p)) if (p - 1.625).abs() < 1e-9));
+
+    assert_eq!(results[2].status, StepStatus::Error);
+    assert!(results[2].result_summary.starts_with("计算失败"), "{}", results[2].result_summary);
+    assert!(results[2].raw_response.is_none());
+}
+
+// ========== 测试：表达式访问 SN、槽位号与内置函数 ==========
+#[test]
+fn test_expression_uses_slot_scope_and_functions() {
+    use catalytic::model::{Calculation, Variable};
+
+    let mut engine = create_test_engine();
+    engine.get_slot(0).unwrap().write().set_sn("P5-002A".into());
+
+    // SN 末四位为十六进制批次号
+    engine.add_test_step(TestStep {
+        step_id: 1,
+        step_name: "Batch".into(),
+        execution_mode: ExecutionMode::Calculation,
+        calculations: vec![Calculation {
+            target: "batch".into(),
+            expr: "hex(str::substring(sn, 3, 7))".into(),
+            unit: None,
+        }],
+        check_type: CheckType::Builtin,
+        check_rule: Some(CheckRule::Expression {
+            expr: "batch == 42 && slot_id == 0 && matches(sn, \"^P5-\") && len(sn) == 7".into(),
+        }),
+        ..Default::default()
+    }).unwrap();
+
+    use catalytic::core::executor;
+    executor::run_slot(&engine, 0).unwrap();
+
+    let slot = engine.get_slot(0).unwrap();
+    let guard = slot.read();
+    let result = &guard.step_results[0];
+    assert_eq!(result.status, StepStatus::Passed, "{}", result.result_summary);
+    assert!(matches!(guard.variables.get("batch"), Some(Variable::Int(42))));
+}
+
+// ========== 测试：自定义解析器与检查器 ==========
+#[test]
+fn test_custom_parser_and_checker() {
+    use std::sync::Arc;
+    use catalytic::checker::{CheckContext, CheckOutput};
+    use catalytic::core::{CustomChecker, CustomParser};
+    use catalytic::error::{EngineError, Result};
+    use catalytic::model::Variable;
+    use catalytic::parser::ParseOutput;
+
+    /// 统计响应中指定字符的个数
+    struct CharCount;
+
+    impl CustomParser for CharCount {
+        fn validate(&self, params: &serde_json::Value) -> Result<()> {
+            match params["char"].as_str().map(|c| c.chars().count()) {
+                Some(1) => Ok(()),
+                _ => Err(EngineError::ParseError("char 必须为单个字符".into())),
+            }
+        }
+
+        fn parse(&self, data: &[u8], params: &serde_json::Value) -> Result<ParseOutput> {
+            let c = params["char"].as_str().and_then(|c| c.chars().next()).unwrap_or_default();
+            let count = String::from_utf8_lossy(data).chars().filter(|x| *x == c).count();
+            Ok(ParseOutput::single(Variable::Int(count as i64)))
+        }
+    }
+
+    /// 判断当前值是否为偶数
+    struct Even;
+
+    impl CustomChecker for Even {
+        fn check(&self, _params: &serde_json::Value, ctx: &CheckContext) -> Result<CheckOutput> {
+            let actual = ctx.value.and_then(Variable::as_i64).unwrap_or(1);
+            Ok(CheckOutput {
+                passed: actual % 2 == 0,
+                template: "even".into(),
+                params: serde_json::json!({}),
+                actual: serde_json::json!(actual),
+                summary: format!("{} 为偶数", actual),
+                children: Vec::new(),
+            })
+        }
+    }
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    engine.register_engine_task_callback(mock_engine_task_instant, Arc::as_ptr(&registry) as *mut std::ffi::c_void);
+
+    let step = |params: serde_json::Value, checker: &str| TestStep {
+        step_id: 1,
+        step_name: "Custom".into(),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: "MockDevice".into(),
+            action_type: ActionType::Query,
+            payload: b"READ".to_vec(),
+            timeout_ms: 1000,
+            parse_rule: Some(ParseRule::Custom { name: "char_count".into(), params }),
+            ..Default::default()
+        }),
+        check_type: CheckType::Builtin,
+        check_rule: Some(CheckRule::Not {
+            rule: Box::new(CheckRule::Custom { name: checker.into(), params: serde_json::Value::Null }),
+        }),
+        ..Default::default()
+    };
+
+    // 未注册时配置校验失败
+    engine.add_test_step(step(serde_json::json!({"char": "S"}), "even")).unwrap();
+    let err = engine.validate_config().unwrap_err().to_string();
+    assert!(err.contains("自定义解析器 'char_count' 未注册") && err.contains("自定义检查器 'even' 未注册"), "{}", err);
+
+    engine.register_custom_parser("char_count", Arc::new(CharCount));
+    engine.register_custom_checker("even", Arc::new(Even));
+    engine.validate_config().unwrap();
+
+    // "SUCCESS" 中含 3 个 S，NOT even → PASS
+    use catalytic::core::executor;
+    executor::run_slot(&engine, 0).unwrap();
+    {
+        let slot = engine.get_slot(0).unwrap();
+        let guard = slot.read();
+        let result = &guard.step_results[0];
+        assert_eq!(result.status, StepStatus::Passed, "{}", result.result_summary);
+        assert_eq!(result.check_result.as_ref().unwrap().children[0].template, "even");
+    }
+
+    // 参数无效同样在校验期发现
+    engine.update_test_step(1, step(serde_json::json!({"char": "SS"}), "even")).unwrap();
+    let err = engine.validate_config().unwrap_err().to_string();
+    assert!(err.contains("参数无效") && err.contains("char 必须为单个字符"), "{}", err);
+}
+
+// --- EngineTask Mock 回调 (返回多值读数) ---
+extern "C" fn mock_engine_task_multi(
+    slot_id: u32,
+    task_id: u64,
+    _device: *const std::ffi::c_char,
+    _addr: *const std::ffi::c_char,
+    _proto: *const std::ffi::c_char,
+    _action: *const std::ffi::c_char,
+    _payload: *const u8,
+    _len: u32,
+    _timeout: u32,
+    user_data: *mut std::ffi::c_void,
+) -> i32 {
+    unsafe {
+        let registry = &*(user_data as *const TaskRegistry);
+        registry.submit(task_id, slot_id, TaskResult::Ok(b"V=3.31 I=0.52 T=25".to_vec()));
+    }
+    0
+}
+
+// ========== 测试：单次查询提取多个变量 ==========
+#[test]
+fn test_regex_fields_extract_multiple_variables() {
+    use std::sync::Arc;
+    use catalytic::model::{FieldMapping, ValueType};
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+    engine.register_engine_task_callback(mock_engine_task_multi, registry_ptr);
+
+    let field = |source: &str, save_to: &str, value_type, unit: Option<&str>| FieldMapping {
+        source: source.into(),
+        save_to: save_to.into(),
+        value_type,
+        unit: unit.map(Into::into),
+    };
+
+    let step = TestStep {
+        step_id: 1,
+        step_name: "Multi_Read".into(),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: "MockDevice".into(),
+            action_type: ActionType::Query,
+            payload: b"MEAS?".to_vec(),
+            timeout_ms: 1000,
+            parse_rule: Some(ParseRule::RegexFields {
+                pattern: r"V=(?P<v>[0-9.]+) I=(?P<i>[0-9.]+) T=(?P<t>\d+)".into(),
+                fields: vec![
+                    field("v", "vout", ValueType::Float, Some("V")),
+                    field("i", "iout", ValueType::Float, Some("A")),
+                    field("t", "temp", ValueType::Int, Some("C")),
+                ],
+            }),
+            ..Default::default()
+        }),
+        check_type: CheckType::Builtin,
+        check_rule: Some(CheckRule::Threshold {
+            variable: "iout".into(),
+            operator: CompareOp::Lt,
+            value: 1.0.into(),
+            unit: Some("A".into()),
+        }),
+        ..Default::default()
+    };
+    engine.add_test_step(step).unwrap();
+
+    use catalytic::core::executor;
+    executor::spawn_slot(&engine, 0).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+
+    let slot = engine.get_slot(0).unwrap();
+    let guard = slot.read();
+    let result = &guard.step_results[0];
+    assert_eq!(result.status, StepStatus::Passed);
+    assert_eq!(result.variables.len(), 3);
+    assert_eq!(result.variables["vout"].unit.as_deref(), Some("V"));
+    assert_eq!(result.variables["temp"].var_type, "int");
+
+    assert_eq!(guard.variables.get("vout").unwrap().as_f64(), Some(3.31));
+    assert_eq!(guard.variables.get("iout").unwrap().as_f64(), Some(0.52));
+    assert_eq!(guard.variables.get("temp").unwrap().as_i64(), Some(25));
+    assert_eq!(guard.variables.unit("iout"), Some("A"));
+}
+
+// ========== 测试：单步多条检查组合判定 ==========
+#[test]
+fn test_composite_check_rules() {
+    use std::sync::Arc;
+    use catalytic::model::{FieldMapping, ValueType};
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+    engine.register_engine_task_callback(mock_engine_task_multi, registry_ptr);
+
+    let field = |source: &str, save_to: &str| FieldMapping {
+        source: source.into(),
+        save_to: save_to.into(),
+        value_type: ValueType::Auto,
+        unit: None,
+    };
+    let threshold = |variable: &str, operator: CompareOp, value: f64| CheckRule::Threshold {
+        variable: variable.into(), operator, value: value.into(), unit: None,
+    };
+
+    let step = TestStep {
+        step_id: 1,
+        step_name: "Multi_Check".into(),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: "MockDevice".into(),
+            action_type: ActionType::Query,
+            payload: b"MEAS?".to_vec(),
+            timeout_ms: 1000,
+            parse_rule: Some(ParseRule::RegexFields {
+                pattern: r"V=(?P<v>[0-9.]+) I=(?P<i>[0-9.]+) T=(?P<t>\d+)".into(),
+                fields: vec![field("v", "vout"), field("i", "iout"), field("t", "temp")],
+            }),
+            ..Default::default()
+        }),
+        check_type: CheckType::Builtin,
+        check_rule: Some(CheckRule::All {
+            rules: vec![
+                CheckRule::RangeCheck {
+                    variable: Some("vout".into()), min: 3.2.into(), max: 3.4.into(), include_min: true, include_max: true, unit: None,
+                },
+                CheckRule::Any {
+                    rules: vec![
+                        threshold("temp", CompareOp::Gt, 100.0),
+                        CheckRule::Not { rule: Box::new(threshold("iout", CompareOp::Gt, 1.0)) },
+                    ],
+                },
+                threshold("temp", CompareOp::Gt, 30.0),
+            ],
+        }),
+        ..Default::default()
+    };
+    engine.add_test_step(step).unwrap();
+
+    use catalytic::core::executor;
+    executor::spawn_slot(&engine, 0).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+
+    let slot = engine.get_slot(0).unwrap();
+    let guard = slot.read();
+    let result = &guard.step_results[0];
+    assert_eq!(result.status, StepStatus::Failed);
+
+    let detail = result.check_result.as_ref().unwrap();
+    assert_eq!(detail.template, "all");
+    let verdicts: Vec<bool> = detail.children.iter().map(|c| c.passed).collect();
+    assert_eq!(verdicts, vec![true, true, false]);
+    assert_eq!(detail.children[1].children.len(), 2);
+    assert!(detail.children[1].children[1].passed);
+}
+
+// --- EngineTask Mock 回调 (返回二进制状态帧) ---
+extern "C" fn mock_engine_task_binary(
+    slot_id: u32,
+    task_id: u64,
+    _device: *const std::ffi::c_char,
+    _addr: *const std::ffi::c_char,
+    _proto: *const std::ffi::c_char,
+    _action: *const std::ffi::c_char,
+    _payload: *const u8,
+    _len: u32,
+    _timeout: u32,
+    user_data: *mut std::ffi::c_void,
+) -> i32 {
+    unsafe {
+        let registry = &*(user_data as *const TaskRegistry);
+        // 状态寄存器 0x80A4（非 UTF-8 字节）+ 温度 0x00FA（25.0 C，×0.1）
+        registry.submit(task_id, slot_id, TaskResult::Ok(vec![0x80, 0xa4, 0x00, 0xfa]));
+    }
+    0
+}
+
+// ========== 测试：二进制状态寄存器 + BitCheck ==========
+#[test]
+fn test_binary_parse_feeds_bit_check() {
+    use std::sync::Arc;
+    use catalytic::model::{BinaryField, BinaryType, Endian};
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+    engine.register_engine_task_callback(mock_engine_task_binary, registry_ptr);
+
+    let field = |save_to: &str, offset, scale| BinaryField {
+        save_to: save_to.into(),
+        offset,
+        data_type: BinaryType::U16,
+        endian: Endian::Big,
+        length: None,
+        bits: None,
+        scale,
+        bias: None,
+        unit: None,
+    };
+
+    let step = TestStep {
+        step_id: 1,
+        step_name: "Status_Register".into(),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: "MockDevice".into(),
+            action_type: ActionType::Query,
+            payload: vec![0x03, 0x00],
+            timeout_ms: 1000,
+            parse_rule: Some(ParseRule::Binary {
+                fields: vec![field("status", 0, None), field("temp", 2, Some(0.1))],
+            }),
+            ..Default::default()
+        }),
+        check_type: CheckType::Builtin,
+        check_rule: Some(CheckRule::BitCheck {
+            variable: "status".into(),
+            bit: 15,
+            value: 1,
+        }),
+        ..Default::default()
+    };
+    engine.add_test_step(step).unwrap();
+
+    use catalytic::core::executor;
+    executor::spawn_slot(&engine, 0).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+
+    let slot = engine.get_slot(0).unwrap();
+    let guard = slot.read();
+    assert_eq!(guard.step_results[0].status, StepStatus::Passed);
+    assert_eq!(guard.variables.get("status").unwrap().as_i64(), Some(0x80a4));
+    assert!((guard.variables.get("temp").unwrap().as_f64().unwrap() - 25.0).abs() < 1e-9);
+}
+
+// --- EngineTask Mock 回调 (返回 IEEE 488.2 二进制块) ---
+extern "C" fn mock_engine_task_block(
+    slot_id: u32,
+    task_id: u64,
+    _device: *const std::ffi::c_char,
+    _addr: *const std::ffi::c_char,
+    _proto: *const std::ffi::c_char,
+    _action: *const std::ffi::c_char,
+    _payload: *const u8,
+    _len: u32,
+    _timeout: u32,
+    user_data: *mut std::ffi::c_void,
+) -> i32 {
+    let mut block = b"#212".to_vec();
+    for v in [0.5f32, 1.0, 1.5] {
+        block.extend_from_slice(&v.to_be_bytes());
+    }
+    block.push(b'\n');
+
+    unsafe {
+        let registry = &*(user_data as *const TaskRegistry);
+        registry.submit(task_id, slot_id, TaskResult::Ok(block));
+    }
+    0
+}
+
+// ========== 测试：SCPI 二进制块下载为波形 ==========
+#[test]
+fn test_scpi_block_waveform_download() {
+    use std::sync::Arc;
+    use catalytic::model::{BinaryType, Endian, Variable};
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+    engine.register_engine_task_callback(mock_engine_task_block, registry_ptr);
+
+    let step = TestStep {
+        step_id: 1,
+        step_name: "Scope_Trace".into(),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: "MockDevice".into(),
+            action_type: ActionType::Query,
+            payload: b"CURV?".to_vec(),
+            timeout_ms: 1000,
+            parse_rule: Some(ParseRule::ScpiBlock { format: BinaryType::F32, byte_order: Endian::Big }),
+            ..Default::default()
+        }),
+        save_to: Some("waveform".into()),
+        ..Default::default()
+    };
+    engine.add_test_step(step).unwrap();
+
+    use catalytic::core::executor;
+    executor::spawn_slot(&engine, 0).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+
+    let slot = engine.get_slot(0).unwrap();
+    let guard = slot.read();
+    assert_eq!(guard.step_results[0].status, StepStatus::Passed);
+    match guard.variables.get("waveform") {
+        Some(Variable::FloatArray(points)) => assert_eq!(points, &vec![0.5, 1.0, 1.5]),
+        other => panic!("expected float array, got {:?}", other),
+    }
+}
+
+// ========== 测试：波形单调性与逐点限值检查 ==========
+#[test]
+fn test_waveform_checks_report_failing_indices() {
+    use std::sync::Arc;
+    use catalytic::model::{BinaryType, Endian, MonotonicDirection};
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+    engine.register_engine_task_callback(mock_engine_task_block, registry_ptr);
+
+    let waveform_step = |step_id: u32, check_rule: CheckRule| TestStep {
+        step_id,
+        step_name: format!("Scope_{}", step_id),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: "MockDevice".into(),
+            action_type: ActionType::Query,
+            payload: b"CURV?".to_vec(),
+            timeout_ms: 1000,
+            parse_rule: Some(ParseRule::ScpiBlock { format: BinaryType::F32, byte_order: Endian::Big }),
+            ..Default::default()
+        }),
+        check_type: CheckType::Builtin,
+        check_rule: Some(check_rule),
+        ..Default::default()
+    };
+    engine.add_test_step(waveform_step(1, CheckRule::Monotonic {
+        variable: None, direction: MonotonicDirection::Increasing, strict: true,
+    })).unwrap();
+    engine.add_test_step(waveform_step(2, CheckRule::WaveformMask {
+        variable: None, upper: Some(vec![1.0, 1.0, 1.0]), lower: None,
+    })).unwrap();
+
+    use catalytic::core::executor;
+    executor::spawn_slot(&engine, 0).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+
+    let slot = engine.get_slot(0).unwrap();
+    let guard = slot.read();
+    assert_eq!(guard.step_results[0].status, StepStatus::Passed);
+
+    let masked = &guard.step_results[1];
+    assert_eq!(masked.status, StepStatus::Failed);
+    let detail = masked.check_result.as_ref().unwrap();
+    assert_eq!(detail.template, "waveform_mask");
+    assert_eq!(detail.actual["failing_indices"], serde_json::json!([2]));
+}
+
+// --- EngineTask Mock 回调 (Modbus RTU 从站) ---
+extern "C" fn mock_engine_task_modbus(
+    slot_id: u32,
+    task_id: u64,
+    _device: *const std::ffi::c_char,
+    _addr: *const std::ffi::c_char,
+    _proto: *const std::ffi::c_char,
+    _action: *const std::ffi::c_char,
+    payload: *const u8,
+    len: u32,
+    _timeout: u32,
+    user_data: *mut std::ffi::c_void,
+) -> i32 {
+    use catalytic::protocol::modbus::crc16;
+
+    let request = unsafe { std::slice::from_raw_parts(payload, len as usize) };
+    // 期望：从站 0x11 读保持寄存器 0x006B 起 2 个
+    let mut response = if request == [0x11, 0x03, 0x00, 0x6B, 0x00, 0x02, 0xB7, 0x47] {
+        vec![0x11, 0x03, 0x04, 0x01, 0x4A, 0x80, 0x01]
+    } else {
+        vec![0x11, 0x83, 0x02]
+    };
+    let crc = crc16(&response);
+    response.extend_from_slice(&crc.to_le_bytes());
+
+    unsafe {
+        let registry = &*(user_data as *const TaskRegistry);
+        registry.submit(task_id, slot_id, TaskResult::Ok(response));
+    }
+    0
+}
+
+// ========== 测试：Modbus RTU 读寄存器 ==========
+#[test]
+fn test_modbus_rtu_read_registers() {
+    use std::sync::Arc;
+    use catalytic::model::{BinaryField, BinaryType, Endian, ModbusFunction, ModbusRequest, ModbusTransport};
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+    engine.register_engine_task_callback(mock_engine_task_modbus, registry_ptr);
+
+    let register = |save_to: &str, offset, scale| BinaryField {
+        save_to: save_to.into(),
+        offset,
+        data_type: BinaryType::U16,
+        endian: Endian::Big,
+        length: None,
+        bits: None,
+        scale,
+        bias: None,
+        unit: None,
+    };
+    let modbus_step = |step_id, address| TestStep {
+        step_id,
+        step_name: format!("Modbus_{}", step_id),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: "MockDevice".into(),
+            action_type: ActionType::Query,
+            timeout_ms: 1000,
+            modbus: Some(ModbusRequest {
+                transport: ModbusTransport::Rtu,
+                unit_id: 0x11,
+                function: ModbusFunction::ReadHoldingRegisters,
+                address,
+                count: 2,
+                values: vec![],
+            }),
+            parse_rule: Some(ParseRule::Modbus {
+                transport: ModbusTransport::Rtu,
+                unit_id: Some(0x11),
+                fields: vec![register("voltage", 0, Some(0.01)), register("status", 2, None)],
+            }),
+            ..Default::default()
+        }),
+        check_type: CheckType::Builtin,
+        check_rule: Some(CheckRule::BitCheck { variable: "status".into(), bit: 15, value: 1 }),
+        next_on_pass: Some(step_id + 1),
+        next_on_fail: Some(step_id + 1),
+        next_on_error: Some(step_id + 1),
+        ..Default::default()
+    };
+    engine.add_test_step(modbus_step(1, 0x006B)).unwrap();
+    // 地址错误时从站返回异常码，字段不会写入
+    let mut bad = modbus_step(2, 0x0100);
+    if let Some(ParseRule::Modbus { fields, .. }) = bad.engine_task.as_mut().and_then(|t| t.parse_rule.as_mut()) {
+        fields[1].save_to = "fault_status".into();
+    }
+    bad.check_rule = Some(CheckRule::BitCheck { variable: "fault_status".into(), bit: 15, value: 1 });
+    bad.next_on_pass = None;
+    bad.next_on_fail = None;
+    bad.next_on_error = None;
+    engine.add_test_step(bad).unwrap();
+
+    use catalytic::core::executor;
+    executor::spawn_slot(&engine, 0).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+
+    let slot = engine.get_slot(0).unwrap();
+    let guard = slot.read();
+    assert_eq!(guard.step_results.len(), 2);
+    assert_eq!(guard.step_results[0].status, StepStatus::Passed);
+    assert!((guard.variables.get("voltage").unwrap().as_f64().unwrap() - 3.30).abs() < 1e-9);
+    assert_eq!(guard.variables.get("status").unwrap().as_i64(), Some(0x8001));
+    assert_ne!(guard.step_results[1].status, StepStatus::Passed);
+    assert!(guard.variables.get("fault_status").is_none());
+}
+
+// --- EngineTask Mock 回调 (JSON 包裹的文本读数) ---
+extern "C" fn mock_engine_task_wrapped(
+    slot_id: u32,
+    task_id: u64,
+    _device: *const std::ffi::c_char,
+    _addr: *const std::ffi::c_char,
+    _proto: *const std::ffi::c_char,
+    _action: *const std::ffi::c_char,
+    _payload: *const u8,
+    _len: u32,
+    _timeout: u32,
+    user_data: *mut std::ffi::c_void,
+) -> i32 {
+    unsafe {
+        let registry = &*(user_data as *const TaskRegistry);
+        registry.submit(task_id, slot_id, TaskResult::Ok(br#"{"meas": "VOLT=0.0033012 V"}"#.to_vec()));
+    }
+    0
+}
+
+// ========== 测试：解析流水线与调试日志 ==========
+#[test]
+fn test_parse_pipeline_logs_stages() {
+    use std::sync::{Arc, Mutex};
+    use catalytic::model::Transform;
+
+    extern "C" fn collect_logs(
+        _ts: u64,
+        level: *const std::ffi::c_char,
+        _source: *const std::ffi::c_char,
+        message: *const std::ffi::c_char,
+        user_data: *mut std::ffi::c_void,
+    ) {
+        let logs = unsafe { &*(user_data as *const Mutex<Vec<String>>) };
+        let (level, message) = unsafe {
+            (std::ffi::CStr::from_ptr(level).to_string_lossy(), std::ffi::CStr::from_ptr(message).to_string_lossy())
+        };
+        if level == "debug" {
+            logs.lock().unwrap().push(message.into_owned());
+        }
+    }
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+    engine.register_engine_task_callback(mock_engine_task_wrapped, registry_ptr);
+    let logs: &Mutex<Vec<String>> = Box::leak(Box::new(Mutex::new(Vec::new())));
+    engine.register_log_callback(collect_logs, logs as *const _ as *mut std::ffi::c_void);
+
+    let step = TestStep {
+        step_id: 1,
+        step_name: "Voltage_mV".into(),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: "MockDevice".into(),
+            action_type: ActionType::Query,
+            payload: b"MEAS?".to_vec(),
+            timeout_ms: 1000,
+            parse_rule: Some(ParseRule::Pipeline {
+                steps: vec![
+                    Transform::Json { path: "$.meas".into() },
+                    Transform::Regex { pattern: r"VOLT=(\S+)".into(), group: 1 },
+                    Transform::Number,
+                    Transform::Scale { scale: 1000.0, bias: 0.0 },
+                    Transform::Round { digits: 2 },
+                ],
+            }),
+            ..Default::default()
+        }),
+        save_to: Some("mv".into()),
+        check_type: CheckType::Builtin,
+        check_rule: Some(CheckRule::RangeCheck { variable: None, min: 3.2.into(), max: 3.4.into(), include_min: true, include_max: true, unit: None }),
+        ..Default::default()
+    };
+    engine.add_test_step(step).unwrap();
+
+    use catalytic::core::executor;
+    executor::spawn_slot(&engine, 0).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+
+    let slot = engine.get_slot(0).unwrap();
+    let guard = slot.read();
+    assert_eq!(guard.step_results[0].status, StepStatus::Passed);
+    assert_eq!(guard.variables.get("mv").unwrap().as_f64(), Some(3.3));
+
+    let logs = logs.lock().unwrap();
+    assert_eq!(logs.len(), 5);
+    assert_eq!(logs[1], r#"Step 1 pipeline regex: "0.0033012""#);
+    assert_eq!(logs[4], "Step 1 pipeline round: 3.3");
+}
+
+// --- EngineTask Mock 回调 (带 SI 词头的读数) ---
+extern "C" fn mock_engine_task_millivolt(
+    slot_id: u32,
+    task_id: u64,
+    _device: *const std::ffi::c_char,
+    _addr: *const std::ffi::c_char,
+    _proto: *const std::ffi::c_char,
+    _action: *const std::ffi::c_char,
+    _payload: *const u8,
+    _len: u32,
+    _timeout: u32,
+    user_data: *mut std::ffi::c_void,
+) -> i32 {
+    unsafe {
+        let registry = &*(user_data as *const TaskRegistry);
+        registry.submit(task_id, slot_id, TaskResult::Ok(b"VOUT 3300.0 mV\n".to_vec()));
+    }
+    0
+}
+
+// ========== 测试：单位换算与单位不兼容检查 ==========
+#[test]
+fn test_units_normalized_and_checked() {
+    use std::sync::Arc;
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+    engine.register_engine_task_callback(mock_engine_task_millivolt, registry_ptr);
+
+    let step = |step_id: u32, check_unit: &str| TestStep {
+        step_id,
+        step_name: format!("Vout_{}", step_id),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: "MockDevice".into(),
+            action_type: ActionType::Query,
+            payload: b"MEAS:VOLT?".to_vec(),
+            timeout_ms: 1000,
+            parse_rule: Some(ParseRule::Quantity { unit: "V".into() }),
+            ..Default::default()
+        }),
+        save_to: Some("vout".into()),
+        check_type: CheckType::Builtin,
+        check_rule: Some(CheckRule::RangeCheck {
+            variable: Some("vout".into()),
+            min: 3200.0.into(),
+            max: 3400.0.into(),
+            include_min: true,
+            include_max: true,
+            unit: Some(check_unit.into()),
+        }),
+        next_on_pass: Some(step_id + 1),
+        next_on_fail: Some(step_id + 1),
+        next_on_error: Some(step_id + 1),
+        ..Default::default()
+    };
+    engine.add_test_step(step(1, "mV")).unwrap();
+    let mut mismatched = step(2, "mA");
+    mismatched.next_on_pass = None;
+    mismatched.next_on_fail = None;
+    mismatched.next_on_error = None;
+    engine.add_test_step(mismatched).unwrap();
+
+    use catalytic::core::executor;
+    executor::spawn_slot(&engine, 0).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+
+    let slot = engine.get_slot(0).unwrap();
+    let guard = slot.read();
+    assert_eq!(guard.step_results.len(), 2);
+
+    let first = &guard.step_results[0];
+    assert_eq!(first.status, StepStatus::Passed, "{}", first.result_summary);
+    assert_eq!(first.unit.as_deref(), Some("V"));
+    assert!(first.result_summary.contains("3300.0000 mV"), "{}", first.result_summary);
+    assert!((guard.variables.get("vout").unwrap().as_f64().unwrap() - 3.3).abs() < 1e-9);
+    assert_eq!(guard.variables.to_display_map()["vout"].unit.as_deref(), Some("V"));
+
+    let second = &guard.step_results[1];
+    assert_eq!(second.status, StepStatus::Error);
+    assert!(second.result_summary.contains("单位不兼容"), "{}", second.result_summary);
+}
+
+// --- EngineTask Mock 回调 (带单位读数) ---
+extern "C" fn mock_engine_task_volt_suffix(
+    slot_id: u32,
+    task_id: u64,
+    _device: *const std::ffi::c_char,
+    _addr: *const std::ffi::c_char,
+    _proto: *const std::ffi::c_char,
+    _action: *const std::ffi::c_char,
+    _payload: *const u8,
+    _len: u32,
+    _timeout: u32,
+    user_data: *mut std::ffi::c_void,
+) -> i32 {
+    unsafe {
+        let registry = &*(user_data as *const TaskRegistry);
+        registry.submit(task_id, slot_id, TaskResult::Ok(b"3.3V".to_vec()));
+    }
+    0
+}
+
+// ========== 测试：未声明单位的规则检查带单位读数 ==========
+#[test]
+fn test_unitless_rule_on_reading_with_unit() {
+    use std::sync::Arc;
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+    engine.register_engine_task_callback(mock_engine_task_volt_suffix, registry_ptr);
+
+    engine.add_test_step(TestStep {
+        step_id: 1,
+        step_name: "Vout".into(),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: "MockDevice".into(),
+            action_type: ActionType::Query,
+            payload: b"MEAS:VOLT?".to_vec(),
+            timeout_ms: 1000,
+            parse_rule: Some(ParseRule::Number),
+            ..Default::default()
+        }),
+        save_to: Some("vout".into()),
+        check_type: CheckType::Builtin,
+        check_rule: Some(CheckRule::RangeCheck {
+            variable: Some("vout".into()),
+            min: 3.2.into(),
+            max: 3.4.into(),
+            include_min: true,
+            include_max: true,
+            unit: None,
+        }),
+        ..Default::default()
+    }).unwrap();
+
+    use catalytic::core::executor;
+    executor::spawn_slot(&engine, 0).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+
+    let slot = engine.get_slot(0).unwrap();
+    let guard = slot.read();
+    assert_eq!(guard.step_results.len(), 1);
+    let result = &guard.step_results[0];
+    assert_eq!(result.status, StepStatus::Passed, "{}", result.result_summary);
+    assert_eq!(guard.variables.to_display_map()["vout"].unit.as_deref(), Some("V"));
+}
+
+// --- EngineTask Mock 回调 (*IDN? 响应) ---
+extern "C" fn mock_engine_task_idn(
+    slot_id: u32,
+    task_id: u64,
+    _device: *const std::ffi::c_char,
+    _addr: *const std::ffi::c_char,
+    _proto: *const std::ffi::c_char,
+    _action: *const std::ffi::c_char,
+    _payload: *const u8,
+    _len: u32,
+    _timeout: u32,
+    user_data: *mut std::ffi::c_void,
+) -> i32 {
+    unsafe {
+        let registry = &*(user_data as *const TaskRegistry);
+        registry.submit(task_id, slot_id, TaskResult::Ok(b"KEYSIGHT,34465A,MY54001234,A.03.01\n".to_vec()));
+    }
+    0
+}
+
+// ========== 测试：字符串变量与 Contains 检查 ==========
+#[test]
+fn test_string_variable_contains_check() {
+    use std::sync::Arc;
+    use catalytic::model::{ValueType, Variable};
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+    engine.register_engine_task_callback(mock_engine_task_idn, registry_ptr);
+
+    let step = TestStep {
+        step_id: 1,
+        step_name: "Firmware".into(),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: "MockDevice".into(),
+            action_type: ActionType::Query,
+            payload: b"*IDN?".to_vec(),
+            timeout_ms: 1000,
+            parse_rule: Some(ParseRule::ScpiList { index: Some(3), value_type: ValueType::String }),
+            ..Default::default()
+        }),
+        save_to: Some("fw".into()),
+        check_type: CheckType::Builtin,
+        check_rule: Some(CheckRule::Contains { variable: "fw".into(), substring: "A.03".into() }),
+        ..Default::default()
+    };
+    engine.add_test_step(step).unwrap();
+
+    use catalytic::core::executor;
+    executor::spawn_slot(&engine, 0).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+
+    let slot = engine.get_slot(0).unwrap();
+    let guard = slot.read();
+    let result = &guard.step_results[0];
+    assert_eq!(result.status, StepStatus::Passed);
+    assert_eq!(result.result_summary, "'A.03.01' 包含 'A.03' → PASS");
+    assert!(matches!(guard.variables.get("fw"), Some(Variable::String(s)) if s == "A.03.01"));
+
+    let display = &guard.variables.to_display_map()["fw"];
+    assert_eq!((display.value.as_str(), display.var_type.as_str()), ("A.03.01", "string"));
+}
+
+// ========== 测试：字符串正则、枚举与版本号检查 ==========
+#[test]
+fn test_string_regex_and_version_checks() {
+    use std::sync::Arc;
+    use catalytic::model::{CompareOp, ValueType};
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+    engine.register_engine_task_callback(mock_engine_task_idn, registry_ptr);
+
+    let idn_step = |step_id: u32, index: usize, save_to: &str, rule: CheckRule| TestStep {
+        step_id,
+        step_name: format!("IDN-{}", save_to),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: "MockDevice".into(),
+            action_type: ActionType::Query,
+            payload: b"*IDN?".to_vec(),
+            timeout_ms: 1000,
+            parse_rule: Some(ParseRule::ScpiList { index: Some(index), value_type: ValueType::String }),
+            ..Default::default()
+        }),
+        save_to: Some(save_to.into()),
+        check_type: CheckType::Builtin,
+        check_rule: Some(rule),
+        ..Default::default()
+    };
+
+    engine.add_test_step(idn_step(1, 0, "vendor", CheckRule::OneOf {
+        variable: "vendor".into(),
+        values: vec!["Keysight".into(), "Agilent".into()],
+        ignore_case: true,
+    })).unwrap();
+    engine.add_test_step(idn_step(2, 2, "serial", CheckRule::RegexMatch {
+        variable: "serial".into(),
+        pattern: r"^MY\d{8}$".into(),
+    })).unwrap();
+    engine.add_test_step(idn_step(3, 3, "fw", CheckRule::Version {
+        variable: "fw".into(),
+        operator: CompareOp::Gte,
+        version: "A.03.02".into(),
+    })).unwrap();
+
+    use catalytic::core::executor;
+    executor::spawn_slot(&engine, 0).unwrap();
+    std::thread::sleep(Duration::from_millis(500));
+
+    let slot = engine.get_slot(0).unwrap();
+    let guard = slot.read();
+    let results = &guard.step_results;
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].status, StepStatus::Passed);
+    assert_eq!(results[0].result_summary, "'KEYSIGHT' ∈ {Keysight, Agilent} → PASS");
+    assert_eq!(results[1].status, StepStatus::Passed);
+    assert_eq!(results[2].status, StepStatus::Failed);
+    assert_eq!(results[2].result_summary, "A.03.01 >= A.03.02 → FAIL");
+}
+
+// --- 外部检查 Mock 回调（步骤 1 立即判定，其余不提交） ---
+extern "C" fn mock_external_check(
+    slot_id: u32,
+    task_id: u64,
+    step_id: u32,
+    value_json: *const std::ffi::c_char,
+    _variables_json: *const std::ffi::c_char,
+    _timeout: u32,
+    user_data: *mut std::ffi::c_void,
+) -> i32 {
+    use catalytic::ffi::submit::cat_engine_submit_check_result;
+
+    if step_id != 1 {
+        return 0;
+    }
+    unsafe {
+        let value: serde_json::Value =
+            serde_json::from_str(std::ffi::CStr::from_ptr(value_json).to_str().unwrap()).unwrap();
+        let passed = value["value"] == "34465A";
+        let actual = std::ffi::CString::new(value["value"].to_string()).unwrap();
+        let summary = std::ffi::CString::new("型号匹配").unwrap();
+        cat_engine_submit_check_result(
+            user_data as *const CatEngine, slot_id, task_id, passed, actual.as_ptr(), summary.as_ptr(),
+        );
+    }
+    0
+}
+
+// ========== 测试：外部检查由 Host 判定并参与超时处理 ==========
+#[test]
+fn test_external_check_callback() {
+    use std::sync::Arc;
+    use catalytic::model::ValueType;
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+    engine.register_engine_task_callback(mock_engine_task_idn, registry_ptr);
+    let engine_ptr = &engine as *const CatEngine as *mut std::ffi::c_void;
+    engine.register_external_check_callback(mock_external_check, engine_ptr);
+
+    for step_id in 1..=2 {
+        engine.add_test_step(TestStep {
+            step_id,
+            step_name: format!("Model-{}", step_id),
+            execution_mode: ExecutionMode::EngineControlled,
+            engine_task: Some(EngineTask {
+                target_device: "MockDevice".into(),
+                action_type: ActionType::Query,
+                payload: b"*IDN?".to_vec(),
+                timeout_ms: 1000,
+                parse_rule: Some(ParseRule::ScpiList { index: Some(1), value_type: ValueType::String }),
+                ..Default::default()
+            }),
+            check_type: CheckType::External,
+            check_timeout_ms: Some(100),
+            next_on_timeout: Some(step_id + 1),
+            ..Default::default()
+        }).unwrap();
+    }
+
+    use catalytic::core::executor;
+    executor::spawn_slot(&engine, 0).unwrap();
+    std::thread::sleep(Duration::from_millis(600));
+
+    let slot = engine.get_slot(0).unwrap();
+    let guard = slot.read();
+    let results = &guard.step_results;
+    assert_eq!(results.len(), 2);
+
+    assert_eq!(results[0].status, StepStatus::Passed);
+    assert_eq!(results[0].result_summary, "型号匹配");
+    let detail = results[0].check_result.as_ref().unwrap();
+    assert_eq!((detail.template.as_str(), &detail.actual), ("external", &serde_json::json!("34465A")));
+
+    assert_eq!(results[1].status, StepStatus::Timeout);
+    assert_eq!(results[1].result_summary, "外部检查超时");
+}
+
+// ========== 测试：解析失败给出错误结果与原始响应 ==========
+#[test]
+fn test_parse_failure_reports_raw_response() {
+    use std::sync::Arc;
+    use catalytic::model::ParseErrorPolicy;
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+    engine.register_engine_task_callback(mock_engine_task_instant, registry_ptr);
+
+    // "SUCCESS" 中没有数字
+    let step = |step_id: u32, policy: ParseErrorPolicy| TestStep {
+        step_id,
+        step_name: format!("Read_{}", step_id),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: "MockDevice".into(),
+            action_type: ActionType::Query,
+            payload: b"READ?".to_vec(),
+            timeout_ms: 1000,
+            parse_rule: Some(ParseRule::Number),
+            ..Default::default()
+        }),
+        save_to: Some("reading".into()),
+        check_type: CheckType::Builtin,
+        check_rule: Some(CheckRule::RangeCheck {
+            variable: None, min: 0.0.into(), max: 1.0.into(), include_min: true, include_max: true, unit: None,
+        }),
+        on_parse_error: policy,
+        ..Default::default()
+    };
+    let mut first = step(1, ParseErrorPolicy::Error);
+    first.next_on_error = Some(2);
+    engine.add_test_step(first).unwrap();
+    engine.add_test_step(step(2, ParseErrorPolicy::Fail)).unwrap();
+
+    use catalytic::core::executor;
+    executor::spawn_slot(&engine, 0).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+
+    let slot = engine.get_slot(0).unwrap();
+    let guard = slot.read();
+    assert_eq!(guard.step_results.len(), 2);
+
+    let errored = &guard.step_results[0];
+    assert_eq!(errored.status, StepStatus::Error);
+    assert!(errored.error_message.as_deref().unwrap().contains("未找到数字"), "{:?}", errored.error_message);
+    let raw = errored.raw_response.as_ref().expect("raw response attached");
+    assert_eq!((raw.text.as_str(), raw.hex.as_str()), ("SUCCESS", "53554343455353"));
+    assert!(!raw.truncated);
+
+    assert_eq!(guard.step_results[1].status, StepStatus::Failed);
+    assert!(guard.step_results[1].raw_response.is_some());
+    assert!(guard.variables.get("reading").is_none());
+}
@@ -123,14 +123,15 @@ fn test_full_flow_simulation() {
             timeout_ms: 1000,
             parse_rule: Some(ParseRule::Regex { 
                 pattern: r"([^,]+),\s*([^,]+)".into(), 
-                group: 1 
+                group: 1,
+                value_type: Default::default(),
             }),
             ..Default::default()
         }),
         save_to: Some("voltage".into()),
         check_type: CheckType::Builtin,
         check_rule: Some(CheckRule::RangeCheck {
-            variable: None, min: 0.09, max: 0.11, include_min: true, include_max: true
+            variable: None, min: 0.09.into(), max: 0.11.into(), include_min: true, include_max: true, unit: None
         }),
         next_on_pass: Some(2),
         ..Default::default()
@@ -149,14 +150,15 @@ fn test_full_flow_simulation() {
             timeout_ms: 1000,
             parse_rule: Some(ParseRule::Regex { 
                 pattern: r"([^,]+),\s*([^,]+)".into(), 
-                group: 2 
+                group: 2,
+                value_type: Default::default(),
             }),
             ..Default::default()
         }),
         save_to: Some("current".into()),
         check_type: CheckType::Builtin,
         check_rule: Some(CheckRule::Threshold {
-            variable: "current".into(), operator: CompareOp::Gt, value: 200.0
+            variable: "current".into(), operator: CompareOp::Gt, value: 200.0.into(), unit: None
         }),
         ..Default::default()
     };
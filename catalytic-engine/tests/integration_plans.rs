@@ -0,0 +1,334 @@
+//! 测试方案相关集成测试
+//! 包括：命名方案、槽位/SN 方案分配、方案包导入导出、配置迁移
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use catalytic::core::engine::CatEngine;
+use catalytic::core::executor;
+use catalytic::core::task::{TaskRegistry, TaskResult};
+use catalytic::model::{
+    TestStep, TestPlan, ExecutionMode, EngineTask, ActionType, DeviceType, DeviceInstance,
+    SlotStatus, StepStatus,
+};
+
+// --- EngineTask Mock 回调 (立即返回) ---
+extern "C" fn mock_engine_task_instant(
+    slot_id: u32,
+    task_id: u64,
+    _device: *const std::ffi::c_char,
+    _addr: *const std::ffi::c_char,
+    _proto: *const std::ffi::c_char,
+    _action: *const std::ffi::c_char,
+    _payload: *const u8,
+    _len: u32,
+    _timeout: u32,
+    user_data: *mut std::ffi::c_void,
+) -> i32 {
+    unsafe {
+        let registry = &*(user_data as *const TaskRegistry);
+        registry.submit(task_id, slot_id, TaskResult::Ok(b"OK".to_vec()));
+    }
+    0
+}
+
+// 辅助函数：创建双槽位引擎
+fn create_test_engine() -> CatEngine {
+    let mut engine = CatEngine::new(2).unwrap();
+
+    let device_type = DeviceType {
+        type_name: "MockDevice".into(),
+        name: "Mock Device".into(),
+        plugin_id: "mock.plugin".into(),
+        instances: vec![],
+        commands: vec![],
+    };
+    engine.add_device_type("MockDevice".into(), device_type).unwrap();
+    engine.add_device_instance("MockDevice", DeviceInstance {
+        id: "mock_inst".into(),
+        name: "MockInst".into(),
+        address: "mock://test".into(),
+        ..Default::default()
+    }).unwrap();
+
+    for slot_id in 0..2 {
+        let mut binding = HashMap::new();
+        binding.insert("MockDevice".into(), vec!["mock_inst".into()]);
+        engine.set_slot_binding(slot_id, binding).unwrap();
+    }
+
+    engine
+}
+
+// 辅助函数：创建单步骤方案
+fn single_step(step_id: u32, name: &str) -> TestStep {
+    TestStep {
+        step_id,
+        step_name: name.into(),
+        execution_mode: ExecutionMode::EngineControlled,
+        engine_task: Some(EngineTask {
+            target_device: "MockDevice".into(),
+            action_type: ActionType::Query,
+            payload: b"CMD".to_vec(),
+            timeout_ms: 1000,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+// ========== 测试：槽位与 SN 前缀方案选择 ==========
+#[test]
+fn test_slot_and_sn_plan_selection() {
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+    engine.register_engine_task_callback(mock_engine_task_instant, registry_ptr);
+
+    engine.add_test_step(single_step(1, "Default_Step")).unwrap();
+    engine.add_test_plan("product_a".into(), TestPlan {
+        steps: vec![single_step(10, "ProductA_Step")],
+        ..Default::default()
+    }).unwrap();
+    engine.add_test_plan("product_b".into(), TestPlan {
+        steps: vec![single_step(20, "ProductB_Step")],
+        ..Default::default()
+    }).unwrap();
+
+    engine.set_slot_plan(0, Some("product_a".into())).unwrap();
+    engine.set_sn_plan_rule("PB".into(), Some("product_b".into())).unwrap();
+    engine.get_slot(1).unwrap().write().set_sn("PB-0001".into());
+
+    executor::spawn_slot(&engine, 0).unwrap();
+    executor::spawn_slot(&engine, 1).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+
+    for (slot_id, plan, step_name) in [(0, "product_a", "ProductA_Step"), (1, "product_b", "ProductB_Step")] {
+        let slot = engine.get_slot(slot_id).unwrap();
+        let guard = slot.read();
+        assert_eq!(guard.status(), SlotStatus::Completed);
+        assert_eq!(guard.plan_name.as_deref(), Some(plan));
+        assert_eq!(guard.step_results.len(), 1);
+        assert_eq!(guard.step_results[0].step_name, step_name);
+        assert_eq!(guard.step_results[0].status, StepStatus::Passed);
+    }
+}
+
+// ========== 测试：分配不存在的方案 / 移除方案 ==========
+#[test]
+fn test_plan_assignment_validation() {
+    let mut engine = create_test_engine();
+    engine.add_test_step(single_step(1, "Default_Step")).unwrap();
+
+    assert!(engine.set_slot_plan(0, Some("missing".into())).is_err());
+
+    engine.add_test_plan("product_a".into(), TestPlan {
+        steps: vec![single_step(10, "ProductA_Step")],
+        ..Default::default()
+    }).unwrap();
+    engine.set_slot_plan(0, Some("product_a".into())).unwrap();
+    assert_eq!(engine.resolve_slot_plan(0).unwrap().0.as_deref(), Some("product_a"));
+
+    // 缩减槽位时清除被删除槽位的分配，重新扩容后不再生效
+    engine.set_slot_plan(1, Some("product_a".into())).unwrap();
+    engine.set_slot_count(1).unwrap();
+    assert!(!engine.get_plan_assignment().slots.contains_key(&1));
+    engine.set_slot_count(2).unwrap();
+    assert!(engine.resolve_slot_plan(1).unwrap().0.is_none());
+    assert_eq!(engine.resolve_slot_plan(0).unwrap().0.as_deref(), Some("product_a"));
+
+    // 移除方案后回退到默认步骤
+    engine.remove_test_plan("product_a").unwrap();
+    let (plan_name, steps) = engine.resolve_slot_plan(0).unwrap();
+    assert!(plan_name.is_none());
+    assert_eq!(steps[0].step_name, "Default_Step");
+}
+
+// ========== 测试：方案包导出 / 导入 ==========
+#[test]
+fn test_package_export_import_roundtrip() {
+    use catalytic::model::{CheckRule, CheckType, PackageMetadata};
+
+    let mut source = create_test_engine();
+    let mut step = single_step(10, "ProductA_Step");
+    step.check_type = CheckType::Builtin;
+    step.check_rule = Some(CheckRule::RangeCheck {
+        variable: None, min: 1.0.into(), max: 2.0.into(), include_min: true, include_max: true, unit: None,
+    });
+    source.add_test_plan("product_a".into(), TestPlan {
+        steps: vec![step],
+        ..Default::default()
+    }).unwrap();
+
+    let package = source.export_package(Some("product_a"), PackageMetadata::default()).unwrap();
+    assert!(package.verify());
+    assert!(package.device_types["MockDevice"].instances.is_empty());
+    assert_eq!(package.limits.len(), 1);
+
+    // 目标工位已有同类型设备实例，导入后应保留
+    let mut target = create_test_engine();
+    let json = serde_json::to_string(&package).unwrap();
+    let report = target.import_package(&json).unwrap();
+    assert!(!report.changes.is_empty());
+    assert_eq!(target.get_test_plan("product_a").unwrap().steps.len(), 1);
+    assert_eq!(target.get_device_type("MockDevice").unwrap().instances.len(), 1);
+
+    // 篡改后的方案包应被拒绝
+    let tampered = json.replace("ProductA_Step", "Tampered_Step");
+    assert!(target.import_package(&tampered).is_err());
+}
+
+// ========== 测试：方案包携带步骤引用的命名限值 ==========
+#[test]
+fn test_package_bundles_named_limits() {
+    use catalytic::model::{CheckRule, CheckType, LimitConfig, LimitValue, PackageMetadata, WaveformStatistic};
+
+    let mut source = create_test_engine();
+    let mut limits = LimitConfig::default();
+    limits.import_csv("name,value,variant,slot\nvmax,2.0,,\nvmin,1.0,,\nunused,9,,\nvmax,2.5,,1\n").unwrap();
+    source.set_limit_config(limits).unwrap();
+
+    let mut step = single_step(10, "ProductA_Step");
+    step.check_type = CheckType::Builtin;
+    step.check_rule = Some(CheckRule::All { rules: vec![
+        CheckRule::RangeCheck {
+            variable: None, min: LimitValue::Ref("@vmin".into()), max: LimitValue::Ref("@vmax".into()),
+            include_min: true, include_max: true, unit: None,
+        },
+        CheckRule::WaveformStat {
+            variable: Some("wave".into()), statistic: WaveformStatistic::Max,
+            min: 0.0.into(), max: LimitValue::Ref("@vmax".into()), unit: None,
+        },
+    ] });
+    source.add_test_plan("product_a".into(), TestPlan {
+        steps: vec![step],
+        ..Default::default()
+    }).unwrap();
+
+    let package = source.export_package(Some("product_a"), PackageMetadata::default()).unwrap();
+    assert_eq!(package.named_limits.constants.len(), 2);
+    assert!(!package.named_limits.defines("unused"));
+
+    // 目标工位没有这些限值，导入后可直接解析
+    let mut target = create_test_engine();
+    let report = target.import_package(&serde_json::to_string(&package).unwrap()).unwrap();
+    assert!(report.changes.iter().any(|c| c.contains("命名限值")), "{:?}", report.changes);
+    let resolved = target.get_limit_config().resolve(1, None);
+    assert_eq!((resolved["vmin"], resolved["vmax"]), (1.0, 2.5));
+    assert!(!target.get_limit_config().defines("unused"));
+}
+
+// ========== 测试：旧版本配置加载时迁移 ==========
+#[test]
+fn test_storage_migrates_legacy_config() {
+    use catalytic::storage::Storage;
+
+    let dir = std::env::temp_dir().join(format!("catalytic_migrate_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let db_path = dir.join("engine.db");
+    let _ = std::fs::remove_file(&db_path);
+
+    // 写入无 schema_version、check_rule 缺少 check_type 的旧配置
+    {
+        let storage = Storage::open(db_path.to_str().unwrap()).unwrap();
+        let legacy = serde_json::json!({
+            "slot_count": 1,
+            "test_steps": [{
+                "step_id": 1, "step_name": "legacy", "execution_mode": "engine_controlled",
+                "check_rule": {"template": "range_check", "min": 1.0, "max": 2.0}
+            }]
+        });
+        storage.save_config("full_config", legacy.to_string().as_bytes()).unwrap();
+    }
+
+    let mut engine = CatEngine::new(1).unwrap();
+    engine.set_data_path(dir.to_str().unwrap()).unwrap();
+
+    let report = engine.last_migration_report().unwrap();
+    assert_eq!(report.from_version, 1);
+    assert_eq!(engine.get_test_steps()[0].check_type, catalytic::model::CheckType::None);
+    assert!(report.changes.iter().any(|c| c.contains("check_type")), "{:?}", report.changes);
+
+    drop(engine);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+// --- EngineTask Mock 回调 (返回电压读数) ---
+extern "C" fn mock_engine_task_voltage(
+    slot_id: u32,
+    task_id: u64,
+    _device: *const std::ffi::c_char,
+    _addr: *const std::ffi::c_char,
+    _proto: *const std::ffi::c_char,
+    _action: *const std::ffi::c_char,
+    _payload: *const u8,
+    _len: u32,
+    _timeout: u32,
+    user_data: *mut std::ffi::c_void,
+) -> i32 {
+    unsafe {
+        let registry = &*(user_data as *const TaskRegistry);
+        registry.submit(task_id, slot_id, TaskResult::Ok(b"3.45".to_vec()));
+    }
+    0
+}
+
+// ========== 测试：按产品变体选择外部限值表 ==========
+#[test]
+fn test_limit_tables_per_variant() {
+    use catalytic::model::{CheckRule, CheckType, LimitConfig, LimitValue, ParseRule};
+
+    let mut engine = create_test_engine();
+    let registry = engine.task_registry();
+    let registry_ptr = Arc::as_ptr(&registry) as *mut std::ffi::c_void;
+    engine.register_engine_task_callback(mock_engine_task_voltage, registry_ptr);
+
+    let limits: LimitConfig = serde_json::from_value(serde_json::json!({
+        "variants": {"SKU_HI": {"sn_prefixes": ["HI"]}}
+    })).unwrap();
+    engine.set_limit_config(limits).unwrap();
+    let csv = "name,value,variant\nvmin,3.0,\nvmax,3.3,\nvmax,3.6,SKU_HI\n";
+    assert_eq!(engine.import_limits_csv(csv).unwrap(), 3);
+    assert!(engine.import_limits_csv("name,value\nvmax,high").is_err());
+
+    let mut step = single_step(1, "Vout");
+    step.engine_task.as_mut().unwrap().parse_rule = Some(ParseRule::Number);
+    step.check_type = CheckType::Builtin;
+    step.check_rule = Some(CheckRule::RangeCheck {
+        variable: None,
+        min: LimitValue::Ref("@vmin".into()),
+        max: LimitValue::Ref("@vmax".into()),
+        include_min: true,
+        include_max: true,
+        unit: None,
+    });
+    engine.add_test_step(step.clone()).unwrap();
+    engine.validate_config().unwrap();
+
+    engine.get_slot(0).unwrap().write().set_sn("LO-0001".into());
+    engine.get_slot(1).unwrap().write().set_sn("HI-0001".into());
+    executor::spawn_slot(&engine, 0).unwrap();
+    executor::spawn_slot(&engine, 1).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+
+    for (slot_id, status, vmax) in [(0, StepStatus::Failed, 3.3), (1, StepStatus::Passed, 3.6)] {
+        let slot = engine.get_slot(slot_id).unwrap();
+        let guard = slot.read();
+        let result = &guard.step_results[0];
+        assert_eq!(result.status, status);
+        assert_eq!(result.check_result.as_ref().unwrap().params["max"], vmax);
+    }
+
+    // 引用未定义的限值：提交时校验失败
+    engine.begin_transaction().unwrap();
+    step.check_rule = Some(CheckRule::Threshold {
+        variable: "vout".into(),
+        operator: catalytic::model::CompareOp::Lt,
+        value: LimitValue::Ref("@imax".into()),
+        unit: None,
+    });
+    engine.update_test_step(1, step).unwrap();
+    let err = engine.commit_transaction().unwrap_err().to_string();
+    assert!(err.contains("@imax"), "{}", err);
+}